@@ -1,15 +1,43 @@
 use std::{str::FromStr, sync::Arc};
 
+use futures::{
+    Stream, StreamExt,
+    channel::{mpsc, oneshot},
+};
+use serde_json::Value;
 use solana_client::{
-    nonblocking::rpc_client::RpcClient, 
+    client_error::ClientError,
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient}, 
     rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{RpcTransactionLogsConfig, RpcTransactionLogsFilter},
+    rpc_request::RpcError,
+};
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::pubkey::Pubkey;
+
+use crate::global::STAKE_PROGRAM_ID;
+use crate::trade::{DEFAULT_CONTAINS_CONCURRENCY, Trade, info::TransactionInfo};
+use crate::types::AccountInfo;
+use crate::tool::{
+    is_valid_pubkey, lamports_to_sol, now_unix_secs,
+    rpc::{signature_limit, websocket_url},
 };
-use solana_sdk::{native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
+
+/// A program log notification received from `Account::subscribe_logs`
+#[derive(Debug, Clone)]
+pub struct LogsNotification {
+    pub signature: String,
+    pub slot: u64,
+    pub err: Option<Value>, // Raw error information, None if the transaction succeeded
+    pub logs: Vec<String>,
+}
 
 /// Account analysis structure for querying and analyzing Solana account information
 pub struct Account {
     client: Arc<RpcClient>,
     address: String,
+    // commitment of the account reads and log subscriptions
+    commitment: CommitmentConfig,
 }
 
 impl Account {
@@ -20,14 +48,35 @@ impl Account {
     /// * `address` - Solana account address to analyze
     /// 
     /// # Returns
-    /// New Account instance
+    /// New Account instance, reading at the client's commitment
     pub fn new(client: Arc<RpcClient>, address: &str) -> Self {
+        let commitment = client.commitment();
         Self {
             client: client,
             address: address.to_string(),
+            commitment,
         }
     }
 
+    /// Sets the commitment of the account reads and log subscriptions
+    /// 
+    /// Use `processed` to read your own writes right after sending a transaction,
+    /// before it is confirmed, and `finalized` for balances that must never roll back
+    /// 
+    /// # Example
+    /// ```ignore
+    /// let account = solana.create_account("address").with_commitment(CommitmentConfig::processed());
+    /// ```
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// Gets the commitment of the account reads
+    pub fn commitment(&self) -> CommitmentConfig {
+        self.commitment
+    }
+
     /// Updates the account address for this instance
     /// 
     /// # Arguments
@@ -36,6 +85,83 @@ impl Account {
         self.address = address.to_string();
     }
 
+    /// Streams the logs of every transaction that mentions an address
+    /// 
+    /// The websocket url is derived from the rpc url; the subscription is dropped
+    /// together with the returned stream
+    /// 
+    /// # Arguments
+    /// * `mentions` - Address the transactions must mention (e.g. a program or pool)
+    /// 
+    /// # Returns
+    /// * `Ok(Stream)` - Log notifications with signature, error and logs
+    /// * `Err(String)` - Error message if the address is invalid or the subscription fails
+    pub async fn subscribe_logs(
+        &self,
+        mentions: &str,
+    ) -> Result<impl Stream<Item = LogsNotification> + use<>, String> {
+        if !is_valid_pubkey(mentions) {
+            return Err(format!("Invalid address format: {}", mentions));
+        }
+        let pubsub = PubsubClient::new(websocket_url(&self.client.url()).as_str())
+            .await
+            .map_err(|e| format!("Failed to connect websocket: {:?}", e))?;
+        let filter = RpcTransactionLogsFilter::Mentions(vec![mentions.to_string()]);
+        let config = RpcTransactionLogsConfig {
+            commitment: Some(self.commitment),
+        };
+        let (sender, receiver) = mpsc::unbounded();
+        let (ready_sender, ready_receiver) = oneshot::channel();
+        tokio::spawn(async move {
+            let (mut notifications, unsubscribe) = match pubsub.logs_subscribe(filter, config).await {
+                Ok(subscription) => {
+                    let _ = ready_sender.send(Ok(()));
+                    subscription
+                }
+                Err(e) => {
+                    let _ = ready_sender.send(Err(format!("Failed to subscribe logs: {:?}", e)));
+                    return;
+                }
+            };
+            while let Some(response) = notifications.next().await {
+                let notification = LogsNotification {
+                    signature: response.value.signature,
+                    slot: response.context.slot,
+                    err: response.value.err.and_then(|err| serde_json::to_value(err).ok()),
+                    logs: response.value.logs,
+                };
+                // the receiver was dropped
+                if sender.unbounded_send(notification).is_err() {
+                    break;
+                }
+            }
+            drop(notifications);
+            unsubscribe().await;
+        });
+        ready_receiver
+            .await
+            .map_err(|_| "Log subscription stopped".to_string())??;
+        Ok(receiver)
+    }
+
+    /// Parses the account address
+    fn pubkey(&self) -> Result<Pubkey, String> {
+        Pubkey::from_str(&self.address).map_err(|e| format!("Invalid address format: {:?}", e))
+    }
+
+    /// Reads the account at `commitment`, a missing account is an error
+    async fn fetch_account(
+        &self,
+        pubkey: &Pubkey,
+        commitment: CommitmentConfig,
+    ) -> Result<solana_sdk::account::Account, ClientError> {
+        self.client
+            .get_account_with_commitment(pubkey, commitment)
+            .await?
+            .value
+            .ok_or_else(|| RpcError::ForUser(format!("AccountNotFound: pubkey={}", pubkey)).into())
+    }
+
     /// Parses and returns comprehensive account information as a formatted string
     /// 
     /// # Returns
@@ -58,11 +184,10 @@ impl Account {
     /// * `Ok(String)` - Formatted balance information (lamports and SOL)
     /// * `Err(String)` - Error message if balance query fails
     pub async fn get_balance_info(&self) -> Result<String, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
-        let account = self.client.get_account(&pubkey).await
+        let pubkey = self.pubkey()?;
+        let account = self.fetch_account(&pubkey, self.commitment).await
             .map_err(|e| format!("Failed to get account: {:?}", e))?;
-        let balance_sol = account.lamports as f64 / LAMPORTS_PER_SOL as f64;
+        let balance_sol = lamports_to_sol(account.lamports);
         Ok(format!(
             "Balance: {} lamports\nSOL: {}",
             account.lamports, balance_sol
@@ -85,15 +210,47 @@ impl Account {
         ))
     }
 
+    /// Gets the full account: lamports, owner program, executable flag, rent epoch and data
+    /// 
+    /// # Returns
+    /// * `Ok(AccountInfo)` - Account state
+    /// * `Err(String)` - Error message if the account does not exist or the query fails
+    pub async fn get_account_info(&self) -> Result<AccountInfo, String> {
+        self.get_account_info_with_commitment(self.commitment).await
+    }
+
+    /// Gets the full account at `commitment` instead of the account's own
+    /// 
+    /// # Arguments
+    /// * `commitment` - e.g. `processed` to see a transaction just sent, `finalized` for accounting
+    /// 
+    /// # Returns
+    /// * `Ok(AccountInfo)` - Account state
+    /// * `Err(String)` - Error message if the account does not exist or the query fails
+    pub async fn get_account_info_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> Result<AccountInfo, String> {
+        let pubkey = self.pubkey()?;
+        self.fetch_account(&pubkey, commitment).await
+            .map(|account| AccountInfo {
+                lamports: account.lamports,
+                owner: account.owner.to_string(),
+                executable: account.executable,
+                rent_epoch: account.rent_epoch,
+                data: account.data,
+            })
+            .map_err(|e| format!("Failed to get account information: {:?}", e))
+    }
+
     /// Checks if the account is an executable program
     /// 
     /// # Returns
     /// * `Ok(bool)` - True if account is executable, false otherwise
     /// * `Err(String)` - Error message if query fails
     pub async fn is_executable(&self) -> Result<bool, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
-        self.client.get_account(&pubkey).await
+        let pubkey = self.pubkey()?;
+        self.fetch_account(&pubkey, self.commitment).await
             .map(|account| account.executable)
             .map_err(|e| format!("Failed to get account information: {:?}", e))
     }
@@ -104,22 +261,29 @@ impl Account {
     /// * `Ok(String)` - Owner program address
     /// * `Err(String)` - Error message if query fails
     pub async fn get_owner(&self) -> Result<String, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
-        self.client.get_account(&pubkey).await
+        let pubkey = self.pubkey()?;
+        self.fetch_account(&pubkey, self.commitment).await
             .map(|account| account.owner.to_string())
             .map_err(|e| format!("Failed to get owner: {:?}", e))
     }
 
+    /// Checks whether the account is owned by the stake program
+    /// 
+    /// # Returns
+    /// * `Ok(bool)` - True if account is a stake account, false otherwise
+    /// * `Err(String)` - Error message if query fails
+    pub async fn is_stake_account(&self) -> Result<bool, String> {
+        self.get_owner().await.map(|owner| owner == STAKE_PROGRAM_ID)
+    }
+
     /// Gets the data size of the account
     /// 
     /// # Returns
     /// * `Ok(usize)` - Account data size in bytes
     /// * `Err(String)` - Error message if query fails
     pub async fn get_data_size(&self) -> Result<usize, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
-        self.client.get_account(&pubkey).await
+        let pubkey = self.pubkey()?;
+        self.fetch_account(&pubkey, self.commitment).await
             .map(|account| account.data.len())
             .map_err(|e| format!("Failed to get data size: {:?}", e))
     }
@@ -130,9 +294,8 @@ impl Account {
     /// * `Ok(u64)` - Rent epoch value
     /// * `Err(String)` - Error message if query fails
     pub async fn get_rent_epoch(&self) -> Result<u64, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
-        self.client.get_account(&pubkey).await
+        let pubkey = self.pubkey()?;
+        self.fetch_account(&pubkey, self.commitment).await
             .map(|account| account.rent_epoch)
             .map_err(|e| format!("Failed to get rent epoch: {:?}", e))
     }
@@ -197,8 +360,7 @@ impl Account {
     /// * `Ok(usize)` - Number of transactions found (up to limit)
     /// * `Err(String)` - Error message if query fails
     pub async fn get_transaction_count(&self, limit: usize) -> Result<usize, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
+        let pubkey = self.pubkey()?;
         let config = GetConfirmedSignaturesForAddress2Config {
             before: None,
             until: None,
@@ -219,8 +381,7 @@ impl Account {
     /// * `Ok(usize)` - Number of successful transactions
     /// * `Err(String)` - Error message if query fails
     pub async fn get_successful_transaction_count(&self, limit: usize) -> Result<usize, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
+        let pubkey = self.pubkey()?;
         let config = GetConfirmedSignaturesForAddress2Config {
             before: None,
             until: None,
@@ -238,8 +399,7 @@ impl Account {
     /// * `Ok(Option<i64>)` - Unix timestamp of last transaction, or None if no transactions
     /// * `Err(String)` - Error message if query fails
     pub async fn get_last_transaction_time(&self) -> Result<Option<i64>, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
+        let pubkey = self.pubkey()?;
         let config = GetConfirmedSignaturesForAddress2Config {
             before: None,
             until: None,
@@ -260,8 +420,7 @@ impl Account {
     /// * `Ok(usize)` - Number of failed transactions
     /// * `Err(String)` - Error message if query fails
     pub async fn get_failed_transaction_count(&self, limit: usize) -> Result<usize, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
+        let pubkey = self.pubkey()?;
         let config = GetConfirmedSignaturesForAddress2Config {
             before: None,
             until: None,
@@ -300,8 +459,7 @@ impl Account {
     /// * `Ok(Vec<String>)` - List of transaction signatures
     /// * `Err(String)` - Error message if query fails
     pub async fn get_recent_transaction_signatures(&self, limit: usize) -> Result<Vec<String>, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
+        let pubkey = self.pubkey()?;
         let config = GetConfirmedSignaturesForAddress2Config {
             before: None,
             until: None,
@@ -342,8 +500,7 @@ impl Account {
         end_time: i64,
         limit: usize,
     ) -> Result<usize, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
+        let pubkey = self.pubkey()?;
         let config = GetConfirmedSignaturesForAddress2Config {
             before: None,
             until: None,
@@ -371,9 +528,8 @@ impl Account {
     /// * `Ok(u64)` - Balance in lamports
     /// * `Err(String)` - Error message if query fails
     pub async fn get_balance(&self) -> Result<u64, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
-        self.client.get_account(&pubkey).await
+        let pubkey = self.pubkey()?;
+        self.fetch_account(&pubkey, self.commitment).await
             .map(|account| account.lamports)
             .map_err(|e| format!("Failed to get balance: {:?}", e))
     }
@@ -386,7 +542,7 @@ impl Account {
     pub async fn get_balance_sol(&self) -> Result<f64, String> {
         self.get_balance()
             .await
-            .map(|balance| balance as f64 / LAMPORTS_PER_SOL as f64)
+            .map(lamports_to_sol)
     }
 
     /// Gets transaction history (signatures only)
@@ -398,8 +554,7 @@ impl Account {
     /// * `Ok(Vec<String>)` - List of transaction signatures
     /// * `Err(String)` - Error message if query fails
     pub async fn get_transaction_history(&self, limit: usize) -> Result<Vec<String>, String> {
-        let pubkey = Pubkey::from_str(&self.address)
-            .map_err(|e| format!("Invalid address format: {:?}", e))?;
+        let pubkey = self.pubkey()?;
         let config = GetConfirmedSignaturesForAddress2Config {
             before: None,
             until: None,
@@ -417,6 +572,52 @@ impl Account {
         }
     }
 
+    /// Reconstructs the SOL balance changes of the account over its latest transactions
+    /// 
+    /// Each entry is the block time and the signed lamport change of the account in that
+    /// transaction, oldest first, so a running balance is a cumulative sum; transactions
+    /// without a block time are skipped
+    /// 
+    /// # Arguments
+    /// * `limit` - Number of latest transactions to walk
+    /// 
+    /// # Returns
+    /// * `Ok(Vec<(i64, i64)>)` - (block time, balance change in lamports) pairs
+    /// * `Err(String)` - Error message if a query fails
+    pub async fn balance_history(&self, limit: usize) -> Result<Vec<(i64, i64)>, String> {
+        self.pubkey()?;
+        let trade = Trade::new(self.client.clone());
+        let signatures: Vec<String> = trade
+            .signatures_stream(&self.address, signature_limit(limit))
+            .take(limit)
+            .map(|signature| signature.map(|signature| signature.signature))
+            .collect::<Vec<_>>()
+            .await
+            .into_iter()
+            .collect::<Result<_, _>>()
+            .map_err(|e| format!("Failed to get transaction history: {}", e))?;
+        let mut history = Vec::with_capacity(signatures.len());
+        for chunk in signatures.chunks(DEFAULT_CONTAINS_CONCURRENCY) {
+            let chunk: Vec<&str> = chunk.iter().map(String::as_str).collect();
+            for (signature, transaction) in trade.fetch_transactions(&chunk).await {
+                let transaction = transaction
+                    .map_err(|e| format!("Failed to get transaction {}: {}", signature, e))?;
+                let info = TransactionInfo::from_encoded_transaction(&transaction, signature);
+                let Some(block_time) = info.block_time else {
+                    continue;
+                };
+                let Some(index) = info.raw_account_keys.iter().position(|key| *key == self.address) else {
+                    continue;
+                };
+                let change = info.raw_post_balances.get(index).copied().unwrap_or(0) as i64
+                    - info.raw_pre_balances.get(index).copied().unwrap_or(0) as i64;
+                history.push((block_time, change));
+            }
+        }
+        history.reverse();
+        Ok(history)
+    }
+
     /// Checks if account is active (has recent transactions)
     /// 
     /// # Arguments
@@ -429,14 +630,121 @@ impl Account {
         let last_tx_time = self.get_last_transaction_time().await?;
         match last_tx_time {
             Some(block_time) => {
-                let now = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs() as i64;
+                let now = now_unix_secs() as i64;
                 let days_diff = (now - block_time) / (24 * 3600);
                 Ok(days_diff <= days_threshold)
             }
             None => Ok(false),
         }
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::{fixture_solana, mock_solana};
+    use crate::mock::MockRpc;
+    use serde_json::json;
+    use solana_client::rpc_request::RpcRequest;
+
+    #[tokio::test]
+    async fn test_account_reads_use_the_commitment() {
+        use crate::{Solana, mock::MockRpc, types::Mode};
+        use solana_client::rpc_client::RpcClientConfig;
+        use solana_commitment_config::CommitmentConfig;
+        let rpc = MockRpc::new().with_response(
+            RpcRequest::GetAccountInfo,
+            json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "lamports": 5_000,
+                    "data": ["", "base64"],
+                    "owner": "11111111111111111111111111111111",
+                    "executable": false,
+                    "rentEpoch": 0,
+                    "space": 0
+                }
+            }),
+        );
+        let client = solana_client::nonblocking::rpc_client::RpcClient::new_sender(
+            rpc.clone(),
+            RpcClientConfig::with_commitment(CommitmentConfig::confirmed()),
+        );
+        let solana = Solana::from_rpc_client(Mode::DEV, client).unwrap();
+        let address = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+        let account = solana.create_account(address);
+        assert_eq!(account.commitment(), CommitmentConfig::confirmed());
+        let account = account.with_commitment(CommitmentConfig::processed());
+        assert_eq!(account.get_balance().await.unwrap(), 5_000);
+        account
+            .get_account_info_with_commitment(CommitmentConfig::finalized())
+            .await
+            .unwrap();
+        let commitments: Vec<_> = rpc
+            .requests()
+            .iter()
+            .map(|(_, params)| params[1]["commitment"].clone())
+            .collect();
+        assert_eq!(commitments, vec![json!("processed"), json!("finalized")]);
+        // a missing account is still an error
+        rpc.set_response(
+            RpcRequest::GetAccountInfo,
+            json!({ "context": { "slot": 1 }, "value": null }),
+        );
+        assert!(account.get_account_info().await.unwrap_err().contains("AccountNotFound"));
+    }
+
+    #[tokio::test]
+    async fn test_get_account_info() {
+        let address = "9xQeWvG816bUx9EPjHmaT23yvVM2ZWbrrpZb9PusVFin";
+        let owner = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetAccountInfo,
+            json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "lamports": 1_461_600,
+                    "data": ["AQID", "base64"],
+                    "owner": owner,
+                    "executable": false,
+                    "rentEpoch": 18_446_744_073_709_551_615u64,
+                    "space": 3
+                }
+            }),
+        );
+        let solana = mock_solana(&rpc);
+        let info = solana.create_account(address).get_account_info().await.unwrap();
+        assert_eq!(info.lamports, 1_461_600);
+        assert_eq!(info.owner, owner);
+        assert!(!info.executable);
+        assert_eq!(info.rent_epoch, u64::MAX);
+        assert_eq!(info.data, vec![1, 2, 3]);
+        assert!(solana.create_account("invalid").get_account_info().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_subscribe_logs_rejects_invalid_address() {
+        let account = mock_solana(&MockRpc::new()).create_account("invalid");
+        let error = match account.subscribe_logs("not-an-address").await {
+            Ok(_) => panic!("subscribed with an invalid address"),
+            Err(e) => e,
+        };
+        assert!(error.starts_with("Invalid address format"));
+    }
+
+    #[tokio::test]
+    async fn test_balance_history() {
+        let recent = solana_sdk::signature::Signature::from([1u8; 64]).to_string();
+        let old = solana_sdk::signature::Signature::from([2u8; 64]).to_string();
+        let solana = fixture_solana(&[
+            (recent.as_str(), "sol_payment_recent"),
+            (old.as_str(), "sol_payment_old"),
+        ]);
+        let account = solana.create_account("3Fav7BV2tWLjwB9kBHnbmb6ibehugwuvimSMCUgwyNbR");
+        assert_eq!(
+            account.balance_history(10).await.unwrap(),
+            vec![(1_752_000_000, 3_000_000), (1_760_660_400, 1_000_000)]
+        );
+        assert_eq!(account.balance_history(1).await.unwrap(), vec![(1_760_660_400, 1_000_000)]);
+    }
+}
@@ -1,14 +1,30 @@
+use futures::{
+    Stream, StreamExt,
+    channel::{mpsc, oneshot},
+};
 use serde::{Deserialize, Serialize};
-use solana_client::{nonblocking::rpc_client::RpcClient, rpc_config::RpcBlockConfig};
+use solana_client::{
+    nonblocking::{pubsub_client::PubsubClient, rpc_client::RpcClient},
+    rpc_config::RpcBlockConfig,
+    rpc_response::SlotInfo,
+};
 use solana_sdk::{clock::Slot, hash::Hash, signature::Signature};
 use solana_transaction_status::{UiConfirmedBlock, UiTransactionEncoding};
 use std::collections::VecDeque;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
 
+use crate::tool::rpc::websocket_url;
+
+/// first delay before reconnecting a dropped slot subscription, doubled per attempt
+const SLOT_RECONNECT_DELAY: Duration = Duration::from_millis(500);
+/// longest delay between slot subscription reconnects
+const SLOT_RECONNECT_MAX_DELAY: Duration = Duration::from_secs(30);
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlockInfo {
     pub slot: Slot,
@@ -119,6 +135,95 @@ impl Block {
         Ok(Some(BlockInfo::parse(block)))
     }
 
+    /// Streams a notification (`slot`, `parent`, `root`) for every slot the node processes
+    ///
+    /// The websocket url is derived from the rpc url: `http` becomes `ws`, `https` becomes `wss`
+    /// and an explicit port is bumped by one (`http://127.0.0.1:8899` -> `ws://127.0.0.1:8900`),
+    /// which is where the official clusters and a local validator serve pubsub (see `Mode::websocket_url`).
+    /// An error is returned when the first connection fails; after that a dropped connection is
+    /// reconnected with a backoff of 500ms doubling up to 30s, slots processed while disconnected
+    /// are not replayed. The subscription is dropped together with the returned stream
+    ///
+    /// # Example
+    /// ```ignore
+    /// let slots = solana.create_block_service().subscribe_slots().await?;
+    /// futures::pin_mut!(slots);
+    /// while let Some(slot) = slots.next().await {
+    ///     println!("slot {} (parent {}, root {})", slot.slot, slot.parent, slot.root);
+    /// }
+    /// ```
+    pub async fn subscribe_slots(&self) -> Result<impl Stream<Item = SlotInfo> + use<>, String> {
+        let url = websocket_url(&self.client.url());
+        let (sender, receiver) = mpsc::unbounded();
+        let (ready_sender, ready_receiver) = oneshot::channel();
+        tokio::spawn(async move {
+            let mut ready_sender = Some(ready_sender);
+            let mut delay = SLOT_RECONNECT_DELAY;
+            while !sender.is_closed() {
+                let subscribed = match PubsubClient::new(&url).await {
+                    Ok(pubsub) => match pubsub.slot_subscribe().await {
+                        Ok((mut notifications, unsubscribe)) => {
+                            if let Some(ready) = ready_sender.take() {
+                                let _ = ready.send(Ok(()));
+                            }
+                            delay = SLOT_RECONNECT_DELAY;
+                            while let Some(slot) = notifications.next().await {
+                                // the receiver was dropped
+                                if sender.unbounded_send(slot).is_err() {
+                                    break;
+                                }
+                            }
+                            drop(notifications);
+                            unsubscribe().await;
+                            Ok(())
+                        }
+                        Err(e) => Err(format!("Failed to subscribe slots: {:?}", e)),
+                    },
+                    Err(e) => Err(format!("Failed to connect websocket: {:?}", e)),
+                };
+                // only the first connection reports its error, later ones are retried
+                if let Err(e) = subscribed
+                    && let Some(ready) = ready_sender.take()
+                {
+                    let _ = ready.send(Err(e));
+                    return;
+                }
+                if sender.is_closed() {
+                    break;
+                }
+                tokio::time::sleep(delay).await;
+                delay = (delay * 2).min(SLOT_RECONNECT_MAX_DELAY);
+            }
+        });
+        ready_receiver
+            .await
+            .map_err(|_| "Slot subscription stopped".to_string())??;
+        Ok(receiver)
+    }
+
+    /// Get the confirmed blocks between `start_slot` and `end_slot` (inclusive, None is the latest)
+    /// the start is clamped to the node's first available block, so ranges reaching into a
+    /// pruned ledger return the blocks the node still has instead of an error
+    pub async fn get_blocks(
+        &self,
+        start_slot: Slot,
+        end_slot: Option<Slot>,
+    ) -> Result<Vec<Slot>, String> {
+        let first_available = self
+            .client
+            .get_first_available_block()
+            .await
+            .map_err(|e| e.to_string())?;
+        let start_slot = start_slot.max(first_available);
+        if end_slot.is_some_and(|end| end < start_slot) {
+            return Ok(Vec::new());
+        }
+        self.client
+            .get_blocks(start_slot, end_slot)
+            .await
+            .map_err(|e| e.to_string())
+    }
+
     /// Fetches all transaction information from latest blocks and calls back in batches
     ///
     /// # Parameters
@@ -246,10 +351,14 @@ impl Block {
 #[cfg(test)]
 mod tests {
     use crate::Solana;
+    use crate::fixtures::mock_solana;
+    use crate::mock::MockRpc;
+    use solana_client::rpc_request::RpcRequest;
 
     use super::*;
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_fetch_transactions_from_latest_blocks() {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let block_service = Arc::new(Block::new(solana.client_arc()));
@@ -269,6 +378,34 @@ mod tests {
     }
 
     #[tokio::test]
+    async fn test_subscribe_slots_reports_connection_errors() {
+        let block = Block::new(Arc::new(RpcClient::new("http://127.0.0.1:1".to_string())));
+        let error = match block.subscribe_slots().await {
+            Ok(_) => panic!("subscribed without a websocket server"),
+            Err(e) => e,
+        };
+        assert!(error.starts_with("Failed to connect websocket"));
+    }
+
+    #[tokio::test]
+    async fn test_get_blocks_clamps_to_first_available_block() {
+        let rpc = MockRpc::new();
+        rpc.set_response(RpcRequest::GetFirstAvailableBlock, serde_json::json!(100));
+        rpc.set_response(RpcRequest::GetBlocks, serde_json::json!([100, 101, 103]));
+        let service = mock_solana(&rpc).create_block_service();
+        assert_eq!(
+            service.get_blocks(50, Some(103)).await.unwrap(),
+            vec![100, 101, 103]
+        );
+        // the whole range was pruned, getBlocks is not sent
+        let rpc = MockRpc::new();
+        rpc.set_response(RpcRequest::GetFirstAvailableBlock, serde_json::json!(100));
+        let service = mock_solana(&rpc).create_block_service();
+        assert!(service.get_blocks(10, Some(99)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_get_block_by_slot() {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let service = Block::new(solana.client_arc());
@@ -280,6 +417,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_get_block_by_slot_is_vote_program() {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let service = Block::new(solana.client_arc());
@@ -295,6 +433,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_poll_latest_block() {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let service = Block::new(solana.client_arc());
@@ -0,0 +1,153 @@
+//! saved transaction fixtures for offline tests
+use crate::Solana;
+use crate::mock::MockRpc;
+use crate::trade::info::TransactionInfo;
+use crate::types::Mode;
+use base64::Engine;
+use serde_json::{Value, json};
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::RpcClientConfig,
+    rpc_request::{RpcError, RpcRequest, RpcResponseErrorData},
+};
+use solana_sdk::{
+    hash::Hash,
+    transaction::{Transaction, TransactionError},
+};
+use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction};
+use std::sync::Arc;
+
+/// load a saved `getTransaction` response from `tests/fixtures/<name>.json`
+pub(crate) fn load_fixture(name: &str) -> EncodedConfirmedTransactionWithStatusMeta {
+    let path = format!(
+        "{}/tests/fixtures/{}.json",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    let json = std::fs::read_to_string(&path)
+        .unwrap_or_else(|e| panic!("read fixture {} error: {:?}", path, e));
+    serde_json::from_str(&json).unwrap_or_else(|e| panic!("parse fixture {} error: {:?}", path, e))
+}
+
+/// load a saved transaction and parse it with its first signature
+pub(crate) fn load_transaction_info(name: &str) -> TransactionInfo {
+    let tx = load_fixture(name);
+    let signature = match &tx.transaction.transaction {
+        EncodedTransaction::Json(json_tx) => json_tx.signatures[0].clone(),
+        _ => String::new(),
+    };
+    TransactionInfo::from_encoded_transaction(&tx, &signature)
+}
+
+/// create a solana object answering through `rpc`
+pub(crate) fn mock_solana(rpc: &MockRpc) -> Solana {
+    let client = RpcClient::new_sender(rpc.clone(), RpcClientConfig::default());
+    Solana::from_rpc_client(Mode::DEV, client).unwrap()
+}
+
+/// decode the transaction of a `sendTransaction` request
+pub(crate) fn sent_transaction(params: &Value) -> Transaction {
+    let encoded = params[0].as_str().unwrap_or_default();
+    let bytes = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .unwrap_or_else(|e| panic!("decode sent transaction error: {:?}", e));
+    bincode::deserialize(&bytes).unwrap_or_else(|e| panic!("parse sent transaction error: {:?}", e))
+}
+
+/// answer `sendTransaction` with the signature of the sent transaction, as an accepting node does
+pub(crate) fn accept_transactions(rpc: &MockRpc) {
+    rpc.set_handler(RpcRequest::SendTransaction, |params| {
+        Ok(json!(sent_transaction(params).signatures[0].to_string()))
+    });
+}
+
+/// error a node answers `sendTransaction` with when the preflight simulation fails with `err`
+pub(crate) fn preflight_failure(err: TransactionError) -> ClientError {
+    let result = serde_json::from_value(json!({ "err": err, "logs": [] }))
+        .unwrap_or_else(|e| panic!("build simulation result error: {:?}", e));
+    ClientErrorKind::RpcError(RpcError::RpcResponseError {
+        code: -32002,
+        message: format!("Transaction simulation failed: {}", err),
+        data: RpcResponseErrorData::SendTransactionPreflightFailure(result),
+    })
+    .into()
+}
+
+/// `getLatestBlockhash` response for `blockhash`
+pub(crate) fn latest_blockhash(blockhash: Hash, last_valid_block_height: u64) -> Value {
+    json!({
+        "context": { "slot": 1 },
+        "value": {
+            "blockhash": blockhash.to_string(),
+            "lastValidBlockHeight": last_valid_block_height
+        }
+    })
+}
+
+/// `getSignatureStatuses` response for one signature, None when it has not landed
+pub(crate) fn signature_statuses(confirmation_status: Option<&str>) -> Value {
+    let status = confirmation_status.map(|confirmation_status| {
+        json!({
+            "slot": 1,
+            // finalized transactions have no confirmation count
+            "confirmations": (confirmation_status != "finalized").then_some(0),
+            "err": null,
+            "status": { "Ok": null },
+            "confirmationStatus": confirmation_status
+        })
+    });
+    json!({ "context": { "slot": 1 }, "value": [status] })
+}
+
+/// create a solana object whose address history is `history`, newest first
+/// each entry is a (signature, fixture name) pair, `getTransaction` answers with the fixture
+pub(crate) fn fixture_solana(history: &[(&str, &str)]) -> Solana {
+    mock_solana(&fixture_rpc(history))
+}
+
+/// mock rpc serving `history` from saved fixtures, see `fixture_solana`
+pub(crate) fn fixture_rpc(history: &[(&str, &str)]) -> MockRpc {
+    let history: Arc<Vec<(String, String)>> = Arc::new(
+        history
+            .iter()
+            .map(|(signature, name)| (signature.to_string(), name.to_string()))
+            .collect(),
+    );
+    let transactions = history.clone();
+    MockRpc::new()
+        .with_handler(RpcRequest::GetSignaturesForAddress, move |params| {
+            Ok(Value::Array(
+                history
+                    .iter()
+                    .skip(match params[1]["before"].as_str() {
+                        Some(before) => history
+                            .iter()
+                            .position(|(s, _)| s == before)
+                            .map_or(history.len(), |i| i + 1),
+                        None => 0,
+                    })
+                    .take_while(|(s, _)| Some(s.as_str()) != params[1]["until"].as_str())
+                    .take(params[1]["limit"].as_u64().map_or(1000, |l| l as usize))
+                    .map(|(signature, name)| {
+                        let tx = load_fixture(name);
+                        json!({
+                            "signature": signature,
+                            "slot": tx.slot,
+                            "err": null,
+                            "memo": null,
+                            "blockTime": tx.block_time,
+                            "confirmationStatus": "finalized",
+                        })
+                    })
+                    .collect(),
+            ))
+        })
+        .with_handler(RpcRequest::GetTransaction, move |params| {
+            let signature = params[0].as_str().unwrap_or_default();
+            match transactions.iter().find(|(s, _)| s == signature) {
+                Some((_, name)) => Ok(serde_json::to_value(load_fixture(name))?),
+                None => Ok(Value::Null),
+            }
+        })
+}
@@ -4,9 +4,16 @@ pub const SOLANA_TEST_NET_URL: &'static str = "https://api.testnet.solana.com";
 pub const SOLANA_OFFICIAL_MAIN_NET_URL: &'static str = "https://api.mainnet-beta.solana.com";
 pub const SOLANA_SERUM_MAIN_NET_URL: &'static str = "https://solana-api.projectserum.com";
 pub const SOLANA_ANKR_MAIN_NET_URL: &'static str = "https://rpc.ankr.com/solana";
+/// solana-test-validator defaults
+pub const SOLANA_LOCAL_NET_URL: &str = "http://127.0.0.1:8899";
+pub const SOLANA_LOCAL_NET_WS_URL: &str = "ws://127.0.0.1:8900";
+// most signatures a getSignaturesForAddress request returns
+pub const MAX_SIGNATURES_PER_REQUEST: usize = 1000;
 
 /// common address
+/// native SOL identifier used for lamport balance changes, not a real mint
 pub const SOL: &'static str = "So11111111111111111111111111111111111111111";
+/// wrapped SOL token mint, held in token accounts; treated as SOL for quotes and decimals
 pub const WSOL: &'static str = "So11111111111111111111111111111111111111112";
 pub const USDC: &'static str = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
 pub const USDT: &'static str = "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB";
@@ -15,8 +22,33 @@ pub const RAY: &'static str = "4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R";
 pub const QUOTES: [&str; 5] = [SOL, WSOL, USDT, USDC, USD_1];
 pub const SPL_TOKEN_PROGRAM_V1: &'static str = "TokenkegQfeZyiNwAJbNbGKPFXCWuBvf9Ss623VQ5DA";
 pub const SPL_TOKEN_PROGRAM_2022: &'static str = "TokenzQdBNbLqP5VEhdkAS6EPFLC1PHnBqCXEpPxuEb";
+pub const ASSOCIATED_TOKEN_PROGRAM_ID: &str = "ATokenGPvbdGVxr1b2hvZbsiqW5xWH25efTNsLJA8knL";
+pub const SYSTEM_PROGRAM_ID: &str = "11111111111111111111111111111111";
+pub const COMPUTE_BUDGET_PROGRAM_ID: &str = "ComputeBudget111111111111111111111111111111";
+/// metaplex token metadata program id
+pub const METAPLEX_TOKEN_METADATA_PROGRAM_ID: &str = "metaqbxxUerdq28cj1RbAWkYQm3ybzjb6a8bt518x1s";
+/// metaplex bubblegum (compressed nft) program id
+pub const BUBBLEGUM_PROGRAM_ID: &str = "BGUMAp9Gq7iTEuizy4pqaxsTyUCBK68MDfK752saRPUY";
+/// stake program id
+pub const STAKE_PROGRAM_ID: &str = "Stake11111111111111111111111111111111111111";
+/// stake history sysvar
+pub const STAKE_HISTORY_SYSVAR_ID: &str = "SysvarStakeHistory1111111111111111111111111";
+/// feature gate that lowered the stake warmup/cooldown rate from 25% to 9%
+pub const REDUCE_STAKE_WARMUP_COOLDOWN_FEATURE_ID: &str =
+    "GwtDQBghCTBgmX2cpEGNPxTEBUTQRaDMGTr5qychdGMj";
 // vote program id
 pub const VOTE_PROGRAM_ID: &str = "Vote111111111111111111111111111111111111111";
+/// jito tip payment accounts, a bundle pays its tip with a SOL transfer to one of them
+pub const JITO_TIP_ACCOUNTS: [&str; 8] = [
+    "96gYZGLnJYVFmbjzopPSU6QiEV5fGqZNyN9nmNhvrZU5",
+    "HFqU5x63VTqvQss8hp11i4wVV8bD44PvwucfZ2bU7gRe",
+    "Cw8CFyM9FkoMi7K7Crf6HNQqf4uEMzpKw6QNghXLvLkY",
+    "ADaUMid9yfUytqMBgopwjb2DTLSokTSzL1zt6iGPaS49",
+    "DfXygSm4jCyNCybVYYK6DymvVbRRBSbQxS3dAWmRMmqU",
+    "ADuUkR4vqLUMWXxW9gh6D6L8pMSawimctcNZ5pGwDcEt",
+    "DttWaMuVvTiduZRnguLF7jNxTgiMBZ1hyAumKUiL2KRL",
+    "3AVi9Tg9Uo68tJfuvoKvqKNWKkC5wPdSSdeBnizKZ6jT",
+];
 
 /// raydium v4 pool program id
 pub const RAYDIUM_V4_POOL_PROGRAM_ID: &'static str = "675kPX9MHTjS2zt1qfr1NYHuzeLXfQM9H24wFSUt1Mp8";
@@ -44,3 +76,50 @@ pub const METEORA_DYNAMIC_BOND_CURVE_PROGRAM_ID: &'static str =
     "dbcij3LWUppWqq96dh6gJWwBifmcGfLSB5D4DuSMaqN";
 /// orca whirlpools program id
 pub const ORCA_WHIRLPOOLS_PROGRAM_ID: &'static str = "whirLbMiicVdio4qvUfM5KAg6Ct8VwpYzGff3uctyCc";
+/// jupiter aggregator v6 program id
+pub const JUPITER_V6_PROGRAM_ID: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+/// dex and launchpad programs whose invocation marks a transaction as a swap
+pub const DEX_PROGRAM_IDS: [&str; 11] = [
+    RAYDIUM_V4_POOL_PROGRAM_ID,
+    RAYDIUM_CPMM_POOL_PROGRAM_ID,
+    RAYDIUM_CLMM_POOL_PROGRAM_ID,
+    RAYDIUM_LAUNCHPAD_PROGRAM_ID,
+    PUMP_AAM_PROGRAM_ID,
+    PUMP_BOND_CURVE_PROGRAM_ID,
+    METEORA_DAMM_V2_PROGRAM_ID,
+    METEORA_DLMM_V2_PROGRAM_ID,
+    METEORA_POOL_PROGRAM_ID,
+    METEORA_DYNAMIC_BOND_CURVE_PROGRAM_ID,
+    ORCA_WHIRLPOOLS_PROGRAM_ID,
+];
+/// log keywords of dex trades, a low confidence fallback for programs not in `DEX_PROGRAM_IDS`
+pub const DEX_LOG_KEYWORDS: [&str; 28] = [
+    "Buy",
+    "buy",
+    "Sell",
+    "sell",
+    "swap",
+    "Swap",
+    "liquidity",
+    "Liquidity",
+    "pool",
+    "Pool",
+    "raydium",
+    "Raydium",
+    "orca",
+    "Orca",
+    "serum",
+    "Serum",
+    "market",
+    "Market",
+    "trade",
+    "Trade",
+    "Pump",
+    "pump",
+    "Pumpswap",
+    "pumpswap",
+    "pump.fun",
+    "Pump.fun",
+    "meteora",
+    "Meteora",
+];
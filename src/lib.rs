@@ -1,7 +1,11 @@
 pub mod account;
 pub mod block;
+#[cfg(test)]
+mod fixtures;
 pub mod global;
 pub mod message;
+pub mod mock;
+pub mod prelude;
 pub mod pyth;
 pub mod scan;
 pub mod spl;
@@ -10,41 +14,90 @@ pub mod trade;
 pub mod types;
 pub mod wallet;
 
-use solana_client::nonblocking::rpc_client::RpcClient;
+use solana_client::{
+    client_error::ClientErrorKind,
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcLargestAccountsConfig, RpcLargestAccountsFilter},
+    rpc_request::{RpcError, RpcResponseErrorData},
+};
+use solana_commitment_config::CommitmentConfig;
 use solana_network_client::SolanaClient;
-use solana_sdk::{epoch_info::EpochInfo, native_token::LAMPORTS_PER_SOL, pubkey::Pubkey};
-use std::{str::FromStr, sync::Arc};
+use solana_sdk::{epoch_info::EpochInfo, epoch_schedule::EpochSchedule, pubkey::Pubkey};
+use std::{str::FromStr, sync::Arc, time::Duration};
 
 use crate::{
     account::Account,
     block::Block,
     scan::Scan,
     spl::Spl,
-    trade::Trade,
-    types::{Mode, UnifiedError, UnifiedResult},
+    trade::{DEFAULT_MAX_SIGNATURES, Trade, info::UiTokenAmount},
+    types::{
+        AccountFilter, BlockProduction, ClusterNode, InflationRate, Mode, NodeInfo, PerfSample,
+        PrioritizationFee, RetryPolicy, SolSupply, StakeActivation, StakeActivationState,
+        UnifiedError, UnifiedResult, VoteAccount, VoteAccounts,
+    },
 };
 
 /// solana client Abstraction
 pub struct Solana {
     mode: Mode,
+    retry: RetryPolicy,
+    max_signatures: usize,
     pub solana_client: Option<Arc<SolanaClient>>,
 }
 
 impl Solana {
     /// create solana object
     pub fn new(mode: Mode) -> Result<Solana, String> {
-        Ok(Self {
+        Self::builder().mode(mode).build()
+    }
+
+    /// create a builder for configuring the rpc url, timeout, commitment and retries
+    /// Example
+    /// ```ignore
+    /// let s = Solana::builder()
+    ///     .url("https://my-rpc.example.com")
+    ///     .timeout(Duration::from_secs(60))
+    ///     .build()
+    ///     .unwrap();
+    /// ```
+    pub fn builder() -> SolanaBuilder {
+        SolanaBuilder::default()
+    }
+
+    /// create a solana object around an existing rpc client
+    /// the client can be built over any `RpcSender`, e.g. `mock::MockRpc` for offline tests
+    /// Example
+    /// ```ignore
+    /// let rpc = MockRpc::new().with_response(RpcRequest::GetSlot, json!(42));
+    /// let client = RpcClient::new_sender(rpc, RpcClientConfig::default());
+    /// let s = Solana::from_rpc_client(Mode::DEV, client).unwrap();
+    /// ```
+    pub fn from_rpc_client(mode: Mode, client: RpcClient) -> Result<Solana, String> {
+        // the wrapped client has no local mode, its rpc client is replaced anyway
+        let mut solana_client = SolanaClient::new(match mode {
+            Mode::MAIN => solana_network_client::Mode::MAIN,
+            Mode::TEST => solana_network_client::Mode::TEST,
+            Mode::DEV | Mode::LOCALNET => solana_network_client::Mode::DEV,
+        })
+        .map_err(|e| format!("create solana client error: {:?}", e))?;
+        solana_client.client = Some(Arc::new(client));
+        Ok(Solana {
             mode,
-            solana_client: Some(Arc::new(
-                SolanaClient::new(solana_network_client::Mode::MAIN)
-                    .map_err(|e| format!("create solana client error: {:?}", e))
-                    .unwrap(),
-            )),
+            retry: RetryPolicy::default(),
+            max_signatures: DEFAULT_MAX_SIGNATURES,
+            solana_client: Some(Arc::new(solana_client)),
         })
     }
+
+    /// get the network mode of this client
+    pub fn mode(&self) -> Mode {
+        self.mode
+    }
+
     /// get client arc
     /// Example
-    /// ```rust
+    /// ```ignore
     /// let s = Solana::new(Mode::TEST);
     /// let client = s.client_arc().await;
     /// ```
@@ -54,12 +107,13 @@ impl Solana {
 
     /// get solana core version
     /// Example
-    /// ```rust
+    /// ```ignore
     /// let s = Solana::new(Mode::TEST);
     /// let client = s.core_version().await;
     /// ```
     pub async fn core_version(&self) -> Result<String, String> {
-        match self.client_arc().get_version().await {
+        let client = self.client_arc();
+        match self.call_with_retry(|| client.get_version()).await {
             Ok(version) => {
                 return Ok(version.solana_core);
             }
@@ -71,12 +125,13 @@ impl Solana {
 
     /// get feature set
     /// Example
-    /// ```rust
+    /// ```ignore
     /// let s = Solana::new(Mode::TEST);
     /// let client = s.feature_set().await;
     /// ```
     pub async fn feature_set(&self) -> Result<String, String> {
-        match self.client_arc().get_version().await {
+        let client = self.client_arc();
+        match self.call_with_retry(|| client.get_version()).await {
             Ok(version) => {
                 return Ok(version.feature_set.unwrap().to_string());
             }
@@ -88,12 +143,13 @@ impl Solana {
 
     /// get block height
     /// Example
-    /// ```rust
+    /// ```ignore
     /// let s = Solana::new(Mode::TEST);
     /// let client = s.block_height().await;
     /// ```
     pub async fn block_height(&self) -> Result<u64, String> {
-        match self.client_arc().get_block_height().await {
+        let client = self.client_arc();
+        match self.call_with_retry(|| client.get_block_height()).await {
             Ok(h) => {
                 return Ok(h);
             }
@@ -105,12 +161,13 @@ impl Solana {
 
     /// last block hash
     /// Example
-    /// ```rust
+    /// ```ignore
     /// let s = Solana::new(Mode::TEST);
     /// let client = s.last_block_hash().await;
     /// ```
     pub async fn last_block_hash(&self) -> Result<String, String> {
-        match self.client_arc().get_latest_blockhash().await {
+        let client = self.client_arc();
+        match self.call_with_retry(|| client.get_latest_blockhash()).await {
             Ok(h) => {
                 return Ok(h.to_string());
             }
@@ -120,14 +177,36 @@ impl Solana {
         }
     }
 
+    /// latest block hash and the last block height it is valid for
+    /// a transaction signed with the hash can land until the chain passes that height,
+    /// after that it has to be re-signed with a fresh hash
+    /// Example
+    /// ```ignore
+    /// let (blockhash, last_valid_block_height) = s.get_latest_blockhash_with_expiry().await?;
+    /// ```
+    pub async fn get_latest_blockhash_with_expiry(&self) -> UnifiedResult<(String, u64), String> {
+        let client = self.client_arc();
+        match self
+            .call_with_retry(|| client.get_latest_blockhash_with_commitment(client.commitment()))
+            .await
+        {
+            Ok((hash, last_valid_block_height)) => Ok((hash.to_string(), last_valid_block_height)),
+            Err(e) => Err(UnifiedError::Error(format!(
+                "get latest block hash error: {:?}",
+                e
+            ))),
+        }
+    }
+
     /// get current slot
     /// Example
-    /// ```rust
+    /// ```ignore
     /// let s = Solana::new(Mode::TEST);
     /// let client = s.slot().await;
     /// ```
     pub async fn slot(&self) -> Result<u64, String> {
-        match self.client_arc().get_slot().await {
+        let client = self.client_arc();
+        match self.call_with_retry(|| client.get_slot()).await {
             Ok(slot) => {
                 return Ok(slot);
             }
@@ -138,12 +217,13 @@ impl Solana {
     }
     /// get current epoch info
     /// Example
-    /// ```rust
+    /// ```ignore
     /// let s = Solana::new(Mode::TEST);
     /// let client = s.epoch().await;
     /// ```
     pub async fn epoch(&self) -> Result<EpochInfo, String> {
-        match self.client_arc().get_epoch_info().await {
+        let client = self.client_arc();
+        match self.call_with_retry(|| client.get_epoch_info()).await {
             Ok(epoch) => {
                 return Ok(epoch);
             }
@@ -152,6 +232,105 @@ impl Solana {
             }
         }
     }
+    /// check whether the rpc node is healthy
+    /// an unhealthy node (e.g. lagging behind the cluster) returns Ok(false),
+    /// transport and other rpc failures return Err
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::TEST).unwrap();
+    /// let healthy = s.get_health().await;
+    /// ```
+    pub async fn get_health(&self) -> UnifiedResult<bool, String> {
+        match self.client_arc().get_health().await {
+            Ok(()) => Ok(true),
+            Err(e) => match e.kind() {
+                ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                    data: RpcResponseErrorData::NodeUnhealthy { .. },
+                    ..
+                }) => Ok(false),
+                _ => Err(UnifiedError::Error(format!("get health error: {:?}", e))),
+            },
+        }
+    }
+
+    /// get the identity pubkey of the rpc node
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::TEST).unwrap();
+    /// let identity = s.get_identity().await;
+    /// ```
+    pub async fn get_identity(&self) -> UnifiedResult<String, String> {
+        let client = self.client_arc();
+        self.call_with_retry(|| client.get_identity())
+            .await
+            .map(|identity| identity.to_string())
+            .map_err(|e| UnifiedError::Error(format!("get identity error: {:?}", e)))
+    }
+
+    /// get the identity, version and health of the rpc node, requested concurrently
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::TEST).unwrap();
+    /// let node = s.node_info().await.unwrap();
+    /// println!("{} {} healthy: {}", node.identity, node.solana_core, node.healthy);
+    /// ```
+    pub async fn node_info(&self) -> UnifiedResult<NodeInfo, String> {
+        let client = self.client_arc();
+        let version = async {
+            self.call_with_retry(|| client.get_version())
+                .await
+                .map_err(|e| UnifiedError::Error(format!("get version error: {:?}", e)))
+        };
+        let (identity, version, healthy) =
+            futures::try_join!(self.get_identity(), version, self.get_health())?;
+        Ok(NodeInfo {
+            identity,
+            solana_core: version.solana_core,
+            feature_set: version.feature_set,
+            healthy,
+        })
+    }
+
+    /// get the epoch schedule (slots_per_epoch, leader_schedule_slot_offset, warmup, ...)
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::TEST).unwrap();
+    /// let schedule = s.get_epoch_schedule().await;
+    /// ```
+    pub async fn get_epoch_schedule(&self) -> UnifiedResult<EpochSchedule, String> {
+        self.client_arc()
+            .get_epoch_schedule()
+            .await
+            .map_err(|e| UnifiedError::Error(format!("get epoch schedule error: {:?}", e)))
+    }
+
+    /// get the oldest block the rpc node still has in its ledger (getFirstAvailableBlock)
+    /// slots before it were pruned, backfills walking blocks should start here
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::TEST).unwrap();
+    /// let first = s.first_available_block().await;
+    /// ```
+    pub async fn first_available_block(&self) -> UnifiedResult<u64, String> {
+        self.client_arc()
+            .get_first_available_block()
+            .await
+            .map_err(|e| UnifiedError::Error(format!("get first available block error: {:?}", e)))
+    }
+
+    /// get the lowest slot the rpc node has information about in its ledger (minimumLedgerSlot)
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::TEST).unwrap();
+    /// let slot = s.minimum_ledger_slot().await;
+    /// ```
+    pub async fn minimum_ledger_slot(&self) -> UnifiedResult<u64, String> {
+        self.client_arc()
+            .minimum_ledger_slot()
+            .await
+            .map_err(|e| UnifiedError::Error(format!("get minimum ledger slot error: {:?}", e)))
+    }
+
     /// get account
     /// # Returns
     /// * 0 solana balance
@@ -163,32 +342,517 @@ impl Solana {
             .get_balance(&pubkey)
             .await
             .map_err(|e| UnifiedError::Error(0.0))?;
-        Ok((balance as f64 / LAMPORTS_PER_SOL as f64, balance))
+        Ok((tool::lamports_to_sol(balance), balance))
+    }
+
+    /// get the lamports balance of many accounts, in the same order as `addresses`
+    /// accounts are fetched 100 at a time, non-existent accounts have a balance of 0
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// let balances = s.get_balances_batch(&["address a", "address b"]).await;
+    /// ```
+    pub async fn get_balances_batch(
+        &self,
+        addresses: &[&str],
+    ) -> UnifiedResult<Vec<(String, u64)>, String> {
+        let pubkeys = addresses
+            .iter()
+            .map(|address| {
+                Pubkey::from_str(address)
+                    .map_err(|e| UnifiedError::Error(format!("address error: {:?}", e)))
+            })
+            .collect::<Result<Vec<Pubkey>, _>>()?;
+        let client = self.client_arc();
+        let mut balances = Vec::with_capacity(addresses.len());
+        for (chunk, pubkeys) in addresses.chunks(100).zip(pubkeys.chunks(100)) {
+            let accounts = client.get_multiple_accounts(pubkeys).await.map_err(|e| {
+                UnifiedError::Error(format!("get multiple accounts error: {:?}", e))
+            })?;
+            for (address, account) in chunk.iter().zip(accounts) {
+                balances.push((
+                    address.to_string(),
+                    account.map(|a| a.lamports).unwrap_or(0),
+                ));
+            }
+        }
+        Ok(balances)
+    }
+
+    /// get the nodes of the cluster, sorted by pubkey
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// for node in s.get_cluster_nodes().await.unwrap() {
+    ///     println!("{} {:?} {:?}", node.pubkey, node.gossip, node.version);
+    /// }
+    /// ```
+    pub async fn get_cluster_nodes(&self) -> UnifiedResult<Vec<ClusterNode>, String> {
+        let client = self.client_arc();
+        let nodes = self
+            .call_with_retry(|| client.get_cluster_nodes())
+            .await
+            .map_err(|e| UnifiedError::Error(format!("get cluster nodes error: {:?}", e)))?;
+        let mut nodes: Vec<ClusterNode> = nodes
+            .into_iter()
+            .map(|node| ClusterNode {
+                pubkey: node.pubkey,
+                gossip: node.gossip,
+                tpu: node.tpu,
+                rpc: node.rpc,
+                version: node.version,
+            })
+            .collect();
+        nodes.sort_by(|a, b| a.pubkey.cmp(&b.pubkey));
+        Ok(nodes)
     }
 
+    /// get the prioritization fees of recent slots (about the last 150)
+    /// with addresses, only transactions locking those accounts as writable are considered
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// let fees = s.get_recent_prioritization_fees(&["pool address"]).await;
+    /// ```
+    pub async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[&str],
+    ) -> UnifiedResult<Vec<PrioritizationFee>, String> {
+        let pubkeys = addresses
+            .iter()
+            .map(|address| {
+                Pubkey::from_str(address)
+                    .map_err(|e| UnifiedError::Error(format!("address error: {:?}", e)))
+            })
+            .collect::<Result<Vec<Pubkey>, _>>()?;
+        let client = self.client_arc();
+        let fees = self
+            .call_with_retry(|| client.get_recent_prioritization_fees(&pubkeys))
+            .await
+            .map_err(|e| {
+                UnifiedError::Error(format!("get recent prioritization fees error: {:?}", e))
+            })?;
+        Ok(fees
+            .into_iter()
+            .map(|fee| PrioritizationFee {
+                slot: fee.slot,
+                fee: fee.prioritization_fee,
+            })
+            .collect())
+    }
+
+    /// get the percentile (0-100) compute unit price in micro-lamports across recent slots
+    /// returns 0 when the rpc has no recent observations
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// let micro_lamports = s.recommended_priority_fee(75.0).await;
+    /// ```
+    pub async fn recommended_priority_fee(&self, percentile: f64) -> UnifiedResult<u64, String> {
+        if !(0.0..=100.0).contains(&percentile) {
+            return Err(UnifiedError::Error(format!(
+                "percentile must be between 0 and 100, got {}",
+                percentile
+            )));
+        }
+        let fees: Vec<u64> = self
+            .get_recent_prioritization_fees(&[])
+            .await?
+            .into_iter()
+            .map(|fee| fee.fee)
+            .collect();
+        Ok(tool::percentile(fees, percentile))
+    }
+
+    /// get the number of transactions processed by the cluster
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// let count = s.get_transaction_count().await;
+    /// ```
+    pub async fn get_transaction_count(&self) -> UnifiedResult<u64, String> {
+        let client = self.client_arc();
+        self.call_with_retry(|| client.get_transaction_count())
+            .await
+            .map_err(|e| UnifiedError::Error(format!("get transaction count error: {:?}", e)))
+    }
+
+    /// get up to `limit` recent performance samples, newest first
+    /// samples are taken about every 60 seconds, the rpc returns at most 720
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// let samples = s.get_recent_performance_samples(10).await.unwrap();
+    /// let tps = samples.first().map(|sample| sample.tps());
+    /// ```
+    pub async fn get_recent_performance_samples(
+        &self,
+        limit: usize,
+    ) -> UnifiedResult<Vec<PerfSample>, String> {
+        let client = self.client_arc();
+        let samples = self
+            .call_with_retry(|| client.get_recent_performance_samples(Some(limit)))
+            .await
+            .map_err(|e| {
+                UnifiedError::Error(format!("get recent performance samples error: {:?}", e))
+            })?;
+        Ok(samples
+            .into_iter()
+            .map(|sample| PerfSample {
+                slot: sample.slot,
+                num_transactions: sample.num_transactions,
+                num_slots: sample.num_slots,
+                sample_period_secs: sample.sample_period_secs,
+            })
+            .collect())
+    }
+
+    /// get the inflation rate of the current epoch, as fractions per year
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// let inflation = s.get_inflation_rate().await.unwrap();
+    /// println!("{:.2}% total inflation", inflation.total * 100.0);
+    /// ```
+    pub async fn get_inflation_rate(&self) -> UnifiedResult<InflationRate, String> {
+        let client = self.client_arc();
+        let rate = self
+            .call_with_retry(|| client.get_inflation_rate())
+            .await
+            .map_err(|e| UnifiedError::Error(format!("get inflation rate error: {:?}", e)))?;
+        Ok(InflationRate {
+            total: rate.total,
+            validator: rate.validator,
+            foundation: rate.foundation,
+            epoch: rate.epoch,
+        })
+    }
+
+    /// get the total, circulating and non-circulating SOL supply in lamports
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// let supply = s.get_supply().await.unwrap();
+    /// println!("{} SOL circulating", tool::lamports_to_sol(supply.circulating));
+    /// ```
+    pub async fn get_supply(&self) -> UnifiedResult<SolSupply, String> {
+        let client = self.client_arc();
+        let supply = self
+            .call_with_retry(|| client.supply())
+            .await
+            .map_err(|e| UnifiedError::Error(format!("get supply error: {:?}", e)))?
+            .value;
+        Ok(SolSupply {
+            total: supply.total,
+            circulating: supply.circulating,
+            non_circulating: supply.non_circulating,
+        })
+    }
+
+    /// get the accounts with the most lamports as (address, lamports), largest first
+    /// the rpc returns at most 20 accounts and caches the result for a few hours; many providers
+    /// disable the method, which returns an error saying so
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// let whales = s.get_largest_accounts(Some(AccountFilter::Circulating)).await.unwrap();
+    /// ```
+    pub async fn get_largest_accounts(
+        &self,
+        filter: Option<AccountFilter>,
+    ) -> UnifiedResult<Vec<(String, u64)>, String> {
+        let client = self.client_arc();
+        let config = RpcLargestAccountsConfig {
+            commitment: Some(client.commitment()),
+            filter: filter.map(|filter| match filter {
+                AccountFilter::Circulating => RpcLargestAccountsFilter::Circulating,
+                AccountFilter::NonCirculating => RpcLargestAccountsFilter::NonCirculating,
+            }),
+            sort_results: None,
+        };
+        match client.get_largest_accounts_with_config(config).await {
+            Ok(accounts) => Ok(accounts
+                .value
+                .into_iter()
+                .map(|account| (account.address, account.lamports))
+                .collect()),
+            Err(e) if Self::is_method_unavailable(e.kind()) => Err(UnifiedError::Error(format!(
+                "get largest accounts error: getLargestAccounts is disabled on this rpc node: {}",
+                e
+            ))),
+            Err(e) => Err(UnifiedError::Error(format!(
+                "get largest accounts error: {:?}",
+                e
+            ))),
+        }
+    }
+
+    /// whether the rpc node rejected a request because it does not serve the method
+    fn is_method_unavailable(kind: &ClientErrorKind) -> bool {
+        match kind {
+            ClientErrorKind::RpcError(RpcError::RpcResponseError { code, message, .. }) => {
+                let message = message.to_lowercase();
+                *code == -32601
+                    || ["not found", "disabled", "not supported", "unavailable"]
+                        .iter()
+                        .any(|reason| message.contains(reason))
+            }
+            _ => false,
+        }
+    }
+
+    /// get the data of an account, see `Account::get_account_info` for the owner, lamports and flags
     pub async fn get_account_data(&self, address: &str) -> UnifiedResult<Vec<u8>, String> {
-        Ok(self
-            .solana_client
+        self.create_account(address)
+            .get_account_info()
+            .await
+            .map(|info| info.data)
+            .map_err(UnifiedError::Error)
+    }
+
+    /// get the total supply and decimals of a token mint
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// let supply = s.get_token_supply("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").await;
+    /// ```
+    pub async fn get_token_supply(&self, mint: &str) -> UnifiedResult<UiTokenAmount, String> {
+        let pubkey = Pubkey::from_str(mint)
+            .map_err(|e| UnifiedError::Error(format!("address error: {:?}", e)))?;
+        let supply = self
+            .client_arc()
+            .get_token_supply(&pubkey)
+            .await
+            .map_err(|e| {
+                if e.to_string().contains("not a Token mint") {
+                    UnifiedError::Error(format!("{} is not a token mint account", mint))
+                } else {
+                    UnifiedError::Error(format!("get token supply error: {:?}", e))
+                }
+            })?;
+        Ok(UiTokenAmount {
+            ui_amount: supply.ui_amount,
+            decimals: supply.decimals,
+            amount: supply.amount,
+            ui_amount_string: Some(supply.ui_amount_string),
+        })
+    }
+
+    /// get the activation of a stake account at the current epoch
+    /// computed from the stake account and the stake history sysvar, like the cli does,
+    /// since the `getStakeActivation` rpc method has been removed
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// let activation = s.get_stake_activation("stake account address").await;
+    /// ```
+    pub async fn get_stake_activation(
+        &self,
+        stake_account: &str,
+    ) -> UnifiedResult<StakeActivation, String> {
+        use solana_stake_interface::{stake_history::StakeHistory, state::StakeStateV2};
+        let pubkey = Pubkey::from_str(stake_account)
+            .map_err(|e| UnifiedError::Error(format!("address error: {:?}", e)))?;
+        let client = self.client_arc();
+        let addresses = [
+            pubkey,
+            Pubkey::from_str_const(global::STAKE_HISTORY_SYSVAR_ID),
+            Pubkey::from_str_const(global::REDUCE_STAKE_WARMUP_COOLDOWN_FEATURE_ID),
+        ];
+        let accounts = self
+            .call_with_retry(|| client.get_multiple_accounts(&addresses))
+            .await
+            .map_err(|e| UnifiedError::Error(format!("get stake account error: {:?}", e)))?;
+        let stake_account_data = accounts[0]
+            .as_ref()
+            .ok_or_else(|| UnifiedError::Error(format!("{} not found", stake_account)))?;
+        if stake_account_data.owner.to_string() != global::STAKE_PROGRAM_ID {
+            return Err(UnifiedError::Error(format!(
+                "{} is not a stake account",
+                stake_account
+            )));
+        }
+        let state: StakeStateV2 = bincode::deserialize(&stake_account_data.data)
+            .map_err(|e| UnifiedError::Error(format!("stake account parse error: {:?}", e)))?;
+        let lamports = stake_account_data.lamports;
+        let (meta, stake) = match state {
+            StakeStateV2::Initialized(meta) => {
+                return Ok(StakeActivation {
+                    state: StakeActivationState::Inactive,
+                    active: 0,
+                    inactive: lamports.saturating_sub(meta.rent_exempt_reserve),
+                });
+            }
+            StakeStateV2::Stake(meta, stake, _) => (meta, stake),
+            _ => {
+                return Err(UnifiedError::Error(format!(
+                    "{} is not an initialized stake account",
+                    stake_account
+                )));
+            }
+        };
+        let history: StakeHistory = match &accounts[1] {
+            Some(account) => bincode::deserialize(&account.data)
+                .map_err(|e| UnifiedError::Error(format!("stake history parse error: {:?}", e)))?,
+            None => StakeHistory::default(),
+        };
+        // the feature account holds Option<activation slot>
+        let new_rate_activation_slot = accounts[2]
             .as_ref()
-            .unwrap()
+            .filter(|feature| feature.data.first() == Some(&1) && feature.data.len() >= 9)
+            .map(|feature| u64::from_le_bytes(feature.data[1..9].try_into().unwrap()));
+        let new_rate_activation_epoch = match new_rate_activation_slot {
+            Some(slot) => Some(self.get_epoch_schedule().await?.get_epoch(slot)),
+            None => None,
+        };
+        let epoch = self.epoch().await.map_err(UnifiedError::Error)?.epoch;
+        let status = stake.delegation.stake_activating_and_deactivating(
+            epoch,
+            &history,
+            new_rate_activation_epoch,
+        );
+        let state = if status.deactivating > 0 {
+            StakeActivationState::Deactivating
+        } else if status.activating > 0 {
+            StakeActivationState::Activating
+        } else if status.effective > 0 {
+            StakeActivationState::Active
+        } else {
+            StakeActivationState::Inactive
+        };
+        Ok(StakeActivation {
+            state,
+            active: status.effective,
+            inactive: lamports
+                .saturating_sub(status.effective)
+                .saturating_sub(meta.rent_exempt_reserve),
+        })
+    }
+
+    /// get the current and delinquent vote accounts of the cluster
+    /// current validators are sorted by activated stake, largest first
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// let votes = s.get_vote_accounts().await.unwrap();
+    /// for validator in votes.current.iter().take(10) {
+    ///     println!("{} {} lamports, {}% commission", validator.vote_pubkey, validator.activated_stake, validator.commission);
+    /// }
+    /// ```
+    pub async fn get_vote_accounts(&self) -> UnifiedResult<VoteAccounts, String> {
+        let client = self.client_arc();
+        let status = self
+            .call_with_retry(|| client.get_vote_accounts())
+            .await
+            .map_err(|e| UnifiedError::Error(format!("get vote accounts error: {:?}", e)))?;
+        let to_vote_accounts = |accounts: Vec<solana_client::rpc_response::RpcVoteAccountInfo>| {
+            accounts
+                .into_iter()
+                .map(|account| VoteAccount {
+                    vote_pubkey: account.vote_pubkey,
+                    node_pubkey: account.node_pubkey,
+                    activated_stake: account.activated_stake,
+                    commission: account.commission,
+                    last_vote: account.last_vote,
+                    root_slot: account.root_slot,
+                    epoch_vote_account: account.epoch_vote_account,
+                })
+                .collect::<Vec<_>>()
+        };
+        let mut current = to_vote_accounts(status.current);
+        current.sort_by_key(|account| std::cmp::Reverse(account.activated_stake));
+        Ok(VoteAccounts {
+            current,
+            delinquent: to_vote_accounts(status.delinquent),
+        })
+    }
+
+    /// get the leader slots and produced blocks of every validator identity over the current epoch so far
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::MAIN).unwrap();
+    /// let production = s.get_block_production().await.unwrap();
+    /// for (identity, (leader_slots, produced)) in &production.by_identity {
+    ///     println!("{} {}/{} skip rate {:?}", identity, produced, leader_slots, production.skip_rate(identity));
+    /// }
+    /// ```
+    pub async fn get_block_production(&self) -> UnifiedResult<BlockProduction, String> {
+        let client = self.client_arc();
+        let production = self
+            .call_with_retry(|| client.get_block_production())
+            .await
+            .map_err(|e| UnifiedError::Error(format!("get block production error: {:?}", e)))?
+            .value;
+        Ok(BlockProduction {
+            by_identity: production.by_identity,
+            first_slot: production.range.first_slot,
+            last_slot: production.range.last_slot,
+        })
+    }
+
+    /// request an airdrop of lamports to the specified address
+    /// only allowed on test clusters, mainnet requests are rejected before reaching the rpc
+    /// Example
+    /// ```ignore
+    /// let s = Solana::new(Mode::DEV).unwrap();
+    /// let signature = s.request_airdrop("address", 1_000_000_000).await;
+    /// ```
+    pub async fn request_airdrop(
+        &self,
+        address: &str,
+        lamports: u64,
+    ) -> UnifiedResult<String, String> {
+        if self.mode.is_mainnet() {
+            return Err(UnifiedError::Error(
+                "airdrop is not available on mainnet".to_string(),
+            ));
+        }
+        let pubkey = Pubkey::from_str(address)
+            .map_err(|e| UnifiedError::Error(format!("address error: {:?}", e)))?;
+        let signature = self
             .client_arc()
-            .get_account_data(
-                &Pubkey::from_str(address)
-                    .map_err(|e| UnifiedError::Error(format!("{:?}", e)))
-                    .unwrap(),
-            )
+            .request_airdrop(&pubkey, lamports)
             .await
-            .map_err(|e| UnifiedError::Error(format!("{:?}", e)))
-            .unwrap())
+            .map_err(|e| UnifiedError::Error(format!("request airdrop error: {:?}", e)))?;
+        Ok(signature.to_string())
+    }
+
+    /// get the retry policy applied to rpc calls
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry
+    }
+
+    /// get the most signatures a history scan of the created trades reads
+    pub fn max_signatures(&self) -> usize {
+        self.max_signatures
+    }
+
+    /// run an rpc call, retrying failed attempts according to the retry policy
+    async fn call_with_retry<T, E, F, Fut>(&self, mut f: F) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let mut attempt = 0;
+        loop {
+            match f().await {
+                Ok(value) => return Ok(value),
+                Err(e) if attempt >= self.retry.max_retries => return Err(e),
+                Err(_) => {
+                    attempt += 1;
+                    tokio::time::sleep(self.retry.delay).await;
+                }
+            }
+        }
     }
 
     /// create account
     pub fn create_account(&self, address: &str) -> Account {
         Account::new(self.client_arc(), address)
     }
-    /// create trade
+    /// create trade, history scans are capped at the `max_signatures` of this object
     pub fn create_trade(&self) -> Trade {
-        Trade::new(self.client_arc())
+        Trade::new(self.client_arc()).with_max_signatures(self.max_signatures)
     }
     /// create block service
     pub fn create_block_service(&self) -> Block {
@@ -204,14 +868,353 @@ impl Solana {
     }
 }
 
+/// builder for [`Solana`]
+/// unset options fall back to the official cluster url of the mode, the rpc client default
+/// timeout (30s), finalized commitment and no retries
+#[derive(Debug, Clone)]
+pub struct SolanaBuilder {
+    url: Option<String>,
+    mode: Mode,
+    timeout: Duration,
+    commitment: CommitmentConfig,
+    retry: RetryPolicy,
+    max_signatures: usize,
+}
+
+impl Default for SolanaBuilder {
+    fn default() -> Self {
+        Self {
+            url: None,
+            mode: Mode::MAIN,
+            timeout: Duration::from_secs(30),
+            commitment: CommitmentConfig::finalized(),
+            retry: RetryPolicy::default(),
+            max_signatures: DEFAULT_MAX_SIGNATURES,
+        }
+    }
+}
+
+impl SolanaBuilder {
+    /// custom rpc url, overrides the official url of the mode
+    pub fn url(mut self, url: &str) -> Self {
+        self.url = Some(url.to_string());
+        self
+    }
+
+    /// network mode
+    pub fn mode(mut self, mode: Mode) -> Self {
+        self.mode = mode;
+        self
+    }
+
+    /// http request timeout of the rpc client
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// default commitment of the rpc client
+    pub fn commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = commitment;
+        self
+    }
+
+    /// retry policy for failed rpc calls
+    pub fn retry(mut self, retry: RetryPolicy) -> Self {
+        self.retry = retry;
+        self
+    }
+
+    /// most signatures a history scan reads (at least 1), see `Trade::with_max_signatures`
+    pub fn max_signatures(mut self, max_signatures: usize) -> Self {
+        self.max_signatures = max_signatures.max(1);
+        self
+    }
+
+    /// build the solana object
+    pub fn build(self) -> Result<Solana, String> {
+        let url = self.url.unwrap_or_else(|| self.mode.url().to_string());
+        let mut solana = Solana::from_rpc_client(
+            self.mode,
+            RpcClient::new_with_timeout_and_commitment(url, self.timeout, self.commitment),
+        )?;
+        solana.retry = self.retry;
+        solana.max_signatures = self.max_signatures;
+        Ok(solana)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use raydium_sdk::Raydium;
     use solana_network_client::SolanaClient;
 
     use super::*;
+    use crate::fixtures::mock_solana;
+    use crate::mock::MockRpc;
+    use solana_client::rpc_request::RpcRequest;
+
+    #[test]
+    fn test_builder_configures_rpc_client() {
+        let solana = Solana::builder()
+            .url("http://127.0.0.1:8899")
+            .mode(Mode::DEV)
+            .timeout(Duration::from_secs(60))
+            .commitment(CommitmentConfig::confirmed())
+            .retry(RetryPolicy::new(3, Duration::from_millis(100)))
+            .max_signatures(50_000)
+            .build()
+            .unwrap();
+        assert!(solana.mode().is_test());
+        assert_eq!(solana.client_arc().url(), "http://127.0.0.1:8899");
+        assert_eq!(
+            solana.client_arc().commitment(),
+            CommitmentConfig::confirmed()
+        );
+        assert_eq!(solana.retry_policy().max_retries, 3);
+        assert_eq!(solana.create_trade().max_signatures(), 50_000);
+        let default = Solana::new(Mode::TEST).unwrap();
+        assert_eq!(default.client_arc().url(), global::SOLANA_TEST_NET_URL);
+        assert_eq!(default.retry_policy().max_retries, 0);
+        assert_eq!(default.max_signatures(), DEFAULT_MAX_SIGNATURES);
+        let local = Solana::new(Mode::LOCALNET).unwrap();
+        assert!(local.mode().is_local());
+        assert_eq!(local.client_arc().url(), global::SOLANA_LOCAL_NET_URL);
+    }
 
     #[tokio::test]
+    async fn test_get_health_and_epoch_schedule() {
+        let rpc = MockRpc::new();
+        rpc.set_response(RpcRequest::GetHealth, serde_json::json!("ok"));
+        rpc.set_response(
+            RpcRequest::GetEpochSchedule,
+            serde_json::json!({
+                "slotsPerEpoch": 432_000,
+                "leaderScheduleSlotOffset": 432_000,
+                "warmup": false,
+                "firstNormalEpoch": 0,
+                "firstNormalSlot": 0,
+            }),
+        );
+        let solana = mock_solana(&rpc);
+        assert!(solana.get_health().await.unwrap());
+        let schedule = solana.get_epoch_schedule().await.unwrap();
+        assert_eq!(schedule.slots_per_epoch, 432_000);
+        assert_eq!(schedule.leader_schedule_slot_offset, 432_000);
+        assert!(mock_solana(&MockRpc::new()).get_health().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_node_info() {
+        let identity = "7Np41oeYqPefeNQEHSv1UDhYrehxin3NStELsSKCT4K2";
+        let rpc = MockRpc::new();
+        rpc.set_response(RpcRequest::GetHealth, serde_json::json!("ok"));
+        rpc.set_response(
+            RpcRequest::GetIdentity,
+            serde_json::json!({ "identity": identity }),
+        );
+        rpc.set_response(
+            RpcRequest::GetVersion,
+            serde_json::json!({ "solana-core": "2.2.1", "feature-set": 3294202862u32 }),
+        );
+        let node = mock_solana(&rpc).node_info().await.unwrap();
+        assert_eq!(node.identity, identity);
+        assert_eq!(node.solana_core, "2.2.1");
+        assert_eq!(node.feature_set, Some(3294202862));
+        assert!(node.healthy);
+        // one failing request fails the whole call
+        assert!(mock_solana(&MockRpc::new()).node_info().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_ledger_bounds() {
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetFirstAvailableBlock,
+            serde_json::json!(250_000_000),
+        );
+        rpc.set_response(RpcRequest::MinimumLedgerSlot, serde_json::json!(123));
+        let solana = mock_solana(&rpc);
+        assert_eq!(solana.first_available_block().await.unwrap(), 250_000_000);
+        assert_eq!(solana.minimum_ledger_slot().await.unwrap(), 123);
+    }
+
+    #[tokio::test]
+    async fn test_get_balances_batch() {
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetMultipleAccounts,
+            serde_json::json!({
+                "context": { "slot": 1 },
+                "value": [
+                    {
+                        "lamports": 1_500_000_000u64,
+                        "data": ["", "base64"],
+                        "owner": "11111111111111111111111111111111",
+                        "executable": false,
+                        "rentEpoch": 0,
+                        "space": 0
+                    },
+                    null
+                ]
+            }),
+        );
+        let solana = mock_solana(&rpc);
+        let addresses = [
+            "5dQ9CmV1yyM7zZMGErPCPi8pCBXLmMdCTUTXFekvNjtT",
+            "hKhFMnnZPTvbuJivkhr6bWhdhPBCyEp3DEw8qSRsMyR",
+        ];
+        let balances = solana.get_balances_batch(&addresses).await.unwrap();
+        assert_eq!(
+            balances,
+            vec![
+                (addresses[0].to_string(), 1_500_000_000),
+                (addresses[1].to_string(), 0)
+            ]
+        );
+        assert!(solana.get_balances_batch(&["bad address"]).await.is_err());
+        assert!(solana.get_balances_batch(&[]).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_get_cluster_nodes() {
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetClusterNodes,
+            serde_json::json!([
+                {
+                    "pubkey": "9QxCLckBiJc783jnMvXZubK4wH86Eqqvashtrwvcsgkv",
+                    "gossip": "10.0.0.2:8001",
+                    "tpu": "10.0.0.2:8004",
+                    "rpc": null,
+                    "version": "2.2.14"
+                },
+                {
+                    "pubkey": "7Np41oeYqPefeNQEHSv1UDhYrehxin3NStELsSKCT4K2",
+                    "gossip": "10.0.0.1:8001",
+                    "tpu": "10.0.0.1:8004",
+                    "rpc": "10.0.0.1:8899",
+                    "version": "2.3.6"
+                }
+            ]),
+        );
+        let nodes = mock_solana(&rpc).get_cluster_nodes().await.unwrap();
+        assert_eq!(
+            nodes
+                .iter()
+                .map(|node| node.pubkey.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "7Np41oeYqPefeNQEHSv1UDhYrehxin3NStELsSKCT4K2",
+                "9QxCLckBiJc783jnMvXZubK4wH86Eqqvashtrwvcsgkv"
+            ]
+        );
+        assert_eq!(nodes[0].rpc, Some("10.0.0.1:8899".parse().unwrap()));
+        assert_eq!(nodes[0].version.as_deref(), Some("2.3.6"));
+        assert_eq!(nodes[1].rpc, None);
+        assert_eq!(nodes[1].tpu, Some("10.0.0.2:8004".parse().unwrap()));
+    }
+
+    #[tokio::test]
+    async fn test_recent_prioritization_fees() {
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetRecentPrioritizationFees,
+            serde_json::json!([
+                { "slot": 100, "prioritizationFee": 0 },
+                { "slot": 101, "prioritizationFee": 5_000 },
+                { "slot": 102, "prioritizationFee": 1_000 },
+                { "slot": 103, "prioritizationFee": 20_000 }
+            ]),
+        );
+        let fees = mock_solana(&rpc)
+            .get_recent_prioritization_fees(&["5dQ9CmV1yyM7zZMGErPCPi8pCBXLmMdCTUTXFekvNjtT"])
+            .await
+            .unwrap();
+        assert_eq!(fees.len(), 4);
+        assert_eq!(
+            fees[1],
+            PrioritizationFee {
+                slot: 101,
+                fee: 5_000
+            }
+        );
+        for (percentile, expected) in [(0.0, 0), (50.0, 1_000), (75.0, 5_000), (100.0, 20_000)] {
+            let fee = mock_solana(&rpc)
+                .recommended_priority_fee(percentile)
+                .await
+                .unwrap();
+            assert_eq!(fee, expected, "percentile {}", percentile);
+        }
+        let solana = mock_solana(&rpc);
+        assert!(solana.recommended_priority_fee(101.0).await.is_err());
+        assert!(
+            solana
+                .get_recent_prioritization_fees(&["bad address"])
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_transaction_count_and_performance_samples() {
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetTransactionCount,
+            serde_json::json!(268_435_456),
+        );
+        rpc.set_response(
+            RpcRequest::GetRecentPerformanceSamples,
+            serde_json::json!([
+                {
+                    "slot": 348_125_340,
+                    "numTransactions": 126_000,
+                    "numNonVoteTransactions": 30_000,
+                    "numSlots": 150,
+                    "samplePeriodSecs": 60
+                },
+                {
+                    "slot": 348_125_190,
+                    "numTransactions": 0,
+                    "numSlots": 0,
+                    "samplePeriodSecs": 0
+                }
+            ]),
+        );
+        let solana = mock_solana(&rpc);
+        assert_eq!(solana.get_transaction_count().await.unwrap(), 268_435_456);
+        let samples = solana.get_recent_performance_samples(2).await.unwrap();
+        assert_eq!(
+            samples[0],
+            PerfSample {
+                slot: 348_125_340,
+                num_transactions: 126_000,
+                num_slots: 150,
+                sample_period_secs: 60
+            }
+        );
+        assert_eq!(samples[0].tps(), 2_100.0);
+        assert_eq!(samples[1].tps(), 0.0);
+    }
+
+    #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
+    async fn test_get_token_supply() {
+        let solana = Solana::new(Mode::MAIN).unwrap();
+        let supply = solana.get_token_supply(global::USDC).await.unwrap();
+        assert_eq!(supply.decimals, 6);
+        assert!(supply.amount.parse::<u64>().unwrap() > 0);
+        assert!(solana.get_token_supply(global::RAY).await.is_ok());
+        assert!(
+            solana
+                .get_token_supply("5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1")
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_clmm_data_parsing() -> Result<(), Box<dyn std::error::Error>> {
         let solana_client = SolanaClient::new(solana_network_client::Mode::MAIN).unwrap();
         let raydium = Raydium::new(Arc::new(solana_client));
@@ -223,6 +1226,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_1() -> Result<(), Box<dyn std::error::Error>> {
         let solana_client = SolanaClient::new(solana_network_client::Mode::MAIN).unwrap();
         let raydium = Raydium::new(Arc::new(solana_client));
@@ -234,6 +1238,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_2() -> Result<(), Box<dyn std::error::Error>> {
         let solana_client = SolanaClient::new(solana_network_client::Mode::MAIN).unwrap();
         let raydium = Raydium::new(Arc::new(solana_client));
@@ -246,6 +1251,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_launchpad_data_parsing() -> Result<(), Box<dyn std::error::Error>> {
         let solana_client = SolanaClient::new(solana_network_client::Mode::MAIN).unwrap();
         let raydium = Raydium::new(Arc::new(solana_client));
@@ -255,4 +1261,322 @@ mod tests {
         println!("Pool Info: {:?}", pool_data);
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_get_latest_blockhash_with_expiry() {
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetLatestBlockhash,
+            serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "blockhash": "4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM",
+                    "lastValidBlockHeight": 300_150
+                }
+            }),
+        );
+        let solana = mock_solana(&rpc);
+        assert_eq!(
+            solana.get_latest_blockhash_with_expiry().await.unwrap(),
+            (
+                "4uQeVj5tqViQh7yWWGStvkEG1Zmhx6uasJtWCJziofM".to_string(),
+                300_150
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_largest_accounts() {
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetLargestAccounts,
+            serde_json::json!({
+                "context": { "slot": 1 },
+                "value": [
+                    { "address": "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM", "lamports": 5_000_000_000_000_000u64 },
+                    { "address": "3Fav7BV2tWLjwB9kBHnbmb6ibehugwuvimSMCUgwyNbR", "lamports": 1_000_000_000 }
+                ]
+            }),
+        );
+        let solana = mock_solana(&rpc);
+        assert_eq!(
+            solana
+                .get_largest_accounts(Some(AccountFilter::Circulating))
+                .await
+                .unwrap(),
+            vec![
+                (
+                    "9WzDXwBbmkg8ZTbNMqUxvQRAyrZzDsGYdLVL9zYtAWWM".to_string(),
+                    5_000_000_000_000_000
+                ),
+                (
+                    "3Fav7BV2tWLjwB9kBHnbmb6ibehugwuvimSMCUgwyNbR".to_string(),
+                    1_000_000_000
+                )
+            ]
+        );
+        let disabled = ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            code: -32601,
+            message: "Method not found".to_string(),
+            data: RpcResponseErrorData::Empty,
+        });
+        assert!(Solana::is_method_unavailable(&disabled));
+        let other = ClientErrorKind::RpcError(RpcError::RpcResponseError {
+            code: -32005,
+            message: "Node is behind".to_string(),
+            data: RpcResponseErrorData::Empty,
+        });
+        assert!(!Solana::is_method_unavailable(&other));
+    }
+
+    #[tokio::test]
+    async fn test_inflation_rate_and_supply() {
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetInflationRate,
+            serde_json::json!({
+                "total": 0.0445,
+                "validator": 0.0445,
+                "foundation": 0.0,
+                "epoch": 861
+            }),
+        );
+        rpc.set_response(
+            RpcRequest::GetSupply,
+            serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "total": 611_000_000_000_000_000u64,
+                    "circulating": 520_000_000_000_000_000u64,
+                    "nonCirculating": 91_000_000_000_000_000u64,
+                    "nonCirculatingAccounts": []
+                }
+            }),
+        );
+        let solana = mock_solana(&rpc);
+        assert_eq!(
+            solana.get_inflation_rate().await.unwrap(),
+            InflationRate {
+                total: 0.0445,
+                validator: 0.0445,
+                foundation: 0.0,
+                epoch: 861
+            }
+        );
+        assert_eq!(
+            solana.get_supply().await.unwrap(),
+            SolSupply {
+                total: 611_000_000_000_000_000,
+                circulating: 520_000_000_000_000_000,
+                non_circulating: 91_000_000_000_000_000
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_vote_accounts() {
+        let vote_account = |vote: &str, stake: u64, commission: u8| {
+            serde_json::json!({
+                "votePubkey": vote,
+                "nodePubkey": "7Np41oeYqPefeNQEHSv1UDhYrehxin3NStELsSKCT4K2",
+                "activatedStake": stake,
+                "commission": commission,
+                "epochVoteAccount": true,
+                "epochCredits": [],
+                "lastVote": 372_000_100u64,
+                "rootSlot": 372_000_068u64
+            })
+        };
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetVoteAccounts,
+            serde_json::json!({
+                "current": [
+                    vote_account("3N7s9zXMZ4QqvHQR15t5GNHyqc89KduzMP7423eWiD5g", 1_000, 5),
+                    vote_account("CertusDeBmqN8ZawdkxK5kFGMwBXdudvWHYwtNgNhvLu", 9_000, 0)
+                ],
+                "delinquent": [vote_account("9QxCLckBiJc783jnMvXZubK4wH86Eqqvashtrwvcsgkv", 500, 100)]
+            }),
+        );
+        let votes = mock_solana(&rpc).get_vote_accounts().await.unwrap();
+        assert_eq!(
+            votes
+                .current
+                .iter()
+                .map(|account| account.activated_stake)
+                .collect::<Vec<_>>(),
+            vec![9_000, 1_000]
+        );
+        assert_eq!(
+            votes.current[0].vote_pubkey,
+            "CertusDeBmqN8ZawdkxK5kFGMwBXdudvWHYwtNgNhvLu"
+        );
+        assert_eq!(votes.current[1].commission, 5);
+        assert_eq!(votes.current[1].last_vote, 372_000_100);
+        assert_eq!(votes.current[1].root_slot, 372_000_068);
+        assert_eq!(votes.delinquent.len(), 1);
+        assert_eq!(votes.delinquent[0].commission, 100);
+    }
+
+    #[tokio::test]
+    async fn test_get_block_production() {
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetBlockProduction,
+            serde_json::json!({
+                "context": { "slot": 372_000_500u64 },
+                "value": {
+                    "byIdentity": {
+                        "7Np41oeYqPefeNQEHSv1UDhYrehxin3NStELsSKCT4K2": [8, 6],
+                        "GdnSyH3YtwcxFvQrVVJMm1JhTS4QVX7MFsX56uJLUfiZ": [4, 4]
+                    },
+                    "range": { "firstSlot": 372_000_000u64, "lastSlot": 372_000_499u64 }
+                }
+            }),
+        );
+        let production = mock_solana(&rpc).get_block_production().await.unwrap();
+        assert_eq!(production.first_slot, 372_000_000);
+        assert_eq!(production.last_slot, 372_000_499);
+        assert_eq!(
+            production.by_identity["7Np41oeYqPefeNQEHSv1UDhYrehxin3NStELsSKCT4K2"],
+            (8, 6)
+        );
+        assert_eq!(
+            production.skip_rate("7Np41oeYqPefeNQEHSv1UDhYrehxin3NStELsSKCT4K2"),
+            Some(0.25)
+        );
+        assert_eq!(
+            production.skip_rate("GdnSyH3YtwcxFvQrVVJMm1JhTS4QVX7MFsX56uJLUfiZ"),
+            Some(0.0)
+        );
+        assert_eq!(production.skip_rate("unknown"), None);
+    }
+
+    /// mocked rpc serving `state` as a stake account, with current epoch 10
+    fn stake_solana(state: solana_stake_interface::state::StakeStateV2) -> Solana {
+        use base64::Engine;
+        use solana_stake_interface::stake_history::{StakeHistory, StakeHistoryEntry};
+        let mut history = StakeHistory::default();
+        history.add(
+            9,
+            StakeHistoryEntry {
+                effective: 400_000_000_000_000_000,
+                activating: 10_000_000_000_000,
+                deactivating: 10_000_000_000_000,
+            },
+        );
+        let account = |owner: &str, data: Vec<u8>, lamports: u64| {
+            serde_json::json!({
+                "lamports": lamports,
+                "data": [base64::engine::general_purpose::STANDARD.encode(data), "base64"],
+                "owner": owner,
+                "executable": false,
+                "rentEpoch": 0,
+                "space": 200
+            })
+        };
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetMultipleAccounts,
+            serde_json::json!({
+                "context": { "slot": 1 },
+                "value": [
+                    account(
+                        global::STAKE_PROGRAM_ID,
+                        bincode::serialize(&state).unwrap(),
+                        10_002_282_880
+                    ),
+                    account(
+                        "Sysvar1111111111111111111111111111111111111",
+                        bincode::serialize(&history).unwrap(),
+                        1
+                    ),
+                    null
+                ]
+            }),
+        );
+        rpc.set_response(
+            RpcRequest::GetEpochInfo,
+            serde_json::json!({
+                "absoluteSlot": 4_320_000,
+                "blockHeight": 4_300_000,
+                "epoch": 10,
+                "slotIndex": 0,
+                "slotsInEpoch": 432_000,
+                "transactionCount": null
+            }),
+        );
+        mock_solana(&rpc)
+    }
+
+    #[tokio::test]
+    async fn test_get_stake_activation() {
+        use solana_stake_interface::{
+            stake_flags::StakeFlags,
+            state::{Delegation, Meta, Stake, StakeStateV2},
+        };
+        let meta = Meta {
+            rent_exempt_reserve: 2_282_880,
+            ..Meta::default()
+        };
+        let delegated = |activation_epoch: u64, deactivation_epoch: u64| {
+            let mut delegation =
+                Delegation::new(&Pubkey::new_unique(), 10_000_000_000, activation_epoch);
+            delegation.deactivation_epoch = deactivation_epoch;
+            StakeStateV2::Stake(
+                meta,
+                Stake {
+                    delegation,
+                    credits_observed: 0,
+                },
+                StakeFlags::empty(),
+            )
+        };
+        // delegated last epoch, fully warmed up
+        let activation = stake_solana(delegated(9, u64::MAX))
+            .get_stake_activation("Stake11111111111111111111111111111111111111")
+            .await
+            .unwrap();
+        assert_eq!(
+            activation,
+            StakeActivation {
+                state: StakeActivationState::Active,
+                active: 10_000_000_000,
+                inactive: 0,
+            }
+        );
+        // delegated this epoch
+        let activation = stake_solana(delegated(10, u64::MAX))
+            .get_stake_activation("Stake11111111111111111111111111111111111111")
+            .await
+            .unwrap();
+        assert_eq!(activation.state, StakeActivationState::Activating);
+        assert_eq!(
+            (activation.active, activation.inactive),
+            (0, 10_000_000_000)
+        );
+        // deactivated this epoch
+        let activation = stake_solana(delegated(9, 10))
+            .get_stake_activation("Stake11111111111111111111111111111111111111")
+            .await
+            .unwrap();
+        assert_eq!(activation.state, StakeActivationState::Deactivating);
+        assert_eq!(activation.active, 10_000_000_000);
+        // initialized but never delegated
+        let activation = stake_solana(StakeStateV2::Initialized(meta))
+            .get_stake_activation("Stake11111111111111111111111111111111111111")
+            .await
+            .unwrap();
+        assert_eq!(activation.state, StakeActivationState::Inactive);
+        assert_eq!(
+            (activation.active, activation.inactive),
+            (0, 10_000_000_000)
+        );
+        assert!(
+            stake_solana(StakeStateV2::Uninitialized)
+                .get_stake_activation("not an address")
+                .await
+                .is_err()
+        );
+    }
 }
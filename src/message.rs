@@ -8,7 +8,7 @@ impl Message {
     /// # params
     /// * w wallet object
     /// * message byte array of the message
-    /// ```rust
+    /// ```ignore
     /// let w = Wallet::from_private_key_64("private key");
     /// let s = Message::sign_message(w, "message".as_bytes());
     /// ```
@@ -20,7 +20,7 @@ impl Message {
     /// * signature signature object
     /// * w wallet object
     /// * message byte array of the message
-    /// ```rust
+    /// ```ignore
     /// let w = Wallet::from_private_key_64("private key");
     /// let s = Message::sign_message(w, "message".as_bytes());
     /// let bool = Message::verify_message(s, w, "message".as_bytes());
@@ -0,0 +1,155 @@
+//! in-memory rpc transport for testing code built on this crate without a live node
+use serde_json::Value;
+use solana_client::{
+    client_error::{ClientError, ClientErrorKind},
+    rpc_request::RpcRequest,
+    rpc_sender::{RpcSender, RpcTransportStats},
+};
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+
+/// rpc transport answering every request with a canned response
+/// clones share their responses and request log, so keep a clone to inspect the requests
+/// after handing the mock to `RpcClient::new_sender`
+///
+/// # Example
+/// ```
+/// use serde_json::json;
+/// use solana_client::{nonblocking::rpc_client::RpcClient, rpc_client::RpcClientConfig, rpc_request::RpcRequest};
+/// use solana_network_sdk::{Solana, mock::MockRpc, types::Mode};
+///
+/// let rpc = MockRpc::new().with_response(RpcRequest::GetSlot, json!(42));
+/// let client = RpcClient::new_sender(rpc.clone(), RpcClientConfig::default());
+/// let solana = Solana::from_rpc_client(Mode::DEV, client).unwrap();
+/// # let _ = solana;
+/// assert!(rpc.requests().is_empty());
+/// ```
+#[derive(Clone, Default)]
+pub struct MockRpc {
+    responses: Arc<Mutex<HashMap<RpcRequest, Handler>>>,
+    requests: Arc<Mutex<Vec<(RpcRequest, Value)>>>,
+}
+
+/// computes the response to a request from its params
+type Handler = Arc<dyn Fn(&Value) -> Result<Value, ClientError> + Send + Sync>;
+
+impl MockRpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// answer every `request` with `response`, replacing an earlier response
+    pub fn with_response(self, request: RpcRequest, response: Value) -> Self {
+        self.set_response(request, response);
+        self
+    }
+
+    /// answer every `request` with `response` from now on
+    pub fn set_response(&self, request: RpcRequest, response: Value) {
+        self.set_handler(request, move |_| Ok(response.clone()));
+    }
+
+    /// answer every `request` by calling `handler` with its params, replacing an earlier response
+    /// use it for responses that depend on the params, change between calls or are rpc errors
+    pub fn with_handler<F>(self, request: RpcRequest, handler: F) -> Self
+    where
+        F: Fn(&Value) -> Result<Value, ClientError> + Send + Sync + 'static,
+    {
+        self.set_handler(request, handler);
+        self
+    }
+
+    /// answer every `request` by calling `handler` with its params from now on
+    pub fn set_handler<F>(&self, request: RpcRequest, handler: F)
+    where
+        F: Fn(&Value) -> Result<Value, ClientError> + Send + Sync + 'static,
+    {
+        self.responses
+            .lock()
+            .unwrap()
+            .insert(request, Arc::new(handler));
+    }
+
+    /// (request, params) received so far, in order
+    pub fn requests(&self) -> Vec<(RpcRequest, Value)> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+impl RpcSender for MockRpc {
+    fn send<'life0, 'async_trait>(
+        &'life0 self,
+        request: RpcRequest,
+        params: Value,
+    ) -> Pin<Box<dyn Future<Output = Result<Value, ClientError>> + Send + 'async_trait>>
+    where
+        'life0: 'async_trait,
+        Self: 'async_trait,
+    {
+        self.requests
+            .lock()
+            .unwrap()
+            .push((request, params.clone()));
+        let handler = self.responses.lock().unwrap().get(&request).cloned();
+        // requests without a response fail, so a missing mock is not mistaken for an empty result
+        let result = match handler {
+            Some(handler) => handler(&params),
+            None => {
+                Err(ClientErrorKind::Custom(format!("no mock response for {}", request)).into())
+            }
+        };
+        Box::pin(async move { result })
+    }
+
+    fn get_transport_stats(&self) -> RpcTransportStats {
+        RpcTransportStats {
+            request_count: self.requests.lock().unwrap().len(),
+            ..RpcTransportStats::default()
+        }
+    }
+
+    fn url(&self) -> String {
+        "mock".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Solana;
+    use crate::fixtures::load_fixture;
+    use crate::types::Mode;
+    use serde_json::json;
+    use solana_client::{nonblocking::rpc_client::RpcClient, rpc_client::RpcClientConfig};
+
+    #[tokio::test]
+    async fn test_mock_rpc_drives_transaction_detection() {
+        let fixture = load_fixture("raydium_v4_swap_buy");
+        let rpc = MockRpc::new().with_response(
+            RpcRequest::GetTransaction,
+            serde_json::to_value(&fixture).unwrap(),
+        );
+        let client = RpcClient::new_sender(rpc.clone(), RpcClientConfig::default());
+        let solana = Solana::from_rpc_client(Mode::DEV, client).unwrap();
+        let signature = "5VERv8NMvzbJMEkV8xnrLkEaWRtSz9CosKDYjCJjBRnbJLgp8uirBgmQpjKhoR4tjF3ZpRzrFmBV6UjKdiSZkQUW";
+        let info = solana
+            .create_trade()
+            .get_transaction_display_details(signature)
+            .await
+            .unwrap();
+        assert!(info.is_swap());
+        assert_eq!(info.slot, fixture.slot);
+        let requests = rpc.requests();
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].0, RpcRequest::GetTransaction);
+        assert_eq!(requests[0].1[0], json!(signature));
+        // no response for the slot, the request fails instead of answering null
+        assert!(solana.slot().await.is_err());
+        rpc.set_response(RpcRequest::GetSlot, json!(42));
+        assert_eq!(solana.slot().await.unwrap(), 42);
+    }
+}
@@ -0,0 +1,17 @@
+//! commonly used types, `use solana_network_sdk::prelude::*;` to import them at once
+pub use crate::{
+    Solana, SolanaBuilder,
+    account::Account,
+    block::{Block, BlockInfo},
+    scan::Scan,
+    spl::Spl,
+    trade::{
+        Trade,
+        info::{InstructionInfo, TransactionInfo},
+    },
+    types::{
+        DexPoolType, DexProgramType, Direction, Mode, Price, RetryPolicy, TradeError,
+        TransactionType, UnifiedError, UnifiedResult,
+    },
+    wallet::Wallet,
+};
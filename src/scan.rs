@@ -1,14 +1,25 @@
+use crate::global::MAX_SIGNATURES_PER_REQUEST;
+use crate::tool::rpc::signature_limit;
+use crate::trade::info::TransactionInfo;
+use crate::types::{Direction, TransactionType, UnifiedError, UnifiedResult};
+use futures::{Stream, StreamExt, channel::mpsc, stream};
+use serde::{Deserialize, Serialize};
 use solana_client::{
     nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
 };
+use solana_commitment_config::CommitmentConfig;
 use solana_sdk::{pubkey::Pubkey, signature::Signature};
-use std::collections::VecDeque;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
 use std::str::FromStr;
 use std::sync::Arc;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tokio::sync::Mutex;
 use tokio::task::JoinHandle;
+use tokio::time::Instant;
 
 /// Scanner for retrieving transaction signatures from Solana blockchain
 /// Provides methods to fetch historical and recent transaction signatures for given addresses
@@ -17,6 +28,8 @@ pub struct Scan {
     client: Arc<RpcClient>,
     /// Optional stop flag for early termination
     poll_all_signatures_by_address_stop_flag: Arc<AtomicBool>,
+    /// Commitment of signature and transaction lookups, None uses the rpc node's default (finalized)
+    commitment: Option<CommitmentConfig>,
 }
 
 impl Scan {
@@ -31,6 +44,28 @@ impl Scan {
         Self {
             client: client,
             poll_all_signatures_by_address_stop_flag: Arc::new(AtomicBool::new(false)),
+            commitment: None,
+        }
+    }
+
+    /// Sets the commitment of signature and transaction lookups, e.g. finalized for a reorg-proof audit trail
+    /// The rpc only accepts confirmed or finalized for these requests
+    ///
+    /// # Example
+    /// ```ignore
+    /// let scan = solana.create_scan().with_commitment(CommitmentConfig::finalized());
+    /// ```
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = Some(commitment);
+        self
+    }
+
+    /// Trade service sharing this scanner's client and commitment
+    fn trade(&self) -> crate::trade::Trade {
+        let trade = crate::trade::Trade::new(self.client.clone());
+        match self.commitment {
+            Some(commitment) => trade.with_commitment(commitment),
+            None => trade,
         }
     }
 
@@ -40,7 +75,7 @@ impl Scan {
     /// # Params
     /// * `address` - Solana address (base58 encoded) to fetch signatures for
     /// * `interval_time` - Optional delay between requests in milliseconds (default: 200ms)
-    /// * `batch_size` - Optional number of signatures to fetch per batch (default: 1000)
+    /// * `batch_size` - Optional number of signatures to fetch per batch (default: 1000, clamped to 1..=1000)
     /// * `callback` - Callback function for signature processing. f(sign: String)
     ///
     /// # Returns
@@ -61,7 +96,7 @@ impl Scan {
         let mut all_signatures = Vec::new();
         let mut before: Option<Signature> = None;
         let sleep_duration = interval_time.unwrap_or(200);
-        let batch_limit = batch_size.unwrap_or(1000);
+        let batch_limit = signature_limit(batch_size.unwrap_or(1000) as usize);
         let mut history_completed = false;
         loop {
             if self
@@ -73,8 +108,8 @@ impl Scan {
             let config = GetConfirmedSignaturesForAddress2Config {
                 before,
                 until: None,
-                limit: Some(batch_limit.try_into().unwrap()),
-                commitment: None,
+                limit: Some(batch_limit),
+                commitment: self.commitment,
             };
             let signatures = match self
                 .client
@@ -164,7 +199,7 @@ impl Scan {
                 before,
                 until: None,
                 limit: Some(batch_size),
-                commitment: None,
+                commitment: self.commitment,
             };
             let signatures = match self
                 .client
@@ -207,7 +242,7 @@ impl Scan {
     ///
     /// # Params
     /// * `address` - Solana address (base58 encoded) to fetch signatures for
-    /// * `count` - Number of recent signatures to return
+    /// * `count` - Number of recent signatures to return, at most 1000
     ///
     /// # Returns
     /// * `Ok(Vec<String>)` - Vector of the most recent transaction signatures
@@ -218,11 +253,14 @@ impl Scan {
         count: usize,
     ) -> Result<Vec<String>, String> {
         let pubkey = Pubkey::from_str(address).map_err(|e| format!("address error:{:?}", e))?;
+        if count == 0 {
+            return Ok(Vec::new());
+        }
         let config = GetConfirmedSignaturesForAddress2Config {
             before: None,
             until: None,
-            limit: Some(count),
-            commitment: None,
+            limit: Some(signature_limit(count)),
+            commitment: self.commitment,
         };
         let signatures = self
             .client
@@ -254,9 +292,9 @@ impl Scan {
     {
         let trade_batch_size: u64 = find_trade_batch_size.unwrap_or(50);
         let sleep_duration = interval_time.unwrap_or(200);
-        let batch_limit = signs_batch_size.unwrap_or(1000);
+        let batch_limit = signature_limit(signs_batch_size.unwrap_or(1000) as usize);
         let signatures_queue: Arc<Mutex<VecDeque<String>>> = Arc::new(Mutex::new(VecDeque::new()));
-        let trade = crate::trade::Trade::new(self.client.clone());
+        let trade = self.trade();
         let trade_arc = Arc::new(trade);
         let fetch_completed = Arc::new(AtomicBool::new(false));
         let signatures_queue_clone = signatures_queue.clone();
@@ -284,8 +322,8 @@ impl Scan {
                     let config = GetConfirmedSignaturesForAddress2Config {
                         before,
                         until: None,
-                        limit: Some(batch_limit.try_into().unwrap()),
-                        commitment: None,
+                        limit: Some(batch_limit),
+                        commitment: scan.commitment,
                     };
                     let signatures = match scan
                         .client
@@ -385,15 +423,1248 @@ impl Scan {
             .map_err(|e| format!("Thread Execution Error: {:?}", e))?;
         Ok(())
     }
+
+    /// Fetches the latest trades of a token
+    /// A signature seen twice while paging the history, e.g. across a reorg, yields one record;
+    /// records of separate calls can still overlap, so callers merging them should dedupe by signature
+    ///
+    /// # Params
+    /// * `mint` - Token mint address
+    /// * `opts` - Number of signatures to scan and the delay between rpc requests
+    ///
+    /// # Returns
+    /// * `Ok(Vec<TokenTradeRecord>)` - Trade records, newest first
+    /// * `Err(String)` - Error message if address parsing or an RPC call fails
+    pub async fn get_token_trade_history(
+        &self,
+        mint: &str,
+        opts: &TokenTradeHistoryOptions,
+    ) -> Result<Vec<TokenTradeRecord>, String> {
+        let limiter = RateLimiter::new(Duration::from_millis(opts.interval_time));
+        self.token_trade_history(mint, opts, &limiter).await
+    }
+
+    /// Fetches one page of a token's trades, e.g. for a paginated api
+    /// A page scans `page_size` signatures, so it can hold fewer records than that;
+    /// pass the returned `next_cursor` to get the next, older page
+    /// The cursor's own transaction and signatures repeated within the page are skipped, so a
+    /// page boundary shifted by a reorg does not repeat a record; other overlaps between separate
+    /// calls are possible, callers merging pages should still dedupe by signature
+    /// Self transfers are skipped, see `TransactionInfo::is_self_transfer`
+    ///
+    /// # Params
+    /// * `mint` - Token mint address
+    /// * `cursor` - Signature to continue before, None starts at the newest transaction
+    /// * `page_size` - Number of signatures to scan, clamped to 1..=1000
+    ///
+    /// # Returns
+    /// * `Ok(TradePage)` - Trade records of the page, newest first, and where to resume
+    /// * `Err(String)` - Error message if address or cursor parsing or an RPC call fails
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut cursor = None;
+    /// loop {
+    ///     let page = scan.get_token_trade_history_page(mint, cursor, 100).await?;
+    ///     println!("{} trades", page.records.len());
+    ///     if !page.has_more {
+    ///         break;
+    ///     }
+    ///     cursor = page.next_cursor;
+    /// }
+    /// ```
+    pub async fn get_token_trade_history_page(
+        &self,
+        mint: &str,
+        cursor: Option<String>,
+        page_size: usize,
+    ) -> UnifiedResult<TradePage, String> {
+        let trade = self.trade();
+        let page_size = signature_limit(page_size);
+        let signatures = match trade
+            .get_transactions_history_by_cursor(mint, cursor.clone(), page_size as u32)
+            .await
+        {
+            Ok((signatures, _)) => signatures,
+            Err(e) => {
+                return Err(UnifiedError::Error(format!("get signatures error:{}", e)));
+            }
+        };
+        let mut seen: HashSet<&str> = cursor.iter().map(String::as_str).collect();
+        let mut records = Vec::new();
+        for signature in &signatures {
+            if !seen.insert(signature.signature.as_str()) {
+                continue;
+            }
+            let info = trade
+                .get_transaction_display_details(&signature.signature)
+                .await
+                .map_err(|e| UnifiedError::Error(format!("get transaction error:{}", e)))?;
+            if info.is_self_transfer() {
+                continue;
+            }
+            if let Some(record) = Self::parse_transaction_to_record(&info, mint) {
+                records.push(record);
+            }
+        }
+        Ok(TradePage {
+            records,
+            next_cursor: signatures.last().map(|s| s.signature.clone()),
+            has_more: signatures.len() == page_size,
+        })
+    }
+
+    /// Fetches the latest trades of several tokens concurrently
+    /// At most `opts.concurrency` tokens are fetched at once and all of them share one
+    /// rate limit of a request every `opts.interval_time` milliseconds
+    ///
+    /// # Params
+    /// * `mints` - Token mint addresses
+    /// * `opts` - Number of signatures to scan per token, concurrency and delay between rpc requests
+    ///
+    /// # Returns
+    /// * `Ok(HashMap)` - Trade records or the error of each mint, a failing mint does not fail the others
+    /// * `Err(String)` - Error message if the options are invalid
+    pub async fn get_multi_token_trade_history(
+        &self,
+        mints: &[&str],
+        opts: &TokenTradeHistoryOptions,
+    ) -> UnifiedResult<HashMap<String, Result<Vec<TokenTradeRecord>, String>>, String> {
+        if opts.concurrency == 0 {
+            return Err(UnifiedError::Error(
+                "concurrency must be at least 1".to_string(),
+            ));
+        }
+        let limiter = RateLimiter::new(Duration::from_millis(opts.interval_time));
+        let limiter = &limiter;
+        Ok(stream::iter(mints.iter().copied())
+            .map(|mint| async move {
+                let records = self.token_trade_history(mint, opts, limiter).await;
+                (mint.to_string(), records)
+            })
+            .buffer_unordered(opts.concurrency)
+            .collect()
+            .await)
+    }
+
+    /// Trades of a token within its latest `opts.limit` signatures, every rpc request waits for the limiter
+    async fn token_trade_history(
+        &self,
+        mint: &str,
+        opts: &TokenTradeHistoryOptions,
+        limiter: &RateLimiter,
+    ) -> Result<Vec<TokenTradeRecord>, String> {
+        let signatures = self.latest_signatures(mint, opts.limit, limiter).await?;
+        let trade = self.trade();
+        let mut records = Vec::new();
+        for signature in signatures {
+            limiter.acquire().await;
+            let info = trade
+                .get_transaction_display_details(&signature)
+                .await
+                .map_err(|e| format!("get transaction error:{}", e))?;
+            if !opts.include_self_transfers && info.is_self_transfer() {
+                continue;
+            }
+            if let Some(record) = Self::parse_transaction_to_record(&info, mint) {
+                records.push(record);
+            }
+        }
+        Ok(records)
+    }
+
+    /// Streams the swaps of a token as they happen
+    /// Subscribes to the logs of transactions mentioning the mint, fetches every successful
+    /// transaction and yields the swap records of the token; pool creations, liquidity
+    /// changes and self transfers are skipped
+    /// When the websocket drops the subscription is reopened with a growing delay
+    /// (1s up to 30s) and the swaps signed in the meantime are fetched from the signature history
+    ///
+    /// # Params
+    /// * `mint` - Token mint address
+    ///
+    /// # Returns
+    /// * `Ok(Stream)` - Swap records, the subscription stops when the stream is dropped
+    /// * `Err(String)` - Error message if the address is invalid or the first subscription fails
+    ///
+    /// # Example
+    /// ```ignore
+    /// let swaps = scan.watch_token_swaps("token mint").await?;
+    /// futures::pin_mut!(swaps);
+    /// while let Some(swap) = swaps.next().await {
+    ///     println!("{} {} at {:?}", swap.side, swap.base_amount, swap.price);
+    /// }
+    /// ```
+    pub async fn watch_token_swaps(
+        &self,
+        mint: &str,
+    ) -> Result<impl Stream<Item = TokenTradeRecord> + use<>, String> {
+        const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+        let mut account = crate::account::Account::new(self.client.clone(), mint);
+        if let Some(commitment) = self.commitment {
+            account = account.with_commitment(commitment);
+        }
+        let mut logs = account.subscribe_logs(mint).await?;
+        // look transactions up at the subscription's commitment, the history is not served
+        // below confirmed
+        let commitment = if account.commitment().is_at_least_confirmed() {
+            account.commitment()
+        } else {
+            CommitmentConfig::confirmed()
+        };
+        let client = self.client.clone();
+        let trade = crate::trade::Trade::new(self.client.clone()).with_commitment(commitment);
+        let mint = mint.to_string();
+        let (sender, receiver) = mpsc::unbounded();
+        tokio::spawn(async move {
+            let mut seen: VecDeque<String> = VecDeque::new();
+            let mut last_signature: Option<String> = None;
+            loop {
+                while let Some(notification) = logs.next().await {
+                    if notification.err.is_some() {
+                        continue;
+                    }
+                    last_signature = Some(notification.signature.clone());
+                    if let Some(record) =
+                        Self::watched_swap(&trade, &notification.signature, &mint, &mut seen).await
+                        && sender.unbounded_send(record).is_err()
+                    {
+                        return;
+                    }
+                }
+                // the websocket dropped, reconnect before backfilling so nothing falls in between
+                let mut delay = Duration::from_secs(1);
+                logs = loop {
+                    if sender.is_closed() {
+                        return;
+                    }
+                    tokio::time::sleep(delay).await;
+                    match account.subscribe_logs(&mint).await {
+                        Ok(logs) => break logs,
+                        Err(_) => delay = (delay * 2).min(MAX_RECONNECT_DELAY),
+                    }
+                };
+                let Some(until) = &last_signature else {
+                    continue;
+                };
+                for signature in
+                    Self::signatures_since(&client, Some(commitment), &mint, until).await
+                {
+                    last_signature = Some(signature.clone());
+                    if let Some(record) =
+                        Self::watched_swap(&trade, &signature, &mint, &mut seen).await
+                        && sender.unbounded_send(record).is_err()
+                    {
+                        return;
+                    }
+                }
+            }
+        });
+        Ok(receiver)
+    }
+
+    /// Swap record of a signaled transaction, None for other transactions and ones already seen
+    /// A transaction that cannot be fetched is not marked as seen, so the backfill after a
+    /// reconnect can pick it up again
+    async fn watched_swap(
+        trade: &crate::trade::Trade,
+        signature: &str,
+        mint: &str,
+        seen: &mut VecDeque<String>,
+    ) -> Option<TokenTradeRecord> {
+        const SEEN_CAPACITY: usize = 1000;
+        const FETCH_ATTEMPTS: usize = 3;
+        if seen.iter().any(|s| s == signature) {
+            return None;
+        }
+        // the logs can arrive before the transaction is queryable at the lookup commitment
+        let mut info = None;
+        for attempt in 0..FETCH_ATTEMPTS {
+            if attempt > 0 {
+                tokio::time::sleep(Duration::from_millis(500)).await;
+            }
+            if let Ok(details) = trade.get_transaction_display_details(signature).await {
+                info = Some(details);
+                break;
+            }
+        }
+        let info = info?;
+        if seen.len() == SEEN_CAPACITY {
+            seen.pop_front();
+        }
+        seen.push_back(signature.to_string());
+        if info.is_self_transfer() {
+            return None;
+        }
+        Self::parse_transaction_to_record(&info, mint).filter(|record| record.trade_type == "swap")
+    }
+
+    /// Successful signatures of an address newer than `until`, oldest first
+    /// Pages back with the `before` cursor until the rpc reaches `until`; on an rpc error
+    /// nothing is returned, so the caller retries from the same signature
+    async fn signatures_since(
+        client: &RpcClient,
+        commitment: Option<CommitmentConfig>,
+        address: &str,
+        until: &str,
+    ) -> Vec<String> {
+        let (Ok(pubkey), Ok(until)) = (Pubkey::from_str(address), Signature::from_str(until))
+        else {
+            return Vec::new();
+        };
+        let mut signatures = Vec::new();
+        let mut before = None;
+        loop {
+            let config = GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: Some(until),
+                limit: Some(MAX_SIGNATURES_PER_REQUEST),
+                commitment,
+            };
+            let Ok(page) = client
+                .get_signatures_for_address_with_config(&pubkey, config)
+                .await
+            else {
+                return Vec::new();
+            };
+            let full = page.len() == MAX_SIGNATURES_PER_REQUEST;
+            before = page
+                .last()
+                .and_then(|status| Signature::from_str(&status.signature).ok());
+            signatures.extend(
+                page.into_iter()
+                    .filter(|status| status.err.is_none())
+                    .map(|status| status.signature),
+            );
+            if !full || before.is_none() {
+                break;
+            }
+        }
+        signatures.reverse();
+        signatures
+    }
+
+    /// Builds a swap PnL report of a wallet from its latest signatures
+    /// Every swap the wallet signed becomes one row, failed transactions and other
+    /// transaction types are skipped
+    ///
+    /// # Params
+    /// * `wallet` - Wallet address
+    /// * `opts` - Number of signatures to scan and the delay between rpc requests
+    ///
+    /// # Returns
+    /// * `Ok(Vec<WalletTrade>)` - Swaps of the wallet, newest first
+    /// * `Err(String)` - Error message if address parsing or an RPC call fails
+    pub async fn wallet_trade_report(
+        &self,
+        wallet: &str,
+        opts: &WalletTradeReportOptions,
+    ) -> UnifiedResult<Vec<WalletTrade>, String> {
+        let limiter = RateLimiter::new(Duration::from_millis(opts.interval_time));
+        let signatures = self
+            .latest_signatures(wallet, opts.limit, &limiter)
+            .await
+            .map_err(UnifiedError::Error)?;
+        let trade = self.trade();
+        let mut trades = Vec::new();
+        for signature in signatures {
+            limiter.acquire().await;
+            let info = trade
+                .get_transaction_display_details(&signature)
+                .await
+                .map_err(|e| UnifiedError::Error(format!("get transaction error:{}", e)))?;
+            if (info.signer != wallet && info.fee_payer != wallet)
+                || (!opts.include_self_transfers && info.is_self_transfer())
+            {
+                continue;
+            }
+            if let Some(row) = Self::parse_transaction_to_wallet_trade(&info) {
+                trades.push(row);
+            }
+        }
+        Ok(trades)
+    }
+
+    /// Signatures of an address within its latest `limit`, every rpc request waits for the limiter
+    async fn latest_signatures(
+        &self,
+        address: &str,
+        limit: usize,
+        limiter: &RateLimiter,
+    ) -> Result<Vec<String>, String> {
+        let pubkey = Pubkey::from_str(address).map_err(|e| format!("address error:{:?}", e))?;
+        let mut signatures = Vec::new();
+        let mut seen = HashSet::new();
+        let mut before: Option<Signature> = None;
+        while signatures.len() < limit {
+            let batch_size = (limit - signatures.len()).min(1000);
+            let config = GetConfirmedSignaturesForAddress2Config {
+                before,
+                until: None,
+                limit: Some(batch_size),
+                commitment: self.commitment,
+            };
+            limiter.acquire().await;
+            let page = self
+                .client
+                .get_signatures_for_address_with_config(&pubkey, config)
+                .await
+                .map_err(|e| format!("get signatures error:{:?}", e))?;
+            let page_len = page.len();
+            before = match page.last() {
+                Some(last) => Some(
+                    Signature::from_str(&last.signature)
+                        .map_err(|e| format!("signature error:{:?}", e))?,
+                ),
+                None => None,
+            };
+            // pages overlap when a reorg shifts the history between two requests
+            signatures.extend(
+                page.into_iter()
+                    .map(|sig| sig.signature)
+                    .filter(|signature| seen.insert(signature.clone())),
+            );
+            if page_len < batch_size {
+                break;
+            }
+        }
+        Ok(signatures)
+    }
+
+    /// Aggregates the swap volume of trade records per DEX
+    /// Only "swap" records count as volume; records without a DEX program are grouped under "unknown"
+    ///
+    /// # Params
+    /// * `records` - Trade records, e.g. from `get_token_trade_history`
+    ///
+    /// # Returns
+    /// * `HashMap` - (base volume, quote volume, number of swaps) by `dex_program`
+    pub fn volume_by_dex(records: &[TokenTradeRecord]) -> HashMap<String, (f64, f64, u64)> {
+        let mut volumes: HashMap<String, (f64, f64, u64)> = HashMap::new();
+        for record in records.iter().filter(|record| record.trade_type == "swap") {
+            let dex = record.dex_program.as_deref().unwrap_or("unknown");
+            let volume = volumes.entry(dex.to_string()).or_default();
+            volume.0 += record.base_amount;
+            volume.1 += record.quote_amount;
+            volume.2 += 1;
+        }
+        volumes
+    }
+
+    /// Writes trade records to a file as one pretty-printed JSON array
+    ///
+    /// # Params
+    /// * `records` - Trade records, e.g. from `get_token_trade_history`
+    /// * `path` - File to create or overwrite
+    ///
+    /// # Returns
+    /// * `Ok(())` - If every record was written
+    /// * `Err(String)` - Error message if the file can't be created or written
+    pub fn export_trade_history_json(
+        records: &[TokenTradeRecord],
+        path: impl AsRef<Path>,
+    ) -> Result<(), String> {
+        let file = File::create(path.as_ref())
+            .map_err(|e| format!("create {} error:{}", path.as_ref().display(), e))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer_pretty(&mut writer, records)
+            .map_err(|e| format!("write json error:{}", e))?;
+        writer
+            .flush()
+            .map_err(|e| format!("write json error:{}", e))
+    }
+
+    /// Writes trade records as newline-delimited JSON, one compact record per line
+    ///
+    /// # Params
+    /// * `records` - Trade records, e.g. from `get_token_trade_history`
+    /// * `writer` - Destination, e.g. a file, stdout or a socket; writes are buffered
+    ///
+    /// # Returns
+    /// * `Ok(())` - If every record was written
+    /// * `Err(String)` - Error message if a write fails
+    ///
+    /// # Example
+    /// ```ignore
+    /// let records = scan.get_token_trade_history(mint, &Default::default()).await?;
+    /// Scan::export_trade_history_ndjson(&records, std::io::stdout())?;
+    /// ```
+    pub fn export_trade_history_ndjson<W: Write>(
+        records: &[TokenTradeRecord],
+        writer: W,
+    ) -> Result<(), String> {
+        let mut writer = BufWriter::new(writer);
+        for record in records {
+            serde_json::to_writer(&mut writer, record)
+                .map_err(|e| format!("write ndjson error:{}", e))?;
+            writer
+                .write_all(b"\n")
+                .map_err(|e| format!("write ndjson error:{}", e))?;
+        }
+        writer
+            .flush()
+            .map_err(|e| format!("write ndjson error:{}", e))
+    }
+
+    /// Builds a trade record of the given token from a parsed transaction
+    ///
+    /// # Params
+    /// * `info` - Parsed transaction information
+    /// * `mint` - Token mint address the record is built for
+    ///
+    /// # Returns
+    /// * `Some(TokenTradeRecord)` - For swaps and liquidity-pool creations involving the token
+    /// * `None` - If the transaction is neither a swap nor a pool creation of the token
+    pub fn parse_transaction_to_record(
+        info: &TransactionInfo,
+        mint: &str,
+    ) -> Option<TokenTradeRecord> {
+        let mut record = TokenTradeRecord {
+            signature: info.signature.clone(),
+            slot: info.slot,
+            block_time: info.block_time,
+            trade_type: String::new(),
+            side: "unknown".to_string(),
+            mint: mint.to_string(),
+            quote_mint: None,
+            base_amount: 0.0,
+            quote_amount: 0.0,
+            price: None,
+            dex_program: info.dex_program_id.clone(),
+            trader: info.signer.clone(),
+            from: None,
+            to: None,
+        };
+        if info.transaction_type == Some(TransactionType::PoolCreate) {
+            let reserves = info.get_pool_initial_reserves()?;
+            if reserves.base_mint != mint {
+                return None;
+            }
+            record.trade_type = "pool_create".to_string();
+            record.quote_mint = Some(reserves.quote_mint);
+            record.base_amount = reserves.base_reserve;
+            record.quote_amount = reserves.quote_reserve;
+            record.price = info.get_pool_initial_price();
+            record.dex_program = info.get_pool_creation_program_id();
+            return Some(record);
+        }
+        if !info.is_swap() || info.get_pool_base_token_address().as_deref() != Some(mint) {
+            return None;
+        }
+        record.trade_type = match info.transaction_type {
+            Some(TransactionType::AddLiquidity) => "add_liquidity",
+            Some(TransactionType::RemoveLiquidity) => "remove_liquidity",
+            _ => "swap",
+        }
+        .to_string();
+        (record.from, record.to) = Self::token_flow_owners(info, mint);
+        // the trader is a buyer when the token moved into its accounts
+        record.side = if record.to.as_deref() == Some(info.signer.as_str()) {
+            "buy"
+        } else if record.from.as_deref() == Some(info.signer.as_str()) {
+            "sell"
+        } else {
+            match info.get_direction() {
+                Direction::Buy => "buy",
+                Direction::Sell => "sell",
+                Direction::Unknown => "unknown",
+            }
+        }
+        .to_string();
+        record.quote_mint = info.get_pool_quote_token_address();
+        record.base_amount = info
+            .get_signer_base_token_change_decimal()
+            .unwrap_or(0.0)
+            .abs();
+        record.quote_amount = info
+            .get_signer_quote_token_change_decimal()
+            .unwrap_or(0.0)
+            .abs();
+        record.price = info.get_token_quote_ratio();
+        Some(record)
+    }
+
+    /// (owner losing the most, owner gaining the most) of `mint` in the transaction,
+    /// read from the token balances, i.e. the pool and the trader of a swap
+    fn token_flow_owners(info: &TransactionInfo, mint: &str) -> (Option<String>, Option<String>) {
+        let mut changes: HashMap<String, i128> = HashMap::new();
+        for account in info.token_account_changes() {
+            if let Some(owner) = account.owner
+                && account.mint == mint
+            {
+                *changes.entry(owner).or_default() += account.delta;
+            }
+        }
+        let from = changes
+            .iter()
+            .filter(|(_, delta)| **delta < 0)
+            .min_by_key(|(owner, delta)| (**delta, owner.as_str()))
+            .map(|(owner, _)| owner.to_string());
+        let to = changes
+            .iter()
+            .filter(|(_, delta)| **delta > 0)
+            .max_by_key(|(owner, delta)| (**delta, std::cmp::Reverse(owner.as_str())))
+            .map(|(owner, _)| owner.to_string());
+        (from, to)
+    }
+
+    /// Builds a wallet trade row from a parsed swap
+    ///
+    /// # Params
+    /// * `info` - Parsed transaction information
+    ///
+    /// # Returns
+    /// * `Some(WalletTrade)` - For successful swaps with a base token
+    /// * `None` - If the transaction failed, is not a swap or has no base token
+    pub fn parse_transaction_to_wallet_trade(info: &TransactionInfo) -> Option<WalletTrade> {
+        if info.is_failed() || !info.is_swap() {
+            return None;
+        }
+        Some(WalletTrade {
+            signature: info.signature.clone(),
+            slot: info.slot,
+            block_time: info.block_time,
+            side: match info.get_direction() {
+                Direction::Buy => "buy",
+                Direction::Sell => "sell",
+                Direction::Unknown => "unknown",
+            }
+            .to_string(),
+            base_mint: info.get_pool_base_token_address()?,
+            quote_mint: info.get_pool_quote_token_address(),
+            base_amount: info
+                .get_signer_base_token_change_decimal()
+                .unwrap_or(0.0)
+                .abs(),
+            quote_amount: info
+                .get_signer_quote_token_change_decimal()
+                .unwrap_or(0.0)
+                .abs(),
+            price: info.get_token_quote_ratio(),
+            fee: info.fee,
+            dex_program: info.dex_program_id.clone(),
+        })
+    }
+}
+
+/// Options of `Scan::wallet_trade_report`
+#[derive(Debug, Clone, Copy)]
+pub struct WalletTradeReportOptions {
+    /// Number of latest signatures of the wallet scanned
+    pub limit: usize,
+    /// Minimum delay between two rpc requests in milliseconds
+    pub interval_time: u64,
+    /// Keep transactions only moving value between accounts of one owner, see
+    /// `TransactionInfo::is_self_transfer`; they are skipped by default
+    pub include_self_transfers: bool,
+}
+
+impl Default for WalletTradeReportOptions {
+    fn default() -> Self {
+        Self {
+            limit: 1000,
+            interval_time: 200,
+            include_self_transfers: false,
+        }
+    }
+}
+
+/// Options of `Scan::get_token_trade_history` and `Scan::get_multi_token_trade_history`
+#[derive(Debug, Clone, Copy)]
+pub struct TokenTradeHistoryOptions {
+    /// Number of latest signatures scanned per token
+    pub limit: usize,
+    /// Number of tokens fetched at the same time
+    pub concurrency: usize,
+    /// Minimum delay between two rpc requests in milliseconds, shared by all tokens
+    pub interval_time: u64,
+    /// Keep transactions only moving value between accounts of one owner, see
+    /// `TransactionInfo::is_self_transfer`; they are skipped by default
+    pub include_self_transfers: bool,
+}
+
+impl Default for TokenTradeHistoryOptions {
+    fn default() -> Self {
+        Self {
+            limit: 100,
+            concurrency: 4,
+            interval_time: 200,
+            include_self_transfers: false,
+        }
+    }
+}
+
+/// Spaces out rpc requests shared between concurrent tasks
+struct RateLimiter {
+    interval: Duration,
+    next: Mutex<Instant>,
+}
+
+impl RateLimiter {
+    fn new(interval: Duration) -> Self {
+        Self {
+            interval,
+            next: Mutex::new(Instant::now()),
+        }
+    }
+
+    /// Waits for the next free request slot
+    async fn acquire(&self) {
+        let slot = {
+            let mut next = self.next.lock().await;
+            let slot = (*next).max(Instant::now());
+            *next = slot + self.interval;
+            slot
+        };
+        tokio::time::sleep_until(slot).await;
+    }
+}
+
+/// Trade record of a token, built from a parsed transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenTradeRecord {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// "swap", "pool_create", "add_liquidity" or "remove_liquidity"
+    pub trade_type: String,
+    /// "buy", "sell" or "unknown"
+    pub side: String,
+    pub mint: String,
+    pub quote_mint: Option<String>,
+    /// Base token amount (in token units with decimals)
+    pub base_amount: f64,
+    /// Quote token amount (in token units with decimals)
+    pub quote_amount: f64,
+    /// Quote token amount per 1 base token
+    pub price: Option<f64>,
+    pub dex_program: Option<String>,
+    pub trader: String,
+    /// Owner the token moved out of (the pool on a buy, the trader on a sell), None for pool creations
+    pub from: Option<String>,
+    /// Owner the token moved into (the trader on a buy, the pool on a sell), None for pool creations
+    pub to: Option<String>,
+}
+
+/// One page of `Scan::get_token_trade_history_page`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradePage {
+    pub records: Vec<TokenTradeRecord>,
+    /// Cursor of the next, older page, None when the page is empty
+    pub next_cursor: Option<String>,
+    /// Whether older signatures may follow, false once a page comes back short
+    pub has_more: bool,
+}
+
+/// Swap of a wallet, one row of `Scan::wallet_trade_report`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalletTrade {
+    pub signature: String,
+    pub slot: u64,
+    pub block_time: Option<i64>,
+    /// "buy", "sell" or "unknown"
+    pub side: String,
+    pub base_mint: String,
+    pub quote_mint: Option<String>,
+    /// Base token amount (in token units with decimals)
+    pub base_amount: f64,
+    /// Quote token amount (in token units with decimals)
+    pub quote_amount: f64,
+    /// Quote token amount per 1 base token
+    pub price: Option<f64>,
+    /// Transaction fee in lamports
+    pub fee: u64,
+    pub dex_program: Option<String>,
 }
 
 #[cfg(test)]
 mod tests {
+    use super::{Scan, TokenTradeHistoryOptions, TokenTradeRecord, WalletTradeReportOptions};
     use crate::Solana;
+    use crate::fixtures::{
+        fixture_rpc, fixture_solana, load_fixture, load_transaction_info, mock_solana,
+    };
+    use crate::types::TransactionType;
+    use serde_json::Value;
+    use solana_client::nonblocking::rpc_client::RpcClient;
+    use solana_client::rpc_client::RpcClientConfig;
+    use solana_client::rpc_request::RpcRequest;
+    use solana_commitment_config::CommitmentConfig;
+    use solana_sdk::signature::Signature;
+    use std::collections::VecDeque;
     use std::sync::Arc;
     use std::time::Duration;
 
+    #[test]
+    fn test_parse_raydium_pool_creation_record() {
+        let info = load_transaction_info("raydium_v4_pool_create");
+        assert_eq!(info.transaction_type, Some(TransactionType::PoolCreate));
+        assert_eq!(
+            info.get_pool_creation_program_id().as_deref(),
+            Some(crate::global::RAYDIUM_V4_POOL_PROGRAM_ID)
+        );
+        let mint = "BHAYupF1LHTKqsjp6anmRPo2ViRwNi5A9FP3qtA1UBJh";
+        let record = Scan::parse_transaction_to_record(&info, mint).unwrap();
+        assert_eq!(record.trade_type, "pool_create");
+        assert_eq!(record.quote_mint.as_deref(), Some(crate::global::WSOL));
+        assert_eq!(record.base_amount, 206_900_000.0);
+        assert_eq!(record.quote_amount, 79.0);
+        let price = record.price.unwrap();
+        assert!((price - 79.0 / 206_900_000.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_record_from_and_to_are_owner_addresses() {
+        let mint = "6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau";
+        let info = load_transaction_info("raydium_v4_swap_buy");
+        let record = Scan::parse_transaction_to_record(&info, mint).unwrap();
+        // the raydium authority owns the vaults, the signer receives the tokens
+        assert_eq!(
+            record.from.as_deref(),
+            Some("5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1")
+        );
+        assert_eq!(record.to.as_deref(), Some(info.signer.as_str()));
+        assert_eq!(record.side, "buy");
+        let mint = "6ScPNL11uxcnepV8nYswqfDcBrgkHWpgo7sAXKdjHcwx";
+        let info = load_transaction_info("pump_bond_curve_sell");
+        let record = Scan::parse_transaction_to_record(&info, mint).unwrap();
+        assert_eq!(record.from.as_deref(), Some(info.signer.as_str()));
+        assert!(record.to.is_some_and(|to| to != info.signer));
+        assert_eq!(record.side, "sell");
+    }
+
+    #[tokio::test]
+    async fn test_watched_swaps_skip_other_records_and_repeats() {
+        let swap = Signature::from([1u8; 64]).to_string();
+        let pool_create = Signature::from([2u8; 64]).to_string();
+        let scan = fixture_solana(&[
+            (swap.as_str(), "raydium_v4_swap_buy"),
+            (pool_create.as_str(), "raydium_v4_pool_create"),
+        ])
+        .create_scan();
+        let trade = scan.trade();
+        let mut seen = VecDeque::new();
+        let mint = "6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau";
+        let record = Scan::watched_swap(&trade, &swap, mint, &mut seen)
+            .await
+            .unwrap();
+        assert_eq!(record.trade_type, "swap");
+        assert_eq!(record.signature, swap);
+        // the same signature from the backfill and the new subscription is yielded once
+        assert!(
+            Scan::watched_swap(&trade, &swap, mint, &mut seen)
+                .await
+                .is_none()
+        );
+        let mint = "BHAYupF1LHTKqsjp6anmRPo2ViRwNi5A9FP3qtA1UBJh";
+        assert!(
+            Scan::watched_swap(&trade, &pool_create, mint, &mut seen)
+                .await
+                .is_none()
+        );
+        assert!(scan.watch_token_swaps("not a mint").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_token_trade_history_page() {
+        let signatures: Vec<String> = (1..=3u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        let scan = fixture_solana(&[
+            (signatures[0].as_str(), "raydium_v4_swap_buy"),
+            (signatures[1].as_str(), "sol_transfer"),
+            (signatures[2].as_str(), "raydium_v4_swap_buy"),
+        ])
+        .create_scan();
+        let mint = "6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau";
+        let page = scan
+            .get_token_trade_history_page(mint, None, 2)
+            .await
+            .unwrap();
+        assert_eq!(page.records.len(), 1);
+        assert_eq!(page.next_cursor.as_deref(), Some(signatures[1].as_str()));
+        assert!(page.has_more);
+        let page = scan
+            .get_token_trade_history_page(mint, page.next_cursor, 2)
+            .await
+            .unwrap();
+        assert_eq!(page.records.len(), 1);
+        assert_eq!(page.records[0].signature, signatures[2]);
+        assert!(!page.has_more);
+        let page = scan
+            .get_token_trade_history_page(mint, page.next_cursor, 2)
+            .await
+            .unwrap();
+        assert!(page.records.is_empty());
+        assert_eq!(page.next_cursor, None);
+        assert!(!page.has_more);
+    }
+
+    #[tokio::test]
+    async fn test_overlapping_pages_are_deduped() {
+        let signatures: Vec<String> = (1..=3u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        // the boundary signature shows up on both pages, as after a reorg
+        let scan = fixture_solana(&[
+            (signatures[0].as_str(), "raydium_v4_swap_buy"),
+            (signatures[1].as_str(), "raydium_v4_swap_buy"),
+            (signatures[1].as_str(), "raydium_v4_swap_buy"),
+            (signatures[2].as_str(), "raydium_v4_swap_buy"),
+        ])
+        .create_scan();
+        let mint = "6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau";
+        let first = scan
+            .get_token_trade_history_page(mint, None, 2)
+            .await
+            .unwrap();
+        let second = scan
+            .get_token_trade_history_page(mint, first.next_cursor.clone(), 2)
+            .await
+            .unwrap();
+        let paged: Vec<&str> = first
+            .records
+            .iter()
+            .chain(&second.records)
+            .map(|record| record.signature.as_str())
+            .collect();
+        assert_eq!(paged, signatures);
+        let opts = TokenTradeHistoryOptions {
+            limit: 10,
+            concurrency: 1,
+            interval_time: 0,
+            ..Default::default()
+        };
+        let history: Vec<String> = scan
+            .get_token_trade_history(mint, &opts)
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|record| record.signature)
+            .collect();
+        assert_eq!(history, signatures);
+    }
+
+    #[test]
+    fn test_volume_by_dex() {
+        let mint = "6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau";
+        let swap =
+            Scan::parse_transaction_to_record(&load_transaction_info("raydium_v4_swap_buy"), mint)
+                .unwrap();
+        let dex = swap.dex_program.clone().unwrap();
+        let mut unknown = swap.clone();
+        unknown.dex_program = None;
+        let mut pool_create = swap.clone();
+        pool_create.trade_type = "pool_create".to_string();
+        let volumes = Scan::volume_by_dex(&[swap.clone(), swap.clone(), unknown, pool_create]);
+        assert_eq!(volumes.len(), 2);
+        assert_eq!(
+            volumes[&dex],
+            (swap.base_amount * 2.0, swap.quote_amount * 2.0, 2)
+        );
+        assert_eq!(volumes["unknown"], (swap.base_amount, swap.quote_amount, 1));
+        assert!(Scan::volume_by_dex(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_export_trade_history() {
+        let mint = "6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau";
+        let record =
+            Scan::parse_transaction_to_record(&load_transaction_info("raydium_v4_swap_buy"), mint)
+                .unwrap();
+        let records = vec![record.clone(), record];
+        let mut ndjson = Vec::new();
+        Scan::export_trade_history_ndjson(&records, &mut ndjson).unwrap();
+        let lines: Vec<&str> = std::str::from_utf8(&ndjson).unwrap().lines().collect();
+        assert_eq!(lines.len(), 2);
+        let parsed: TokenTradeRecord = serde_json::from_str(lines[0]).unwrap();
+        assert_eq!(parsed.signature, records[0].signature);
+        assert_eq!(parsed.side, "buy");
+        let path = std::env::temp_dir().join(format!("trade_history_{}.json", std::process::id()));
+        Scan::export_trade_history_json(&records, &path).unwrap();
+        let parsed: Vec<TokenTradeRecord> =
+            serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert_eq!(parsed[1].base_amount, records[1].base_amount);
+    }
+
+    #[tokio::test]
+    async fn test_signatures_since_pages_until_reached() {
+        let signatures: Vec<String> = (0..1003u32)
+            .map(|i| {
+                let mut bytes = [1u8; 64];
+                bytes[..4].copy_from_slice(&i.to_le_bytes());
+                Signature::from(bytes).to_string()
+            })
+            .collect();
+        let history: Vec<(&str, &str)> = signatures
+            .iter()
+            .map(|s| (s.as_str(), "sol_transfer"))
+            .collect();
+        let rpc = fixture_rpc(&history);
+        let client = RpcClient::new_sender(rpc.clone(), RpcClientConfig::default());
+        let mint = "6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau";
+        // everything newer than the oldest signature, which takes a second page
+        let since = Scan::signatures_since(&client, None, mint, &signatures[1002]).await;
+        let mut expected = signatures[..1002].to_vec();
+        expected.reverse();
+        assert_eq!(since, expected);
+        let pages = rpc
+            .requests()
+            .iter()
+            .filter(|(request, _)| *request == RpcRequest::GetSignaturesForAddress)
+            .count();
+        assert_eq!(pages, 2);
+    }
+
+    #[tokio::test]
+    async fn test_watched_swap_marks_fetched_transactions_seen() {
+        let found = Signature::from([1u8; 64]).to_string();
+        let missing = Signature::from([2u8; 64]).to_string();
+        let mint = "6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau";
+        let trade = fixture_solana(&[(found.as_str(), "raydium_v4_swap_buy")]).create_trade();
+        let mut seen = VecDeque::new();
+        // not fetched, so a later backfill can still deliver it
+        assert!(
+            Scan::watched_swap(&trade, &missing, mint, &mut seen)
+                .await
+                .is_none()
+        );
+        assert!(seen.is_empty());
+        assert!(
+            Scan::watched_swap(&trade, &found, mint, &mut seen)
+                .await
+                .is_some()
+        );
+        assert!(
+            Scan::watched_swap(&trade, &found, mint, &mut seen)
+                .await
+                .is_none()
+        );
+        assert_eq!(seen, VecDeque::from([found]));
+    }
+
+    #[tokio::test]
+    async fn test_self_transfers_are_skipped_by_default() {
+        let mint = "BHAYupF1LHTKqsjp6anmRPo2ViRwNi5A9FP3qtA1UBJh";
+        let signature = Signature::from([1u8; 64]).to_string();
+        // a pool creation whose token accounts all belong to one owner and where no other
+        // account is funded, so it only moves value between the accounts of that owner
+        let mut tx = serde_json::to_value(load_fixture("raydium_v4_pool_create")).unwrap();
+        let meta = &mut tx["meta"];
+        meta["postTokenBalances"]
+            .as_array_mut()
+            .unwrap()
+            .retain(|balance| balance["accountIndex"] != 6);
+        for balances in ["preTokenBalances", "postTokenBalances"] {
+            for balance in meta[balances].as_array_mut().unwrap() {
+                balance["owner"] = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1".into();
+            }
+        }
+        for index in [1, 6, 7, 8, 9] {
+            meta["postBalances"][index] = meta["preBalances"][index].clone();
+        }
+        let rpc = fixture_rpc(&[(signature.as_str(), "raydium_v4_pool_create")]);
+        rpc.set_response(RpcRequest::GetTransaction, tx);
+        let scan = mock_solana(&rpc).create_scan();
+        let opts = TokenTradeHistoryOptions {
+            limit: 10,
+            concurrency: 1,
+            interval_time: 0,
+            ..Default::default()
+        };
+        assert!(
+            scan.get_token_trade_history(mint, &opts)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let page = scan
+            .get_token_trade_history_page(mint, None, 10)
+            .await
+            .unwrap();
+        assert!(page.records.is_empty());
+        let opts = TokenTradeHistoryOptions {
+            include_self_transfers: true,
+            ..opts
+        };
+        let history = scan.get_token_trade_history(mint, &opts).await.unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].signature, signature);
+    }
+
+    #[tokio::test]
+    async fn test_signature_limits_are_clamped() {
+        let signature = Signature::from([1u8; 64]).to_string();
+        let history = [(signature.as_str(), "raydium_v4_swap_buy")];
+        let mint = "6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau";
+        let rpc = fixture_rpc(&history);
+        let scan = mock_solana(&rpc).create_scan();
+        assert!(scan.get_last_signatures(mint, 0).await.unwrap().is_empty());
+        scan.get_last_signatures(mint, 5000).await.unwrap();
+        scan.get_token_trade_history_page(mint, None, 0)
+            .await
+            .unwrap();
+        let limits: Vec<Value> = rpc
+            .requests()
+            .iter()
+            .filter(|(request, _)| *request == RpcRequest::GetSignaturesForAddress)
+            .map(|(_, params)| params[1]["limit"].clone())
+            .collect();
+        assert_eq!(limits, vec![Value::from(1000), Value::from(1)]);
+    }
+
+    #[tokio::test]
+    async fn test_multi_token_trade_history() {
+        let signatures: Vec<String> = (1..=3u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        let solana = fixture_solana(&[
+            (&signatures[0], "raydium_v4_swap_buy"),
+            (&signatures[1], "raydium_v4_swap_buy_new_ata"),
+            (&signatures[2], "token_to_token_swap"),
+        ]);
+        let scan = solana.create_scan();
+        let opts = TokenTradeHistoryOptions {
+            limit: 10,
+            concurrency: 2,
+            interval_time: 0,
+            ..Default::default()
+        };
+        let (first, second, bad) = (
+            "6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau",
+            "B2FqYRbPsFmrNNbW5bXgmnfmmQD57vD2MzyA1Yv6v6Ek",
+            "not a mint",
+        );
+        let history = scan
+            .get_multi_token_trade_history(&[first, second, bad], &opts)
+            .await
+            .unwrap();
+        assert_eq!(history.len(), 3);
+        let records = history[first].as_ref().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].signature, signatures[0]);
+        assert_eq!(records[0].side, "buy");
+        let records = history[second].as_ref().unwrap();
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].signature, signatures[1]);
+        // an invalid mint fails alone
+        assert!(history[bad].is_err());
+        let single = scan.get_token_trade_history(second, &opts).await.unwrap();
+        assert_eq!(single.len(), 1);
+        let opts = TokenTradeHistoryOptions {
+            concurrency: 0,
+            ..opts
+        };
+        assert!(
+            scan.get_multi_token_trade_history(&[first], &opts)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wallet_trade_report() {
+        let signatures: Vec<String> = (1..=4u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        let solana = fixture_solana(&[
+            (&signatures[0], "pump_amm_sell"),
+            (&signatures[1], "raydium_v4_swap_buy"),
+            (&signatures[2], "sol_transfer"),
+            (&signatures[3], "pump_amm_buy"),
+        ]);
+        let wallet = "AWNBeheFeCin3tv2nvMLTD5yZJ38YjiPSzsEvrBmnykk";
+        let mint = "9eU21qY11yiohyYTuWWfnP2Zgi7RZqMXgc1g1yMmLCgM";
+        let opts = WalletTradeReportOptions {
+            limit: 10,
+            interval_time: 0,
+            ..Default::default()
+        };
+        let report = solana
+            .create_scan()
+            .wallet_trade_report(wallet, &opts)
+            .await
+            .unwrap();
+        // the raydium swap was signed by another wallet, the transfer is not a swap
+        assert_eq!(
+            report
+                .iter()
+                .map(|t| t.signature.as_str())
+                .collect::<Vec<_>>(),
+            vec![signatures[0].as_str(), signatures[3].as_str()]
+        );
+        assert_eq!(report[0].side, "sell");
+        assert_eq!(report[1].side, "buy");
+        assert!(report.iter().all(|t| t.base_mint == mint));
+        assert_eq!(report[0].base_amount, 1_000_000.0);
+        assert_eq!(report[1].base_amount, 3_500_000.0);
+        assert_eq!(report[1].fee, 5000);
+        let price = report[1].price.unwrap();
+        assert!((price - 0.10025 / 3_500_000.0).abs() < 1e-15);
+        assert!(
+            solana
+                .create_scan()
+                .wallet_trade_report("not a wallet", &opts)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scan_commitment_is_sent() {
+        let signature = Signature::from([1u8; 64]).to_string();
+        let history = [(signature.as_str(), "raydium_v4_swap_buy")];
+        let opts = TokenTradeHistoryOptions {
+            limit: 1,
+            interval_time: 0,
+            ..Default::default()
+        };
+        let mint = "6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau";
+        // without a commitment the rpc node's default applies
+        let rpc = fixture_rpc(&history);
+        mock_solana(&rpc)
+            .create_scan()
+            .get_token_trade_history(mint, &opts)
+            .await
+            .unwrap();
+        let sent = rpc.requests();
+        assert_eq!(sent.len(), 2);
+        assert!(
+            sent.iter()
+                .all(|(_, params)| params[1]["commitment"].is_null())
+        );
+        let rpc = fixture_rpc(&history);
+        let scan = mock_solana(&rpc)
+            .create_scan()
+            .with_commitment(CommitmentConfig::finalized());
+        assert_eq!(
+            scan.get_last_signatures(mint, 5).await.unwrap(),
+            vec![signature.clone()]
+        );
+        scan.get_token_trade_history(mint, &opts).await.unwrap();
+        let requests = rpc.requests();
+        assert_eq!(
+            requests
+                .iter()
+                .map(|(request, _)| *request)
+                .collect::<Vec<_>>(),
+            vec![
+                RpcRequest::GetSignaturesForAddress,
+                RpcRequest::GetSignaturesForAddress,
+                RpcRequest::GetTransaction
+            ]
+        );
+        assert!(
+            requests
+                .iter()
+                .all(|(_, params)| params[1]["commitment"] == "finalized")
+        );
+    }
+
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_get_last_signatures() -> Result<(), ()> {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let trade = solana.create_trade();
@@ -410,6 +1681,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_get_all_signatures_by_address_and_batch_find_transaction() -> Result<(), ()> {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let scan = Arc::new(solana.create_scan());
@@ -432,6 +1704,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_get_all_signatures_by_address_stop() -> Result<(), ()> {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let scan = Arc::new(solana.create_scan());
@@ -1,19 +1,55 @@
 use crate::{
-    global::{SPL_TOKEN_PROGRAM_2022, SPL_TOKEN_PROGRAM_V1},
-    types::{UnifiedError, UnifiedResult},
+    global::{
+        ASSOCIATED_TOKEN_PROGRAM_ID, METAPLEX_TOKEN_METADATA_PROGRAM_ID, SPL_TOKEN_PROGRAM_2022,
+        SPL_TOKEN_PROGRAM_V1, SYSTEM_PROGRAM_ID,
+    },
+    tool::raw_to_ui,
+    trade::{
+        Trade,
+        info::{TransactionInfo, UiTokenAmount},
+    },
+    types::{TxOptions, UnifiedError, UnifiedResult},
+    wallet::Wallet,
+};
+use solana_account_decoder_client_types::{UiAccountEncoding, UiDataSliceConfig};
+use solana_client::{
+    nonblocking::rpc_client::RpcClient,
+    rpc_config::{RpcAccountInfoConfig, RpcProgramAccountsConfig},
+    rpc_filter::{Memcmp, RpcFilterType},
 };
-use solana_client::nonblocking::rpc_client::RpcClient;
 use solana_commitment_config::CommitmentConfig;
-use solana_sdk::pubkey::Pubkey;
-use std::{str::FromStr, sync::Arc};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    pubkey::Pubkey,
+    signature::{Keypair, Signer},
+    transaction::Transaction,
+};
+use std::{
+    collections::HashMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+
+/// size of a token mint account
+const MINT_ACCOUNT_SIZE: usize = 82;
+/// system program `CreateAccount` instruction tag
+const SYSTEM_CREATE_ACCOUNT: u32 = 0;
+/// token program `MintTo` and `InitializeMint2` instruction tags
+const MINT_TO: u8 = 7;
+const INITIALIZE_MINT2: u8 = 20;
 
 pub struct Spl {
     client: Arc<RpcClient>,
+    // token metadata by mint, None when the mint has no metadata account
+    metadata_cache: Mutex<HashMap<String, Option<TokenMetadata>>>,
 }
 
 impl Spl {
     pub fn new(client: Arc<RpcClient>) -> Self {
-        Self { client }
+        Self {
+            client,
+            metadata_cache: Mutex::new(HashMap::new()),
+        }
     }
 
     /// Get token information by specified SPL token address (supports both standard SPL Token and Token2022)
@@ -26,7 +62,7 @@ impl Spl {
     /// * `Err(String)` - Failed to get or parse token information
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let solana = Solana::new(Mode::DEV).unwrap();
     /// let spl = solana.create_spl();
     /// let token_info = spl.get_token_info("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").await?;
@@ -64,7 +100,7 @@ impl Spl {
         } else {
             None
         };
-        let supply = supply_raw as f64 / 10_u64.pow(decimals as u32) as f64;
+        let supply = raw_to_ui(supply_raw, decimals);
         Ok(SplTokenInfo {
             mint_address: mint_address.to_string(),
             decimals,
@@ -81,6 +117,115 @@ impl Spl {
         })
     }
 
+    /// Get the Metaplex metadata (name, symbol, uri) of a token
+    /// Results are cached by mint for the lifetime of this `Spl`
+    ///
+    /// # Example
+    /// ```ignore
+    /// let spl = solana.create_spl();
+    /// let metadata = spl.get_token_metadata("4k3Dyjzvzp8eMZWUXbBCjEvwSkkk59S5iCNLY3QrkX6R").await?;
+    /// println!("{} ({})", metadata.name, metadata.symbol);
+    /// ```
+    pub async fn get_token_metadata(
+        &self,
+        mint_address: &str,
+    ) -> UnifiedResult<TokenMetadata, String> {
+        self.find_token_metadata(mint_address)
+            .await?
+            .ok_or_else(|| UnifiedError::Error("Token metadata does not exist".to_string()))
+    }
+
+    /// Get the Metaplex metadata of a token and the image from its off-chain json at `uri`
+    pub async fn get_token_metadata_with_image(
+        &self,
+        mint_address: &str,
+    ) -> UnifiedResult<TokenMetadata, String> {
+        let mut metadata = self.get_token_metadata(mint_address).await?;
+        if metadata.image.is_some() || metadata.uri.is_empty() {
+            return Ok(metadata);
+        }
+        let json: serde_json::Value = reqwest::get(&metadata.uri)
+            .await
+            .map_err(|e| UnifiedError::Error(format!("Failed to fetch metadata uri: {:?}", e)))?
+            .json()
+            .await
+            .map_err(|e| UnifiedError::Error(format!("Failed to parse metadata json: {:?}", e)))?;
+        metadata.image = json
+            .get("image")
+            .and_then(|image| image.as_str())
+            .map(String::from);
+        self.metadata_cache
+            .lock()
+            .unwrap()
+            .insert(mint_address.to_string(), Some(metadata.clone()));
+        Ok(metadata)
+    }
+
+    /// Fill `token_name`/`token_symbol` (and `nft_name`/`nft_symbol` for nft transfers) of a transaction
+    /// The token is `token_mint`, or the traded base token for swaps; tokens without metadata are left as None
+    ///
+    /// # Example
+    /// ```ignore
+    /// let mut info = trade.get_transaction_display_details("signature").await?;
+    /// solana.create_spl().enrich_token_metadata(&mut info).await?;
+    /// ```
+    pub async fn enrich_token_metadata(
+        &self,
+        info: &mut TransactionInfo,
+    ) -> UnifiedResult<(), String> {
+        if let Some(mint) = info
+            .token_mint
+            .clone()
+            .or_else(|| info.get_pool_base_token_address())
+            && let Some(metadata) = self.find_token_metadata(&mint).await?
+        {
+            info.token_name = Some(metadata.name);
+            info.token_symbol = Some(metadata.symbol);
+        }
+        if let Some(mint) = info.nft_mint.clone()
+            && let Some(metadata) = self.find_token_metadata(&mint).await?
+        {
+            info.nft_name = Some(metadata.name);
+            info.nft_symbol = Some(metadata.symbol);
+        }
+        Ok(())
+    }
+
+    /// Fetch the metadata account of a mint, through the cache
+    async fn find_token_metadata(
+        &self,
+        mint_address: &str,
+    ) -> UnifiedResult<Option<TokenMetadata>, String> {
+        if let Some(metadata) = self.metadata_cache.lock().unwrap().get(mint_address) {
+            return Ok(metadata.clone());
+        }
+        let mint_pubkey = Pubkey::from_str(mint_address)
+            .map_err(|_| UnifiedError::Error("Invalid token address format".to_string()))?;
+        let program = Pubkey::from_str(METAPLEX_TOKEN_METADATA_PROGRAM_ID)
+            .expect("Invalid token metadata program ID");
+        let (metadata_pubkey, _) = Pubkey::find_program_address(
+            &[b"metadata", program.as_ref(), mint_pubkey.as_ref()],
+            &program,
+        );
+        let account_response = self
+            .client
+            .get_account_with_commitment(&metadata_pubkey, CommitmentConfig::confirmed())
+            .await
+            .map_err(|e| UnifiedError::Error(format!("Failed to get account: {:?}", e)))?;
+        let metadata = match account_response.value {
+            Some(account) => Some(TokenMetadata::from_account_data(
+                mint_address,
+                &account.data,
+            )?),
+            None => None,
+        };
+        self.metadata_cache
+            .lock()
+            .unwrap()
+            .insert(mint_address.to_string(), metadata.clone());
+        Ok(metadata)
+    }
+
     /// Helper function to get token program type from owner pubkey
     fn get_token_program_type_from_owner(
         &self,
@@ -102,11 +247,7 @@ impl Spl {
     /// Get token age in seconds (time since creation)
     pub async fn get_token_age_seconds(&self, mint_address: &str) -> UnifiedResult<u64, String> {
         let creation_timestamp = self.get_token_creation_timestamp(mint_address).await?;
-        let current_time = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .map_err(|e| UnifiedError::Error(format!("Failed to get current time: {:?}", e)))?
-            .as_secs();
-        Ok(current_time.saturating_sub(creation_timestamp as u64))
+        Ok(crate::tool::now_unix_secs().saturating_sub(creation_timestamp as u64))
     }
 
     /// Get token creation timestamp (Unix timestamp) via account creation slot
@@ -130,6 +271,278 @@ impl Spl {
         Ok(block_time)
     }
 
+    /// Get the number of holders of a token, i.e. token accounts of the mint with a non-zero balance
+    /// Scans the token program's accounts with `getProgramAccounts`, which is heavy for popular
+    /// tokens and disabled or rate limited on many public rpc nodes, a paid rpc may be required
+    ///
+    /// # Parameters
+    /// * `mint` - SPL token or Token2022 mint address
+    ///
+    /// # Returns
+    /// * `Ok(u64)` - Number of token accounts holding the token
+    /// * `Err(String)` - Failed to resolve the mint's program or to scan its accounts
+    ///
+    /// # Example
+    /// ```ignore
+    /// let holders = spl.get_holder_count("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v").await?;
+    /// ```
+    pub async fn get_holder_count(&self, mint: &str) -> UnifiedResult<u64, String> {
+        let mint_pubkey = Pubkey::from_str(mint)
+            .map_err(|_| UnifiedError::Error("Invalid token address format".to_string()))?;
+        // token accounts start with the mint, the amount follows the owner at offset 64
+        let mut filters = vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            0,
+            mint_pubkey.as_ref(),
+        ))];
+        let program = match self.get_token_program_type(mint).await? {
+            TokenProgramType::StandardSplToken => {
+                filters.push(RpcFilterType::DataSize(165));
+                SPL_TOKEN_PROGRAM_V1
+            }
+            // token2022 accounts with extensions are longer than 165 bytes
+            TokenProgramType::Token2022 => SPL_TOKEN_PROGRAM_2022,
+        };
+        let config = RpcProgramAccountsConfig {
+            filters: Some(filters),
+            account_config: RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                data_slice: Some(UiDataSliceConfig {
+                    offset: 64,
+                    length: 8,
+                }),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let accounts = self
+            .client
+            .get_program_ui_accounts_with_config(&Pubkey::from_str(program).unwrap(), config)
+            .await
+            .map_err(|e| UnifiedError::Error(format!("Failed to get token accounts: {:?}", e)))?;
+        Ok(accounts
+            .iter()
+            .filter_map(|(_, account)| account.data.decode())
+            .filter(|amount| amount.iter().any(|byte| *byte != 0))
+            .count() as u64)
+    }
+
+    /// Get the associated token account of `owner` for `mint`, derived locally without rpc
+    ///
+    /// # Parameters
+    /// * `owner` - wallet address
+    /// * `mint` - SPL token or Token2022 mint address
+    /// * `token_program` - program that owns the mint, the address differs between the two
+    ///
+    /// # Returns
+    /// * `Ok(String)` - Base58 associated token account address
+    /// * `Err(String)` - Invalid owner or mint address
+    ///
+    /// # Example
+    /// ```ignore
+    /// let ata = Spl::get_associated_token_address(
+    ///     "wallet address",
+    ///     "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+    ///     TokenProgramType::StandardSplToken,
+    /// )?;
+    /// ```
+    pub fn get_associated_token_address(
+        owner: &str,
+        mint: &str,
+        token_program: TokenProgramType,
+    ) -> UnifiedResult<String, String> {
+        let owner = Pubkey::from_str(owner)
+            .map_err(|_| UnifiedError::Error("Invalid owner address format".to_string()))?;
+        let mint = Pubkey::from_str(mint)
+            .map_err(|_| UnifiedError::Error("Invalid token address format".to_string()))?;
+        Ok(associated_token_address(&owner, &mint, token_program).to_string())
+    }
+
+    /// Get the balance of a token account by its address
+    /// For accounts already known, e.g. a pool vault, so no associated account is derived
+    ///
+    /// # Parameters
+    /// * `token_account` - SPL token or Token2022 token account address
+    ///
+    /// # Returns
+    /// * `Ok(UiTokenAmount)` - Raw amount, decimals and ui amount of the account
+    /// * `Err(String)` - Invalid address, the account is not a token account or the rpc failed
+    ///
+    /// # Example
+    /// ```ignore
+    /// let balance = spl.get_balance_by_account("token account address").await?;
+    /// println!("{} ({} decimals)", balance.amount, balance.decimals);
+    /// ```
+    pub async fn get_balance_by_account(
+        &self,
+        token_account: &str,
+    ) -> UnifiedResult<UiTokenAmount, String> {
+        let pubkey = Pubkey::from_str(token_account)
+            .map_err(|_| UnifiedError::Error("Invalid token account address format".to_string()))?;
+        let balance = self
+            .client
+            .get_token_account_balance(&pubkey)
+            .await
+            .map_err(|e| {
+                if e.to_string().contains("not a Token account") {
+                    UnifiedError::Error(format!("{} is not a token account", token_account))
+                } else {
+                    UnifiedError::Error(format!("Failed to get token account balance: {:?}", e))
+                }
+            })?;
+        Ok(UiTokenAmount {
+            ui_amount: balance.ui_amount,
+            decimals: balance.decimals,
+            amount: balance.amount,
+            ui_amount_string: Some(balance.ui_amount_string),
+        })
+    }
+
+    /// Create a new SPL token mint without freeze authority
+    /// The mint account is a fresh keypair, `payer` pays the fee and its rent exemption
+    /// Sent with the default `TxOptions`, i.e. at the median recent priority fee
+    ///
+    /// # Parameters
+    /// * `payer` - fee payer, signs the transaction
+    /// * `mint_authority` - address allowed to mint supply
+    /// * `decimals` - decimals of the token
+    ///
+    /// # Returns
+    /// * `Ok(String)` - address of the new mint
+    /// * `Err(String)` - Invalid address, wallet without keypair or failed transaction
+    ///
+    /// # Example
+    /// ```ignore
+    /// let spl = Solana::new(Mode::DEV).unwrap().create_spl();
+    /// let mint = spl.create_mint(&wallet, &wallet.public_key, 6).await?;
+    /// let signature = spl.mint_to(&wallet, &mint, "recipient address", 1_000_000_000).await?;
+    /// ```
+    pub async fn create_mint(
+        &self,
+        payer: &Wallet,
+        mint_authority: &str,
+        decimals: u8,
+    ) -> UnifiedResult<String, String> {
+        self.create_mint_with_options(payer, mint_authority, decimals, &TxOptions::default())
+            .await
+    }
+
+    /// `create_mint` with explicit priority fee, compute unit limit, preflight and retries
+    ///
+    /// # Example
+    /// ```ignore
+    /// let opts = TxOptions {
+    ///     priority_fee_micro_lamports: Some(50_000),
+    ///     ..Default::default()
+    /// };
+    /// let mint = spl
+    ///     .create_mint_with_options(&wallet, &wallet.public_key, 6, &opts)
+    ///     .await?;
+    /// ```
+    pub async fn create_mint_with_options(
+        &self,
+        payer: &Wallet,
+        mint_authority: &str,
+        decimals: u8,
+        opts: &TxOptions,
+    ) -> UnifiedResult<String, String> {
+        let payer = wallet_keypair(payer)?;
+        let mint_authority = Pubkey::from_str(mint_authority).map_err(|_| {
+            UnifiedError::Error("Invalid mint authority address format".to_string())
+        })?;
+        let lamports = self
+            .client
+            .get_minimum_balance_for_rent_exemption(MINT_ACCOUNT_SIZE)
+            .await
+            .map_err(|e| UnifiedError::Error(format!("Failed to get rent exemption: {:?}", e)))?;
+        let mint = Keypair::new();
+        let trade = Trade::new(self.client.clone());
+        let mut instructions = trade
+            .compute_budget_instructions(opts)
+            .await
+            .map_err(|e| UnifiedError::Error(format!("Failed to get priority fee: {:?}", e)))?;
+        instructions.extend(create_mint_instructions(
+            &payer.pubkey(),
+            &mint.pubkey(),
+            &mint_authority,
+            decimals,
+            lamports,
+        ));
+        trade
+            .send_and_confirm_with_retry(
+                |blockhash| {
+                    let mut transaction =
+                        Transaction::new_with_payer(&instructions, Some(&payer.pubkey()));
+                    transaction.partial_sign(&[&mint], blockhash);
+                    transaction
+                },
+                payer,
+                opts,
+            )
+            .await
+            .map_err(|e| UnifiedError::Error(format!("Failed to send transaction: {:?}", e)))?;
+        Ok(mint.pubkey().to_string())
+    }
+
+    /// Mint `amount` raw units of `mint` to the associated token account of `to_owner`
+    /// The associated token account is created first when missing, `payer` must be the mint authority
+    /// Sent with the default `TxOptions`, i.e. at the median recent priority fee
+    ///
+    /// # Parameters
+    /// * `payer` - mint authority and fee payer, signs the transaction
+    /// * `mint` - SPL token or Token2022 mint address
+    /// * `to_owner` - wallet receiving the supply
+    /// * `amount` - amount in raw units
+    ///
+    /// # Returns
+    /// * `Ok(String)` - transaction signature
+    /// * `Err(String)` - Invalid address, wallet without keypair or failed transaction
+    pub async fn mint_to(
+        &self,
+        payer: &Wallet,
+        mint: &str,
+        to_owner: &str,
+        amount: u64,
+    ) -> UnifiedResult<String, String> {
+        self.mint_to_with_options(payer, mint, to_owner, amount, &TxOptions::default())
+            .await
+    }
+
+    /// `mint_to` with explicit priority fee, compute unit limit, preflight and retries
+    pub async fn mint_to_with_options(
+        &self,
+        payer: &Wallet,
+        mint: &str,
+        to_owner: &str,
+        amount: u64,
+        opts: &TxOptions,
+    ) -> UnifiedResult<String, String> {
+        let payer = wallet_keypair(payer)?;
+        let to_owner = Pubkey::from_str(to_owner)
+            .map_err(|_| UnifiedError::Error("Invalid owner address format".to_string()))?;
+        let token_program = self.get_token_program_type(mint).await?;
+        let mint = Pubkey::from_str(mint)
+            .map_err(|_| UnifiedError::Error("Invalid token address format".to_string()))?;
+        let instructions = [
+            create_associated_token_account_idempotent(
+                &payer.pubkey(),
+                &to_owner,
+                &mint,
+                token_program,
+            ),
+            mint_to_instruction(
+                &mint,
+                &associated_token_address(&to_owner, &mint, token_program),
+                &payer.pubkey(),
+                amount,
+                token_program,
+            ),
+        ];
+        Trade::new(self.client.clone())
+            .send_with_fresh_blockhash(&instructions, payer, opts)
+            .await
+            .map_err(|e| UnifiedError::Error(format!("Failed to send transaction: {:?}", e)))
+    }
+
     /// Check if a token is Token2022 or standard SPL Token
     pub async fn get_token_program_type(
         &self,
@@ -159,12 +572,160 @@ impl Spl {
     }
 }
 
-#[derive(Debug, Clone)]
+/// Metaplex token metadata
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenMetadata {
+    pub mint_address: String,
+    pub name: String,
+    pub symbol: String,
+    /// off-chain json uri
+    pub uri: String,
+    /// image from the off-chain json, only set by `get_token_metadata_with_image`
+    pub image: Option<String>,
+}
+
+impl TokenMetadata {
+    /// Parse a metadata account: key (1), update authority (32), mint (32), then the
+    /// borsh strings name, symbol and uri, which are padded with trailing zeros on chain
+    fn from_account_data(mint_address: &str, data: &[u8]) -> UnifiedResult<Self, String> {
+        let mut offset = 1 + 32 + 32;
+        let mut read_string = || -> UnifiedResult<String, String> {
+            let invalid = || UnifiedError::Error("Invalid token metadata account data".to_string());
+            let len_bytes = data.get(offset..offset + 4).ok_or_else(invalid)?;
+            let len = u32::from_le_bytes(len_bytes.try_into().unwrap()) as usize;
+            let bytes = data.get(offset + 4..offset + 4 + len).ok_or_else(invalid)?;
+            offset += 4 + len;
+            Ok(String::from_utf8_lossy(bytes)
+                .trim_end_matches('\0')
+                .to_string())
+        };
+        let name = read_string()?;
+        let symbol = read_string()?;
+        let uri = read_string()?;
+        Ok(Self {
+            mint_address: mint_address.to_string(),
+            name,
+            symbol,
+            uri,
+            image: None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TokenProgramType {
     StandardSplToken,
     Token2022,
 }
 
+impl TokenProgramType {
+    /// address of the token program
+    pub fn program_id(&self) -> &'static str {
+        match self {
+            TokenProgramType::StandardSplToken => SPL_TOKEN_PROGRAM_V1,
+            TokenProgramType::Token2022 => SPL_TOKEN_PROGRAM_2022,
+        }
+    }
+}
+
+/// associated token account of `owner` for `mint` under `token_program`
+pub(crate) fn associated_token_address(
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: TokenProgramType,
+) -> Pubkey {
+    let token_program = Pubkey::from_str_const(token_program.program_id());
+    Pubkey::find_program_address(
+        &[owner.as_ref(), token_program.as_ref(), mint.as_ref()],
+        &Pubkey::from_str_const(ASSOCIATED_TOKEN_PROGRAM_ID),
+    )
+    .0
+}
+
+/// create the associated token account of `owner` for `mint` unless it already exists
+pub(crate) fn create_associated_token_account_idempotent(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+    token_program: TokenProgramType,
+) -> Instruction {
+    Instruction {
+        program_id: Pubkey::from_str_const(ASSOCIATED_TOKEN_PROGRAM_ID),
+        accounts: vec![
+            AccountMeta::new(*payer, true),
+            AccountMeta::new(associated_token_address(owner, mint, token_program), false),
+            AccountMeta::new_readonly(*owner, false),
+            AccountMeta::new_readonly(*mint, false),
+            AccountMeta::new_readonly(Pubkey::from_str_const(SYSTEM_PROGRAM_ID), false),
+            AccountMeta::new_readonly(Pubkey::from_str_const(token_program.program_id()), false),
+        ],
+        data: vec![1],
+    }
+}
+
+/// create the mint account with its rent exemption and initialize it without freeze authority
+fn create_mint_instructions(
+    payer: &Pubkey,
+    mint: &Pubkey,
+    mint_authority: &Pubkey,
+    decimals: u8,
+    lamports: u64,
+) -> Vec<Instruction> {
+    let token_program = Pubkey::from_str_const(SPL_TOKEN_PROGRAM_V1);
+    // u32 tag, u64 lamports, u64 space, owner
+    let mut create = SYSTEM_CREATE_ACCOUNT.to_le_bytes().to_vec();
+    create.extend_from_slice(&lamports.to_le_bytes());
+    create.extend_from_slice(&(MINT_ACCOUNT_SIZE as u64).to_le_bytes());
+    create.extend_from_slice(token_program.as_ref());
+    // tag, decimals, mint authority, no freeze authority
+    let mut initialize = vec![INITIALIZE_MINT2, decimals];
+    initialize.extend_from_slice(mint_authority.as_ref());
+    initialize.push(0);
+    vec![
+        Instruction {
+            program_id: Pubkey::from_str_const(SYSTEM_PROGRAM_ID),
+            accounts: vec![
+                AccountMeta::new(*payer, true),
+                AccountMeta::new(*mint, true),
+            ],
+            data: create,
+        },
+        Instruction {
+            program_id: token_program,
+            accounts: vec![AccountMeta::new(*mint, false)],
+            data: initialize,
+        },
+    ]
+}
+
+/// mint `amount` raw units to the `destination` token account
+fn mint_to_instruction(
+    mint: &Pubkey,
+    destination: &Pubkey,
+    mint_authority: &Pubkey,
+    amount: u64,
+    token_program: TokenProgramType,
+) -> Instruction {
+    let mut data = vec![MINT_TO];
+    data.extend_from_slice(&amount.to_le_bytes());
+    Instruction {
+        program_id: Pubkey::from_str_const(token_program.program_id()),
+        accounts: vec![
+            AccountMeta::new(*mint, false),
+            AccountMeta::new(*destination, false),
+            AccountMeta::new_readonly(*mint_authority, true),
+        ],
+        data,
+    }
+}
+
+fn wallet_keypair(wallet: &Wallet) -> UnifiedResult<&Keypair, String> {
+    wallet
+        .keypair
+        .as_ref()
+        .ok_or_else(|| UnifiedError::Error("Wallet has no keypair".to_string()))
+}
+
 #[derive(Debug, Clone)]
 pub struct SplTokenInfo {
     pub mint_address: String,
@@ -224,10 +785,366 @@ impl SplTokenInfo {
 
 #[cfg(test)]
 mod tests {
+    use super::*;
     use crate::Solana;
+    use crate::fixtures::{
+        accept_transactions, latest_blockhash, mock_solana, sent_transaction, signature_statuses,
+    };
+    use crate::global::COMPUTE_BUDGET_PROGRAM_ID;
+    use crate::mock::MockRpc;
     use crate::types::Mode;
+    use base64::Engine;
+    use solana_client::client_error::ClientErrorKind;
+    use solana_client::rpc_request::{RpcError, RpcRequest, RpcResponseErrorData};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_get_balance_by_account() {
+        let vault = "9DgBSggFUnTJBikSNad4qsK5zaM6ZTUpdGm3Q8nGJcHG";
+        let rpc = MockRpc::new();
+        rpc.set_handler(RpcRequest::GetTokenAccountBalance, move |params| {
+            if params[0] != vault {
+                // what a node answers for an account the token programs do not own
+                return Err(ClientErrorKind::RpcError(RpcError::RpcResponseError {
+                    code: -32602,
+                    message: "Invalid param: not a Token account".to_string(),
+                    data: RpcResponseErrorData::Empty,
+                })
+                .into());
+            }
+            Ok(serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "amount": "90000000000",
+                    "decimals": 6,
+                    "uiAmount": 90000.0,
+                    "uiAmountString": "90000"
+                }
+            }))
+        });
+        let spl = mock_solana(&rpc).create_spl();
+        let balance = spl.get_balance_by_account(vault).await.unwrap();
+        assert_eq!(balance.amount, "90000000000");
+        assert_eq!(balance.decimals, 6);
+        assert_eq!(balance.ui_amount, Some(90_000.0));
+        assert!(spl.get_balance_by_account("not an address").await.is_err());
+        let wallet = "3Fav7BV2tWLjwB9kBHnbmb6ibehugwuvimSMCUgwyNbR";
+        match spl.get_balance_by_account(wallet).await {
+            Err(UnifiedError::Error(message)) => {
+                assert_eq!(message, format!("{} is not a token account", wallet))
+            }
+            other => panic!("expected a not a token account error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_create_mint_instructions() {
+        let (payer, mint, authority) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        let [create, initialize] = <[Instruction; 2]>::try_from(create_mint_instructions(
+            &payer, &mint, &authority, 6, 1_461_600,
+        ))
+        .unwrap();
+        assert_eq!(create.program_id.to_string(), SYSTEM_PROGRAM_ID);
+        assert_eq!(create.accounts[1].pubkey, mint);
+        assert!(create.accounts.iter().all(|account| account.is_signer));
+        assert_eq!(&create.data[4..12], &1_461_600u64.to_le_bytes());
+        assert_eq!(&create.data[12..20], &82u64.to_le_bytes());
+        assert_eq!(
+            &create.data[20..],
+            Pubkey::from_str_const(SPL_TOKEN_PROGRAM_V1).as_ref()
+        );
+        assert_eq!(initialize.data.len(), 35);
+        assert_eq!(&initialize.data[..2], &[INITIALIZE_MINT2, 6]);
+        assert_eq!(&initialize.data[2..34], authority.as_ref());
+        // no freeze authority
+        assert_eq!(initialize.data[34], 0);
+    }
+
+    #[tokio::test]
+    async fn test_create_mint_and_mint_to() {
+        let wallet = Wallet::create_new_wallet();
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetAccountInfo,
+            serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "lamports": 1_461_600,
+                    "data": ["", "base64"],
+                    "owner": SPL_TOKEN_PROGRAM_2022,
+                    "executable": false,
+                    "rentEpoch": 0,
+                    "space": 82
+                }
+            }),
+        );
+        rpc.set_response(
+            RpcRequest::GetMinimumBalanceForRentExemption,
+            serde_json::json!(1_461_600),
+        );
+        rpc.set_response(
+            RpcRequest::GetRecentPrioritizationFees,
+            serde_json::json!([
+                { "slot": 100, "prioritizationFee": 5_000 },
+                { "slot": 101, "prioritizationFee": 10_000 },
+                { "slot": 102, "prioritizationFee": 20_000 }
+            ]),
+        );
+        rpc.set_response(
+            RpcRequest::GetLatestBlockhash,
+            latest_blockhash(solana_sdk::hash::Hash::new_unique(), 2_000),
+        );
+        rpc.set_response(
+            RpcRequest::GetSignatureStatuses,
+            signature_statuses(Some("finalized")),
+        );
+        accept_transactions(&rpc);
+        let last_sent = || {
+            let (_, params) = rpc
+                .requests()
+                .into_iter()
+                .rfind(|(request, _)| *request == RpcRequest::SendTransaction)
+                .unwrap();
+            sent_transaction(&params)
+        };
+        let compute_budget = Pubkey::from_str_const(COMPUTE_BUDGET_PROGRAM_ID);
+        // the median recent priority fee, 10000 micro lamports per compute unit
+        let mut median_price = vec![3];
+        median_price.extend_from_slice(&10_000u64.to_le_bytes());
+        let spl = mock_solana(&rpc).create_spl();
+        let mint = spl
+            .create_mint(&wallet, &wallet.public_key, 6)
+            .await
+            .unwrap();
+        assert!(Pubkey::from_str(&mint).is_ok());
+        let transaction = last_sent();
+        let message = &transaction.message;
+        assert_eq!(message.instructions.len(), 3);
+        assert_eq!(
+            message.account_keys[message.instructions[0].program_id_index as usize],
+            compute_budget
+        );
+        assert_eq!(message.instructions[0].data, median_price);
+        let owner = Pubkey::new_unique();
+        let signature = spl
+            .mint_to(&wallet, &mint, &owner.to_string(), 1_000)
+            .await
+            .unwrap();
+        let transaction = last_sent();
+        assert_eq!(transaction.signatures[0].to_string(), signature);
+        let message = &transaction.message;
+        let program = |index: usize| {
+            message.account_keys[message.instructions[index].program_id_index as usize]
+        };
+        let account = |index: usize, position: usize| {
+            message.account_keys[message.instructions[index].accounts[position] as usize]
+        };
+        let token_2022 = Pubkey::from_str_const(SPL_TOKEN_PROGRAM_2022);
+        let mint = Pubkey::from_str(&mint).unwrap();
+        let ata = associated_token_address(&owner, &mint, TokenProgramType::Token2022);
+        assert_eq!(message.instructions.len(), 3);
+        assert_eq!(program(0), compute_budget);
+        assert_eq!(message.instructions[0].data, median_price);
+        // the idempotent ata creation under token 2022 comes first
+        assert_eq!(
+            program(1),
+            Pubkey::from_str_const(ASSOCIATED_TOKEN_PROGRAM_ID)
+        );
+        assert_eq!(message.instructions[1].data, vec![1]);
+        assert_eq!(account(1, 1), ata);
+        assert_eq!(account(1, 5), token_2022);
+        // then the supply is minted into that account
+        assert_eq!(program(2), token_2022);
+        assert_eq!(message.instructions[2].data[0], MINT_TO);
+        assert_eq!(&message.instructions[2].data[1..], &1_000u64.to_le_bytes());
+        assert_eq!(account(2, 0), mint);
+        assert_eq!(account(2, 1), ata);
+        // explicit options replace the median fee
+        let opts = TxOptions {
+            priority_fee_micro_lamports: Some(0),
+            compute_unit_limit: Some(60_000),
+            ..Default::default()
+        };
+        let mint = mint.to_string();
+        spl.mint_to_with_options(&wallet, &mint, &owner.to_string(), 1_000, &opts)
+            .await
+            .unwrap();
+        let transaction = last_sent();
+        let message = &transaction.message;
+        let mut limit = vec![2];
+        limit.extend_from_slice(&60_000u32.to_le_bytes());
+        assert_eq!(message.instructions.len(), 3);
+        assert_eq!(message.instructions[0].data, limit);
+        let no_keypair = Wallet {
+            keypair: None,
+            ..Wallet::create_new_wallet()
+        };
+        assert!(spl.create_mint(&no_keypair, &mint, 6).await.is_err());
+        assert!(
+            spl.mint_to(&wallet, &mint, "not an address", 1)
+                .await
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_get_associated_token_address() {
+        let owner = "3Fav7BV2tWLjwB9kBHnbmb6ibehugwuvimSMCUgwyNbR";
+        let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        assert_eq!(
+            Spl::get_associated_token_address(owner, usdc, TokenProgramType::StandardSplToken)
+                .unwrap(),
+            "9DgBSggFUnTJBikSNad4qsK5zaM6ZTUpdGm3Q8nGJcHG"
+        );
+        assert_eq!(
+            Spl::get_associated_token_address(owner, usdc, TokenProgramType::Token2022).unwrap(),
+            "59KGCHPJGYGhm77BCu12ALgouNYFWifCQWEkHUdmJL7d"
+        );
+        assert!(
+            Spl::get_associated_token_address("not an address", usdc, TokenProgramType::Token2022)
+                .is_err()
+        );
+    }
+
+    /// metadata account data as stored on chain, strings padded with zeros
+    fn metadata_account_data(name: &str, symbol: &str, uri: &str) -> Vec<u8> {
+        let mut data = vec![4u8];
+        data.extend_from_slice(&[1u8; 32]);
+        data.extend_from_slice(&[2u8; 32]);
+        for (value, padded_len) in [(name, 32), (symbol, 10), (uri, 200)] {
+            data.extend_from_slice(&(padded_len as u32).to_le_bytes());
+            let mut bytes = value.as_bytes().to_vec();
+            bytes.resize(padded_len, 0);
+            data.extend_from_slice(&bytes);
+        }
+        data
+    }
+
+    #[tokio::test]
+    async fn test_get_token_metadata_is_cached() {
+        let data = metadata_account_data("Raydium", "RAY", "https://example.com/ray.json");
+        let account = serde_json::json!({
+            "context": { "slot": 1 },
+            "value": {
+                "lamports": 5_616_720,
+                "data": [base64::engine::general_purpose::STANDARD.encode(&data), "base64"],
+                "owner": METAPLEX_TOKEN_METADATA_PROGRAM_ID,
+                "executable": false,
+                "rentEpoch": 0,
+                "space": data.len()
+            }
+        });
+        let lookups = AtomicUsize::new(0);
+        // only the first lookup finds the account, later ones have to come from the cache
+        let rpc = MockRpc::new().with_handler(RpcRequest::GetAccountInfo, move |_| {
+            Ok(match lookups.fetch_add(1, Ordering::Relaxed) {
+                0 => account.clone(),
+                _ => serde_json::json!({ "context": { "slot": 1 }, "value": null }),
+            })
+        });
+        let spl = mock_solana(&rpc).create_spl();
+        let metadata = spl.get_token_metadata(crate::global::RAY).await.unwrap();
+        assert_eq!(metadata.name, "Raydium");
+        assert_eq!(metadata.symbol, "RAY");
+        assert_eq!(metadata.uri, "https://example.com/ray.json");
+        assert_eq!(metadata.image, None);
+        assert_eq!(
+            spl.get_token_metadata(crate::global::RAY).await.unwrap(),
+            metadata
+        );
+        // no metadata account
+        assert!(spl.get_token_metadata(crate::global::USDC).await.is_err());
+        assert!(spl.get_token_metadata("bad address").await.is_err());
+        assert!(TokenMetadata::from_account_data(crate::global::RAY, &data[..80]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_get_holder_count() {
+        let base64 = |data: &[u8]| base64::engine::general_purpose::STANDARD.encode(data);
+        let token_account = |amount: u64| {
+            serde_json::json!({
+                "pubkey": Pubkey::new_unique().to_string(),
+                "account": {
+                    "lamports": 2_039_280,
+                    "data": [base64(&amount.to_le_bytes()), "base64"],
+                    "owner": SPL_TOKEN_PROGRAM_V1,
+                    "executable": false,
+                    "rentEpoch": 0,
+                    "space": 8
+                }
+            })
+        };
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetAccountInfo,
+            serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "lamports": 1_461_600,
+                    "data": [base64(&[0u8; 82]), "base64"],
+                    "owner": SPL_TOKEN_PROGRAM_V1,
+                    "executable": false,
+                    "rentEpoch": 0,
+                    "space": 82
+                }
+            }),
+        );
+        rpc.set_response(
+            RpcRequest::GetProgramAccounts,
+            serde_json::json!([token_account(1_000_000), token_account(0), token_account(1)]),
+        );
+        let spl = mock_solana(&rpc).create_spl();
+        assert_eq!(spl.get_holder_count(crate::global::RAY).await.unwrap(), 2);
+        assert!(spl.get_holder_count("bad address").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_enrich_nft_metadata() {
+        let data = metadata_account_data("Mad Lad #1", "MAD", "https://example.com/1.json");
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetAccountInfo,
+            serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "lamports": 5_616_720,
+                    "data": [base64::engine::general_purpose::STANDARD.encode(&data), "base64"],
+                    "owner": METAPLEX_TOKEN_METADATA_PROGRAM_ID,
+                    "executable": false,
+                    "rentEpoch": 0,
+                    "space": data.len()
+                }
+            }),
+        );
+        let spl = mock_solana(&rpc).create_spl();
+        // no token or traded base token that would take the mocked account first
+        let mut info = crate::fixtures::load_transaction_info("sol_transfer");
+        info.is_nft_transfer = true;
+        info.nft_mint = Some(Pubkey::new_unique().to_string());
+        spl.enrich_token_metadata(&mut info).await.unwrap();
+        assert_eq!(info.nft_name.as_deref(), Some("Mad Lad #1"));
+        assert_eq!(info.nft_symbol.as_deref(), Some("MAD"));
+    }
+
+    #[tokio::test]
+    async fn test_enrich_token_metadata_without_metadata_account() {
+        let rpc = MockRpc::new().with_response(
+            RpcRequest::GetAccountInfo,
+            serde_json::json!({ "context": { "slot": 1 }, "value": null }),
+        );
+        let spl = mock_solana(&rpc).create_spl();
+        let mut info = crate::fixtures::load_transaction_info("raydium_v4_swap_buy");
+        spl.enrich_token_metadata(&mut info).await.unwrap();
+        assert_eq!(info.token_name, None);
+        assert_eq!(info.token_symbol, None);
+    }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_get_token_info_usdc() {
         let solana = match Solana::new(Mode::MAIN) {
             Ok(s) => s,
@@ -255,6 +1172,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_get_token_program_type() {
         let solana = match Solana::new(Mode::MAIN) {
             Ok(s) => s,
@@ -0,0 +1,76 @@
+use solana_sdk::pubkey::Pubkey;
+
+use crate::global::BUBBLEGUM_PROGRAM_ID;
+use crate::trade::info::{InstructionInfo, TransactionInfo};
+
+/// anchor discriminator of the bubblegum `transfer` instruction
+const TRANSFER_DISCRIMINATOR: [u8; 8] = [163, 52, 200, 231, 140, 3, 69, 186];
+/// discriminator, root, data hash, creator hash, nonce, index
+const TRANSFER_DATA_LEN: usize = 8 + 3 * 32 + 8 + 4;
+
+impl TransactionInfo {
+    /// Check if the transaction transfers a compressed nft with the bubblegum program
+    /// Compressed nfts are leaves of a merkle tree, so they never show up as token balances
+    pub fn is_compressed_nft_transfer(&self) -> bool {
+        self.compressed_nft_asset_id().is_some()
+    }
+
+    /// Get the asset id of the compressed nft the transaction transfers
+    /// The asset id is derived from the merkle tree and the leaf nonce, it takes the place of
+    /// the mint for compressed nfts; Returns None for failed transactions and other transactions
+    pub fn compressed_nft_asset_id(&self) -> Option<String> {
+        if self.is_failed() {
+            return None;
+        }
+        self.instructions_for_program(BUBBLEGUM_PROGRAM_ID)
+            .into_iter()
+            .find_map(Self::bubblegum_transfer_asset_id)
+    }
+
+    fn bubblegum_transfer_asset_id(instruction: &InstructionInfo) -> Option<String> {
+        if !matches!(
+            instruction.program.as_str(),
+            "compiled" | "partially_decoded"
+        ) {
+            return None;
+        }
+        let data = bs58::decode(&instruction.data).into_vec().ok()?;
+        if data.len() < TRANSFER_DATA_LEN || data[..8] != TRANSFER_DISCRIMINATOR {
+            return None;
+        }
+        // accounts: tree authority, leaf owner, leaf delegate, new leaf owner, merkle tree, ...
+        let tree: Pubkey = instruction.accounts.get(4)?.parse().ok()?;
+        let nonce = &data[8 + 3 * 32..8 + 3 * 32 + 8];
+        let (asset_id, _) = Pubkey::find_program_address(
+            &[b"asset", tree.as_ref(), nonce],
+            &Pubkey::from_str_const(BUBBLEGUM_PROGRAM_ID),
+        );
+        Some(asset_id.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::load_transaction_info;
+    use crate::types::TransactionType;
+
+    #[test]
+    fn test_compressed_nft_transfer() {
+        let info = load_transaction_info("bubblegum_transfer");
+        assert!(info.is_compressed_nft_transfer());
+        assert_eq!(
+            info.compressed_nft_asset_id().as_deref(),
+            Some("9PcZCRQNcH4WY1Lf32vnp1Wz7YEvjBqTUhmgUL79k82N")
+        );
+        assert!(info.is_nft_transfer);
+        assert_eq!(info.nft_mint, info.compressed_nft_asset_id());
+        assert_eq!(info.transaction_type, Some(TransactionType::NFTTransfer));
+    }
+
+    #[test]
+    fn test_not_a_compressed_nft_transfer() {
+        let info = load_transaction_info("token_payment");
+        assert!(!info.is_compressed_nft_transfer());
+        assert!(!info.is_nft_transfer);
+    }
+}
@@ -0,0 +1,282 @@
+use solana_sdk::pubkey::Pubkey;
+
+use crate::global::{PUMP_BOND_CURVE_PROGRAM_ID, RAYDIUM_CLMM_POOL_PROGRAM_ID, SOL};
+use crate::tool::{anchor_event_payload, raw_to_ui};
+use crate::trade::info::TransactionInfo;
+
+/// anchor discriminator of the pump.fun bond curve `TradeEvent`
+const PUMP_TRADE_EVENT_DISCRIMINATOR: [u8; 8] = [189, 219, 127, 211, 78, 230, 97, 238];
+/// anchor discriminator of the raydium clmm `SwapEvent`
+const RAYDIUM_CLMM_SWAP_EVENT_DISCRIMINATOR: [u8; 8] = [64, 198, 205, 232, 38, 8, 113, 226];
+/// mint, sol amount, token amount, is buy, user, timestamp, virtual sol and token reserves
+const PUMP_TRADE_EVENT_MIN_LEN: usize = 32 + 8 + 8 + 1 + 32 + 8 + 8 + 8;
+/// newer events go on with the real reserves, fee recipient, fee bps, fee, creator,
+/// creator fee bps and creator fee
+const PUMP_TRADE_EVENT_WITH_FEES_LEN: usize = PUMP_TRADE_EVENT_MIN_LEN + 16 + 32 + 16 + 32 + 16;
+/// pool, sender, token account 0 and 1, amount and transfer fee of both tokens, zero for one
+const RAYDIUM_CLMM_SWAP_EVENT_MIN_LEN: usize = 4 * 32 + 4 * 8 + 1;
+
+/// a pump.fun bond curve buy or sell, decoded from its `TradeEvent`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PumpTradeEvent {
+    pub mint: String,
+    pub sol_amount: u64,   // lamports into or out of the curve, fees excluded
+    pub token_amount: u64, // tokens bought or sold (raw units)
+    pub is_buy: bool,
+    pub user: String,
+    pub timestamp: i64,
+    pub virtual_sol_reserves: u64, // curve reserves after the trade
+    pub virtual_token_reserves: u64,
+    pub fee: u64, // protocol and creator fees in lamports, 0 in events older than the fees
+    pub creator_fee: u64,
+}
+
+impl PumpTradeEvent {
+    /// lamports the user paid for a buy or got for a sell, fees included
+    pub fn quote_amount(&self) -> u64 {
+        let fees = self.fee + self.creator_fee;
+        if self.is_buy {
+            self.sol_amount + fees
+        } else {
+            self.sol_amount.saturating_sub(fees)
+        }
+    }
+
+    /// (token address, raw amount) the user spent
+    pub fn spent_token(&self) -> (String, u64) {
+        if self.is_buy {
+            (SOL.to_string(), self.quote_amount())
+        } else {
+            (self.mint.clone(), self.token_amount)
+        }
+    }
+
+    /// (token address, raw amount) the user received
+    pub fn received_token(&self) -> (String, u64) {
+        if self.is_buy {
+            (self.mint.clone(), self.token_amount)
+        } else {
+            (SOL.to_string(), self.quote_amount())
+        }
+    }
+}
+
+/// a raydium clmm swap, decoded from its `SwapEvent`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RaydiumClmmSwapEvent {
+    pub pool: String,
+    pub sender: String,
+    pub token_account_0: String, // the sender's token accounts of the pool's token 0 and 1
+    pub token_account_1: String,
+    pub amount_0: u64, // raw units
+    pub transfer_fee_0: u64,
+    pub amount_1: u64,
+    pub transfer_fee_1: u64,
+    pub zero_for_one: bool, // token 0 in, token 1 out
+}
+
+impl TransactionInfo {
+    /// Decode the first pump.fun bond curve trade of the transaction from its `TradeEvent`
+    /// Returns None for failed transactions and transactions without a complete trade event
+    pub fn get_pump_trade_event(&self) -> Option<PumpTradeEvent> {
+        if self.is_failed() {
+            return None;
+        }
+        Self::program_data(&self.logs, PUMP_BOND_CURVE_PROGRAM_ID)
+            .iter()
+            .filter_map(|data| anchor_event_payload(data, PUMP_TRADE_EVENT_DISCRIMINATOR))
+            .find_map(Self::decode_pump_trade_event)
+    }
+
+    /// Get the (virtual sol, virtual token) reserves of the pump.fun bond curve after the trade
+    pub fn get_virtual_reserves(&self) -> Option<(u64, u64)> {
+        let event = self.get_pump_trade_event()?;
+        Some((event.virtual_sol_reserves, event.virtual_token_reserves))
+    }
+
+    /// Get the pump.fun bond curve price in SOL per token after the trade
+    /// Read from the curve's virtual reserves, so it is the exact mid price even for tiny
+    /// trades, unlike `get_token_quote_ratio` which is the price the trade executed at
+    pub fn get_price_from_curve(&self) -> Option<f64> {
+        let event = self.get_pump_trade_event()?;
+        let sol = raw_to_ui(event.virtual_sol_reserves, self.get_token_decimals(SOL)?);
+        let tokens = raw_to_ui(
+            event.virtual_token_reserves,
+            self.get_token_decimals(&event.mint)?,
+        );
+        (sol > 0.0 && tokens > 0.0).then(|| sol / tokens)
+    }
+
+    fn decode_pump_trade_event(event: &[u8]) -> Option<PumpTradeEvent> {
+        if event.len() < PUMP_TRADE_EVENT_MIN_LEN {
+            return None;
+        }
+        let u64_at =
+            |offset: usize| u64::from_le_bytes(event[offset..offset + 8].try_into().unwrap());
+        let pubkey_at = |offset: usize| {
+            Pubkey::new_from_array(event[offset..offset + 32].try_into().unwrap()).to_string()
+        };
+        let (fee, creator_fee) = if event.len() >= PUMP_TRADE_EVENT_WITH_FEES_LEN {
+            (u64_at(161), u64_at(209))
+        } else {
+            (0, 0)
+        };
+        Some(PumpTradeEvent {
+            mint: pubkey_at(0),
+            sol_amount: u64_at(32),
+            token_amount: u64_at(40),
+            is_buy: event[48] != 0,
+            user: pubkey_at(49),
+            timestamp: u64_at(81) as i64,
+            virtual_sol_reserves: u64_at(89),
+            virtual_token_reserves: u64_at(97),
+            fee,
+            creator_fee,
+        })
+    }
+
+    /// Decode the first raydium clmm swap of the transaction from its `SwapEvent`
+    /// Returns None for failed transactions and transactions without a clmm swap event
+    pub fn get_raydium_clmm_swap_event(&self) -> Option<RaydiumClmmSwapEvent> {
+        if self.is_failed() {
+            return None;
+        }
+        Self::program_data(&self.logs, RAYDIUM_CLMM_POOL_PROGRAM_ID)
+            .iter()
+            .filter_map(|data| anchor_event_payload(data, RAYDIUM_CLMM_SWAP_EVENT_DISCRIMINATOR))
+            .find_map(Self::decode_raydium_clmm_swap_event)
+    }
+
+    fn decode_raydium_clmm_swap_event(event: &[u8]) -> Option<RaydiumClmmSwapEvent> {
+        if event.len() < RAYDIUM_CLMM_SWAP_EVENT_MIN_LEN {
+            return None;
+        }
+        let u64_at =
+            |offset: usize| u64::from_le_bytes(event[offset..offset + 8].try_into().unwrap());
+        let pubkey_at = |offset: usize| {
+            Pubkey::new_from_array(event[offset..offset + 32].try_into().unwrap()).to_string()
+        };
+        Some(RaydiumClmmSwapEvent {
+            pool: pubkey_at(0),
+            sender: pubkey_at(32),
+            token_account_0: pubkey_at(64),
+            token_account_1: pubkey_at(96),
+            amount_0: u64_at(128),
+            transfer_fee_0: u64_at(136),
+            amount_1: u64_at(144),
+            transfer_fee_1: u64_at(152),
+            zero_for_one: event[160] != 0,
+        })
+    }
+
+    /// ((spent mint, raw amount), (received mint, raw amount)) of a raydium clmm swap event,
+    /// the mints are read from the token balances of the sender's token accounts
+    pub(crate) fn raydium_clmm_swap_tokens(&self) -> Option<((String, u64), (String, u64))> {
+        let event = self.get_raydium_clmm_swap_event()?;
+        let token_0 = (
+            self.token_account_mint(&event.token_account_0)?,
+            event.amount_0,
+        );
+        let token_1 = (
+            self.token_account_mint(&event.token_account_1)?,
+            event.amount_1,
+        );
+        Some(if event.zero_for_one {
+            (token_0, token_1)
+        } else {
+            (token_1, token_0)
+        })
+    }
+
+    /// mint of a token account of the transaction, from its token balances
+    fn token_account_mint(&self, account: &str) -> Option<String> {
+        let index = self
+            .raw_account_keys
+            .iter()
+            .position(|key| key == account)?;
+        self.raw_pre_token_balances
+            .iter()
+            .chain(&self.raw_post_token_balances)
+            .find(|balance| balance.account_index as usize == index)
+            .map(|balance| balance.mint.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::load_transaction_info;
+    use crate::global::{SOL, USDC, WSOL};
+    use crate::types::Direction;
+
+    const PUMP_MINT: &str = "6ScPNL11uxcnepV8nYswqfDcBrgkHWpgo7sAXKdjHcwx";
+
+    #[test]
+    fn test_pump_trade_event() {
+        let info = load_transaction_info("pump_bond_curve_sell");
+        let event = info.get_pump_trade_event().unwrap();
+        assert_eq!(event.mint, PUMP_MINT);
+        assert!(!event.is_buy);
+        assert_eq!(event.user, "3D8HnHvXc7Z5t1MshyawgNFMET4mUJ6BrsdbsodDkpST");
+        assert_eq!(event.sol_amount, 300_000_000);
+        assert_eq!(event.token_amount, 10_000_000_000_000);
+        assert_eq!((event.fee, event.creator_fee), (3_000_000, 150_000));
+        // the amount getters read the event: the seller gets the sol net of fees
+        assert_eq!(
+            info.get_received_token_sol(),
+            Some((SOL.to_string(), 296_850_000))
+        );
+        assert_eq!(
+            info.get_spent_token_sol(),
+            Some((PUMP_MINT.to_string(), 10_000_000_000_000))
+        );
+        assert_eq!(info.get_direction(), Direction::Sell);
+        let ratio = info.get_token_quote_ratio().unwrap();
+        assert!((ratio - 0.29685 / 10_000_000.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_pump_curve_price() {
+        let info = load_transaction_info("pump_bond_curve_sell");
+        assert_eq!(
+            info.get_virtual_reserves(),
+            Some((39_700_000_000, 790_000_000_000_000))
+        );
+        let price = info.get_price_from_curve().unwrap();
+        assert!((price - 39.7 / 790_000_000.0).abs() < 1e-18);
+        let info = load_transaction_info("pump_bond_curve_buy");
+        assert!(info.get_virtual_reserves().is_none());
+        assert!(info.get_price_from_curve().is_none());
+    }
+
+    #[test]
+    fn test_truncated_pump_trade_event_falls_back_to_balances() {
+        let info = load_transaction_info("pump_bond_curve_buy");
+        assert!(info.get_pump_trade_event().is_none());
+        assert!(info.get_token_quote_ratio().is_some());
+    }
+
+    #[test]
+    fn test_raydium_clmm_swap_event() {
+        let info = load_transaction_info("raydium_clmm_swap");
+        let event = info.get_raydium_clmm_swap_event().unwrap();
+        assert_eq!(event.pool, "Cqf58ScqWuoKP7B822DnzT4s51AjMKzbN9tekVK72Cqn");
+        assert!(event.zero_for_one);
+        assert_eq!(
+            (event.amount_0, event.amount_1),
+            (1_000_000_000, 180_000_000)
+        );
+        assert_eq!(
+            info.get_spent_token_sol(),
+            Some((WSOL.to_string(), 1_000_000_000))
+        );
+        assert_eq!(
+            info.get_received_token_sol(),
+            Some((USDC.to_string(), 180_000_000))
+        );
+        assert!(
+            load_transaction_info("raydium_v4_swap_buy")
+                .get_raydium_clmm_swap_event()
+                .is_none()
+        );
+    }
+}
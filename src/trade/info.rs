@@ -1,22 +1,28 @@
+use std::collections::HashSet;
+use std::sync::{Mutex, PoisonError};
 use std::vec;
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use solana_sdk::native_token::LAMPORTS_PER_SOL;
-use solana_sdk::transaction::TransactionVersion;
+use solana_sdk::transaction::{TransactionVersion, VersionedTransaction};
 use solana_transaction_status::option_serializer::OptionSerializer;
+use solana_transaction_status::parse_accounts::ParsedAccountSource;
 use solana_transaction_status::{
-    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction, UiMessage, UiParsedInstruction,
-    UiTransactionTokenBalance,
+    EncodedConfirmedTransactionWithStatusMeta, EncodedTransaction,
+    EncodedTransactionWithStatusMeta, UiAddressTableLookup, UiCompiledInstruction, UiMessage,
+    UiParsedInstruction, UiRawMessage, UiTransaction, UiTransactionTokenBalance,
 };
 
 use crate::Solana;
 use crate::global::{
-    METEORA_DAMM_V2_PROGRAM_ID, METEORA_DLMM_V2_PROGRAM_ID, METEORA_DYNAMIC_BOND_CURVE_PROGRAM_ID,
-    METEORA_POOL_PROGRAM_ID, ORCA_WHIRLPOOLS_PROGRAM_ID, PUMP_AAM_PROGRAM_ID,
-    PUMP_BOND_CURVE_PROGRAM_ID, RAYDIUM_CLMM_POOL_PROGRAM_ID, RAYDIUM_CPMM_POOL_PROGRAM_ID,
-    RAYDIUM_LAUNCHPAD_PROGRAM_ID, RAYDIUM_V4_POOL_PROGRAM_ID,
+    ASSOCIATED_TOKEN_PROGRAM_ID, DEX_LOG_KEYWORDS, DEX_PROGRAM_IDS, METEORA_DAMM_V2_PROGRAM_ID,
+    METEORA_DLMM_V2_PROGRAM_ID, METEORA_DYNAMIC_BOND_CURVE_PROGRAM_ID, METEORA_POOL_PROGRAM_ID,
+    ORCA_WHIRLPOOLS_PROGRAM_ID, PUMP_AAM_PROGRAM_ID, PUMP_BOND_CURVE_PROGRAM_ID,
+    RAYDIUM_CLMM_POOL_PROGRAM_ID, RAYDIUM_CPMM_POOL_PROGRAM_ID, RAYDIUM_LAUNCHPAD_PROGRAM_ID,
+    RAYDIUM_V4_POOL_PROGRAM_ID, SYSTEM_PROGRAM_ID, WSOL,
 };
+use crate::tool::{lamports_to_sol, now_unix_secs, ui_str_to_raw};
 use crate::trade::Trade;
 use crate::types::{DexProgramType, TransactionType};
 
@@ -65,6 +71,7 @@ pub struct TransactionInfo {
     // Resource Consumption
     pub compute_units_consumed: Option<u64>, // Compute units consumed
     pub compute_unit_price: Option<u64>,     // Compute unit price
+    pub compute_unit_limit: Option<u32>,     // Requested compute unit limit
     // Instructions and Logs
     pub log_index: u64,
     pub data: Option<String>,
@@ -112,7 +119,7 @@ pub struct TransactionInfo {
     pub source: String,  // Data source
     pub confidence: f64, // Data confidence level 0.0-1.0
     // raw data
-    pub raw_account_keys: Vec<String>, // raw account_keys
+    pub raw_account_keys: Vec<String>, // static account keys, then the keys loaded from address lookup tables
     pub raw_pre_balances: Vec<u64>,    // raw pre_balances
     pub raw_post_balances: Vec<u64>,   // raw post_balances
     pub raw_pre_token_balances: Vec<RawTokenBalance>, // raw pre_token_balances
@@ -122,6 +129,47 @@ pub struct TransactionInfo {
     pub instructions: Vec<InstructionInfo>, // Instruction details
     pub inner_instructions: Vec<InnerInstructionInfo>, // Inner instructions
     pub inner_instructions_count: u64,      // Number of inner instructions
+    // quote configuration
+    pub extra_quotes: Vec<String>, // Quote mints recognized on top of QUOTES
+    // cache
+    pub net_token_changes_cache: NetTokenChangesCache, // Signer net token changes
+}
+
+/// memo of `TransactionInfo::net_token_changes`, kept with a fingerprint of the signer,
+/// fee payer and token balances it was computed from, so a changed input recomputes it
+#[derive(Debug, Default)]
+pub struct NetTokenChangesCache(Mutex<Option<(u64, NetTokenChanges)>>);
+
+/// (mint, raw change) per mint of the signer
+type NetTokenChanges = Vec<(String, i64)>;
+
+impl NetTokenChangesCache {
+    /// the memoized changes when they were computed for `fingerprint`, else the ones of `compute`
+    pub(crate) fn get_or_compute<F>(&self, fingerprint: u64, compute: F) -> NetTokenChanges
+    where
+        F: FnOnce() -> NetTokenChanges,
+    {
+        let mut memo = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some((memo_fingerprint, changes)) = memo.as_ref()
+            && *memo_fingerprint == fingerprint
+        {
+            return changes.clone();
+        }
+        let changes = compute();
+        *memo = Some((fingerprint, changes.clone()));
+        changes
+    }
+}
+
+impl Clone for NetTokenChangesCache {
+    fn clone(&self) -> Self {
+        Self(Mutex::new(
+            self.0
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .clone(),
+        ))
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -133,6 +181,17 @@ pub struct RawTokenBalance {
     pub program_id: Option<String>,
 }
 
+/// raw balance change of one token account in a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TokenAccountChange {
+    pub account_index: u8,
+    pub owner: Option<String>,
+    pub mint: String,
+    pub decimals: u8,
+    /// post minus pre amount in raw units, positive means received
+    pub delta: i128,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RawInstruction {
     pub program_id_index: Option<u8>,
@@ -145,7 +204,7 @@ pub struct RawInstruction {
 impl TransactionInfo {
     // Get the maximum amount of a specified token address
     fn get_max_amount_for_mint(&self, mint: &str) -> Option<u64> {
-        use crate::global::SOL;
+        use crate::global::{SOL, WSOL};
         // Extract the maximum amount from the log.
         let mut max_amount = 0u64;
         // Find the maximum amount corresponding to the token in the log.
@@ -159,13 +218,13 @@ impl TransactionInfo {
                     // Find the first number from back to front.
                     for part in parts.iter().rev() {
                         let cleaned = part.replace(',', "");
-                        if let Ok(amount_f64) = cleaned.parse::<f64>() {
-                            let amount = if mint == SOL {
-                                (amount_f64 * LAMPORTS_PER_SOL as f64) as u64
-                            } else {
-                                (amount_f64 * 1_000_000.0) as u64
-                            };
-
+                        let decimals = if mint == SOL || mint == WSOL {
+                            9
+                        } else {
+                            // fall back to 6 decimals only when the mint has no balance entry
+                            self.get_token_decimals_for_mint(mint).unwrap_or(6)
+                        };
+                        if let Some(amount) = ui_str_to_raw(&cleaned, decimals) {
                             if amount > max_amount {
                                 max_amount = amount;
                             }
@@ -182,7 +241,7 @@ impl TransactionInfo {
                 let pre_amount = self
                     .pre_token_balances
                     .iter()
-                    .find(|b| b.mint == mint && b.owner == post_balance.owner)
+                    .find(|b| b.account_index == post_balance.account_index)
                     .and_then(|b| b.ui_token_amount.amount.parse::<u64>().ok())
                     .unwrap_or(0);
                 let post_amount = post_balance
@@ -205,7 +264,7 @@ impl TransactionInfo {
                 let post_amount = self
                     .post_token_balances
                     .iter()
-                    .find(|b| b.mint == mint && b.owner == pre_balance.owner)
+                    .find(|b| b.account_index == pre_balance.account_index)
                     .and_then(|b| b.ui_token_amount.amount.parse::<u64>().ok())
                     .unwrap_or(0);
                 let pre_amount = pre_balance
@@ -242,15 +301,12 @@ impl TransactionInfo {
     }
 
     fn get_token_decimals_for_mint(&self, mint: &str) -> Option<u8> {
-        use crate::global::SOL;
-        if mint == SOL {
+        use crate::global::{SOL, WSOL};
+        if mint == SOL || mint == WSOL {
             return Some(9);
         }
-        use crate::global::{USDC, USDT};
-        if mint == USDC {
-            return Some(6);
-        }
-        if mint == USDT {
+        use crate::global::{USD_1, USDC, USDT};
+        if mint == USDC || mint == USDT || mint == USD_1 {
             return Some(6);
         }
         for balance in self
@@ -293,7 +349,7 @@ impl TransactionInfo {
                 .transpose()
                 .unwrap_or(None);
             info.fee = meta.fee;
-            info.fee_sol = meta.fee as f64 / LAMPORTS_PER_SOL as f64;
+            info.fee_sol = lamports_to_sol(meta.fee);
             info.compute_units_consumed = match &meta.compute_units_consumed {
                 OptionSerializer::Some(value) => Some(*value),
                 OptionSerializer::None => None,
@@ -301,16 +357,83 @@ impl TransactionInfo {
             };
         }
         Self::parse_transaction_content(&mut info, tx);
-        info.created_at = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
+        // compressed nfts live in a merkle tree, they move no token balances
+        if let Some(asset_id) = info.compressed_nft_asset_id() {
+            info.is_nft_transfer = true;
+            info.nft_mint = Some(asset_id);
+            info.transaction_type = Some(TransactionType::NFTTransfer);
+        }
+        info.priority_fee = info.fee_breakdown().priority_fee;
+        info.resolve_swap_legs();
+        // a reverted swap is still flagged as a swap, but it moved no tokens
+        if info.is_failed() {
+            info.input_amount = None;
+            info.output_amount = None;
+        }
+        info.created_at = now_unix_secs();
         info.updated_at = info.created_at;
         info.source = "rpc".to_string();
-        info.confidence = 1.0;
         info
     }
 
+    /// Parse a serialized (bincode) transaction that was not fetched from the rpc,
+    /// e.g. one read from a mempool feed or built by a wallet before sending
+    /// Only the message is available: accounts, compiled instructions, signers, fee payer and
+    /// the compute budget are filled in, balances, logs, fees and transfer amounts are not
+    /// Accounts loaded from address lookup tables cannot be resolved without the rpc
+    ///
+    /// # Example
+    /// ```ignore
+    /// let bytes = base64::engine::general_purpose::STANDARD.decode(encoded)?;
+    /// let info = TransactionInfo::from_raw_transaction(&bytes)?;
+    /// ```
+    pub fn from_raw_transaction(bytes: &[u8]) -> Result<TransactionInfo, String> {
+        let tx: VersionedTransaction = bincode::deserialize(bytes)
+            .map_err(|e| format!("deserialize transaction error: {:?}", e))?;
+        tx.sanitize()
+            .map_err(|e| format!("invalid transaction: {:?}", e))?;
+        let signature = tx
+            .signatures
+            .first()
+            .map(|signature| signature.to_string())
+            .unwrap_or_default();
+        let keys = tx.message.static_account_keys();
+        let message = UiRawMessage {
+            header: *tx.message.header(),
+            account_keys: keys.iter().map(|key| key.to_string()).collect(),
+            recent_blockhash: tx.message.recent_blockhash().to_string(),
+            instructions: tx
+                .message
+                .instructions()
+                .iter()
+                .map(|instruction| UiCompiledInstruction::from(instruction, None))
+                .collect(),
+            address_table_lookups: tx
+                .message
+                .address_table_lookups()
+                .map(|lookups| lookups.iter().map(UiAddressTableLookup::from).collect()),
+        };
+        let encoded = EncodedConfirmedTransactionWithStatusMeta {
+            slot: 0,
+            transaction: EncodedTransactionWithStatusMeta {
+                transaction: EncodedTransaction::Json(UiTransaction {
+                    signatures: tx.signatures.iter().map(|s| s.to_string()).collect(),
+                    message: UiMessage::Raw(message),
+                }),
+                meta: None,
+                version: Some(tx.version()),
+            },
+            block_time: None,
+        };
+        let mut info = Self::from_encoded_transaction(&encoded, &signature);
+        let signers = &keys[..tx.message.header().num_required_signatures as usize];
+        info.signers = signers.iter().map(|key| key.to_string()).collect();
+        info.fee_payer = info.signers.first().cloned().unwrap_or_default();
+        info.signer = info.fee_payer.clone();
+        info.source = "raw".to_string();
+        Ok(info)
+    }
+
     fn parse_raw_arrays(
         info: &mut TransactionInfo,
         tx: &EncodedConfirmedTransactionWithStatusMeta,
@@ -318,18 +441,21 @@ impl TransactionInfo {
         match &tx.transaction.transaction {
             EncodedTransaction::Json(json_tx) => match &json_tx.message {
                 UiMessage::Parsed(parsed_msg) => {
-                    info.raw_account_keys = parsed_msg
+                    // parsed messages list the loaded keys too, they are appended from the meta below
+                    let static_keys: Vec<String> = parsed_msg
                         .account_keys
                         .iter()
+                        .filter(|acc| acc.source != Some(ParsedAccountSource::LookupTable))
                         .map(|acc| acc.pubkey.clone())
                         .collect();
+                    info.raw_account_keys =
+                        Self::full_account_keys(&static_keys, tx.transaction.meta.as_ref());
                 }
                 UiMessage::Raw(raw_msg) => {
-                    info.raw_account_keys = raw_msg
-                        .account_keys
-                        .iter()
-                        .map(|pk| pk.to_string())
-                        .collect();
+                    info.raw_account_keys = Self::full_account_keys(
+                        &raw_msg.account_keys,
+                        tx.transaction.meta.as_ref(),
+                    );
                 }
             },
             _ => {
@@ -469,15 +595,29 @@ impl TransactionInfo {
                     },
                 };
                 InstructionInfo {
-                    program_id: Self::extract_program_id_from_ui_instruction(inst),
-                    accounts: Self::extract_accounts_from_ui_instruction(inst),
+                    program_id: Self::extract_program_id_from_ui_instruction(
+                        inst,
+                        &info.involved_accounts,
+                    ),
+                    accounts: Self::extract_accounts_from_ui_instruction(
+                        inst,
+                        &info.involved_accounts,
+                    ),
                     data: Self::extract_data_from_ui_instruction(inst),
                     stack_height: stack_height,
                     program: program,
+                    parent_index: None,
                 }
             })
             .collect();
         info.recent_blockhash = parsed_msg.recent_blockhash.clone();
+        for instruction in info.instructions.clone() {
+            Self::parse_compute_budget_instruction(
+                info,
+                &instruction.program_id,
+                &instruction.data,
+            );
+        }
         Self::extract_transfer_info(info, parsed_msg);
     }
 
@@ -493,10 +633,93 @@ impl TransactionInfo {
             .collect();
         info.instructions_count = raw_msg.instructions.len() as u64;
         info.recent_blockhash = raw_msg.recent_blockhash.clone();
+        let key_at = |index: u8| {
+            info.raw_account_keys
+                .get(index as usize)
+                .cloned()
+                .unwrap_or_default()
+        };
+        info.instructions = raw_msg
+            .instructions
+            .iter()
+            .map(|instruction| InstructionInfo {
+                program_id: key_at(instruction.program_id_index),
+                accounts: instruction
+                    .accounts
+                    .iter()
+                    .map(|index| key_at(*index))
+                    .collect(),
+                data: instruction.data.clone(),
+                stack_height: instruction.stack_height,
+                program: "compiled".to_string(),
+                parent_index: None,
+            })
+            .collect();
+        for instruction in info.instructions.clone() {
+            Self::parse_compute_budget_instruction(
+                info,
+                &instruction.program_id,
+                &instruction.data,
+            );
+        }
         info.transaction_type = Some(TransactionType::Raw);
         info.program_id = "unknown".to_string();
     }
 
+    /// static account keys followed by the keys loaded from address lookup tables
+    fn full_account_keys(
+        static_keys: &[String],
+        meta: Option<&solana_transaction_status::UiTransactionStatusMeta>,
+    ) -> Vec<String> {
+        let mut keys = static_keys.to_vec();
+        if let Some(OptionSerializer::Some(loaded)) = meta.map(|meta| &meta.loaded_addresses) {
+            keys.extend(loaded.writable.iter().cloned());
+            keys.extend(loaded.readonly.iter().cloned());
+        }
+        keys
+    }
+
+    /// point every inner instruction at the inner instruction that invoked it, using the stack heights
+    /// instructions invoked by the top-level instruction itself, or without a stack height, keep None
+    fn link_inner_instruction_parents(instructions: &mut [InstructionInfo]) {
+        let mut invoke_stack: Vec<(u32, usize)> = Vec::new();
+        for (index, instruction) in instructions.iter_mut().enumerate() {
+            let Some(height) = instruction.stack_height else {
+                continue;
+            };
+            while invoke_stack.last().is_some_and(|(h, _)| *h >= height) {
+                invoke_stack.pop();
+            }
+            instruction.parent_index = invoke_stack.last().map(|(_, parent)| *parent);
+            invoke_stack.push((height, index));
+        }
+    }
+
+    /// read the requested compute unit limit and price from a compute budget instruction
+    fn parse_compute_budget_instruction(info: &mut TransactionInfo, program_id: &str, data: &str) {
+        use crate::global::COMPUTE_BUDGET_PROGRAM_ID;
+        if program_id != COMPUTE_BUDGET_PROGRAM_ID {
+            return;
+        }
+        let Ok(bytes) = bs58::decode(data).into_vec() else {
+            return;
+        };
+        match bytes.split_first() {
+            // SetComputeUnitLimit(u32)
+            Some((2, rest)) if rest.len() >= 4 => {
+                info.compute_unit_limit =
+                    Some(u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]));
+            }
+            // SetComputeUnitPrice(u64), in micro-lamports
+            Some((3, rest)) if rest.len() >= 8 => {
+                let mut price = [0u8; 8];
+                price.copy_from_slice(&rest[..8]);
+                info.compute_unit_price = Some(u64::from_le_bytes(price));
+            }
+            _ => {}
+        }
+    }
+
     /// parse metadata
     fn parse_metadata(
         info: &mut TransactionInfo,
@@ -509,22 +732,49 @@ impl TransactionInfo {
         }
         match &meta.inner_instructions {
             OptionSerializer::Some(inner_instructions) => {
+                let account_keys = info.raw_account_keys.clone();
                 info.inner_instructions_count = inner_instructions.len() as u64;
                 info.inner_instructions = inner_instructions
                     .iter()
-                    .map(|inner| InnerInstructionInfo {
-                        index: inner.index,
-                        instructions: inner
+                    .map(|inner| {
+                        let mut instructions: Vec<InstructionInfo> = inner
                             .instructions
                             .iter()
-                            .map(|inst| InstructionInfo {
-                                program_id: Self::extract_program_id_from_ui_instruction(inst),
-                                accounts: Self::extract_accounts_from_ui_instruction(inst),
-                                data: Self::extract_data_from_ui_instruction(inst),
-                                stack_height: None,
-                                program: "inner".to_string(),
+                            .map(|inst| {
+                                let (stack_height, program) = match inst {
+                                    solana_transaction_status::UiInstruction::Compiled(
+                                        compiled,
+                                    ) => (compiled.stack_height, "compiled".to_string()),
+                                    solana_transaction_status::UiInstruction::Parsed(
+                                        UiParsedInstruction::Parsed(parsed_inst),
+                                    ) => (parsed_inst.stack_height, parsed_inst.program.clone()),
+                                    solana_transaction_status::UiInstruction::Parsed(
+                                        UiParsedInstruction::PartiallyDecoded(partial_inst),
+                                    ) => {
+                                        (partial_inst.stack_height, "partially_decoded".to_string())
+                                    }
+                                };
+                                InstructionInfo {
+                                    program_id: Self::extract_program_id_from_ui_instruction(
+                                        inst,
+                                        &account_keys,
+                                    ),
+                                    accounts: Self::extract_accounts_from_ui_instruction(
+                                        inst,
+                                        &account_keys,
+                                    ),
+                                    data: Self::extract_data_from_ui_instruction(inst),
+                                    stack_height,
+                                    program,
+                                    parent_index: None,
+                                }
                             })
-                            .collect(),
+                            .collect();
+                        Self::link_inner_instruction_parents(&mut instructions);
+                        InnerInstructionInfo {
+                            index: inner.index,
+                            instructions,
+                        }
                     })
                     .collect();
             }
@@ -535,6 +785,7 @@ impl TransactionInfo {
         }
         // parse balance changes
         Self::parse_balance_changes(info, meta, tx);
+        Self::collect_involved_accounts(info);
         // token balance
         match &meta.pre_token_balances {
             OptionSerializer::Some(pre_token_balances) => {
@@ -614,40 +865,15 @@ impl TransactionInfo {
                 }
                 if let (Some(from_idx), Some(to_idx)) = (from_index, to_index) {
                     if info.from == "unknown" || info.to == "unknown" {
-                        // Try to get the account address from the transaction
-                        let transaction_with_meta = &tx.transaction;
-                        if let EncodedTransaction::Json(json_tx) =
-                            &transaction_with_meta.transaction
-                        {
-                            match &json_tx.message {
-                                UiMessage::Parsed(parsed_msg) => {
-                                    if let account_keys = &parsed_msg.account_keys {
-                                        if let (Some(from_account), Some(to_account)) =
-                                            (account_keys.get(from_idx), account_keys.get(to_idx))
-                                        {
-                                            let (from_pubkey, to_pubkey) =
-                                                (&from_account.pubkey, &to_account.pubkey);
-                                            info.from = from_pubkey.clone();
-                                            info.to = to_pubkey.clone();
-                                            info.value = transfer_amount.to_string();
-                                            info.value_sol =
-                                                transfer_amount as f64 / LAMPORTS_PER_SOL as f64;
-                                        }
-                                    }
-                                }
-                                UiMessage::Raw(raw_msg) => {
-                                    if let (Some(from_account), Some(to_account)) = (
-                                        raw_msg.account_keys.get(from_idx),
-                                        raw_msg.account_keys.get(to_idx),
-                                    ) {
-                                        info.from = from_account.to_string();
-                                        info.to = to_account.to_string();
-                                        info.value = transfer_amount.to_string();
-                                        info.value_sol =
-                                            transfer_amount as f64 / LAMPORTS_PER_SOL as f64;
-                                    }
-                                }
-                            }
+                        // balances follow the resolved account keys, lookup table keys included
+                        if let (Some(from_account), Some(to_account)) = (
+                            info.raw_account_keys.get(from_idx).cloned(),
+                            info.raw_account_keys.get(to_idx).cloned(),
+                        ) {
+                            info.from = from_account;
+                            info.to = to_account;
+                            info.value = transfer_amount.to_string();
+                            info.value_sol = lamports_to_sol(transfer_amount);
                         }
                     }
                 }
@@ -692,16 +918,26 @@ impl TransactionInfo {
                         info.readonly_accounts = Vec::new();
                     }
                 }
-                // Collect all involved accounts
-                let mut all_accounts = Vec::new();
-                all_accounts.extend(info.writable_accounts.clone());
-                all_accounts.extend(info.readonly_accounts.clone());
-                all_accounts.dedup();
-                info.involved_accounts = all_accounts;
             }
         }
     }
 
+    /// every account of the transaction once, in first-seen order: the account keys
+    /// (static, then loaded from lookup tables), the message keys, then the loaded writable
+    /// and readonly accounts
+    fn collect_involved_accounts(info: &mut TransactionInfo) {
+        let mut seen: HashSet<String> = HashSet::new();
+        info.involved_accounts = info
+            .raw_account_keys
+            .iter()
+            .chain(&info.involved_accounts)
+            .chain(&info.writable_accounts)
+            .chain(&info.readonly_accounts)
+            .filter(|account| seen.insert(account.to_string()))
+            .cloned()
+            .collect();
+    }
+
     /// parse token transactions
     fn parse_token_transactions(
         info: &mut TransactionInfo,
@@ -721,6 +957,15 @@ impl TransactionInfo {
                         Self::check_nft_transfer(info, pre_balances, post_balances);
                         // check dex transaction
                         Self::check_dex_transaction(info, meta);
+                        if info.transaction_type == Some(TransactionType::Swap) {
+                            info.is_swap = true;
+                        }
+                        // check pool creation
+                        if info.is_bond_curve_migration() {
+                            info.transaction_type = Some(TransactionType::Migration);
+                        } else if info.is_pool_creation() {
+                            info.transaction_type = Some(TransactionType::PoolCreate);
+                        }
                     }
                 }
                 _ => {}
@@ -729,59 +974,75 @@ impl TransactionInfo {
     }
 
     // check token transfers
+    // pre and post balances are paired by account index, the rpc does not keep their order
+    // and an account created or closed in the transaction only appears on one side
     fn check_token_transfers(
         info: &mut TransactionInfo,
         pre_balances: &[UiTransactionTokenBalance],
         post_balances: &[UiTransactionTokenBalance],
     ) {
-        for pre_balance in pre_balances {
-            if let Some(post_balance) = post_balances
+        let mut account_indexes: Vec<u8> = pre_balances
+            .iter()
+            .chain(post_balances)
+            .map(|balance| balance.account_index)
+            .collect();
+        account_indexes.sort_unstable();
+        account_indexes.dedup();
+        for account_index in account_indexes {
+            let pre_balance = pre_balances
                 .iter()
-                .find(|pb| pb.mint == pre_balance.mint && pb.owner == pre_balance.owner)
-            {
-                let pre_amount = pre_balance
-                    .ui_token_amount
-                    .amount
-                    .parse::<u64>()
-                    .unwrap_or(0);
-                let post_amount = post_balance
-                    .ui_token_amount
-                    .amount
-                    .parse::<u64>()
-                    .unwrap_or(0);
-
-                if pre_amount != post_amount {
-                    info.pre_token_balances.push(TokenBalance {
-                        account_index: pre_balance.account_index,
-                        mint: pre_balance.mint.clone(),
-                        owner: pre_balance.owner.clone().unwrap_or("".to_string()),
-                        ui_token_amount: UiTokenAmount {
-                            ui_amount: pre_balance.ui_token_amount.ui_amount,
-                            decimals: pre_balance.ui_token_amount.decimals,
-                            amount: pre_balance.ui_token_amount.amount.clone(),
-                            ui_amount_string: Some(
-                                pre_balance.ui_token_amount.ui_amount_string.clone(),
-                            ),
-                        },
-                    });
-                    info.post_token_balances.push(TokenBalance {
-                        account_index: post_balance.account_index,
-                        mint: post_balance.mint.clone(),
-                        owner: post_balance.owner.clone().unwrap_or("".to_string()),
-                        ui_token_amount: UiTokenAmount {
-                            ui_amount: post_balance.ui_token_amount.ui_amount,
-                            decimals: post_balance.ui_token_amount.decimals,
-                            amount: post_balance.ui_token_amount.amount.clone(),
-                            ui_amount_string: Some(
-                                post_balance.ui_token_amount.ui_amount_string.clone(),
-                            ),
-                        },
-                    });
-                }
+                .find(|balance| balance.account_index == account_index);
+            let post_balance = post_balances
+                .iter()
+                .find(|balance| balance.account_index == account_index);
+            let Some(known) = pre_balance.or(post_balance) else {
+                continue;
+            };
+            let amount_of = |balance: Option<&UiTransactionTokenBalance>| {
+                balance
+                    .and_then(|balance| balance.ui_token_amount.amount.parse::<u64>().ok())
+                    .unwrap_or(0)
+            };
+            if amount_of(pre_balance) != amount_of(post_balance) {
+                info.pre_token_balances
+                    .push(Self::to_token_balance(pre_balance, known));
+                info.post_token_balances
+                    .push(Self::to_token_balance(post_balance, known));
             }
         }
     }
 
+    // convert an rpc token balance, a missing balance is an empty account of the same mint
+    fn to_token_balance(
+        balance: Option<&UiTransactionTokenBalance>,
+        known: &UiTransactionTokenBalance,
+    ) -> TokenBalance {
+        match balance {
+            Some(balance) => TokenBalance {
+                account_index: balance.account_index,
+                mint: balance.mint.clone(),
+                owner: balance.owner.clone().unwrap_or("".to_string()),
+                ui_token_amount: UiTokenAmount {
+                    ui_amount: balance.ui_token_amount.ui_amount,
+                    decimals: balance.ui_token_amount.decimals,
+                    amount: balance.ui_token_amount.amount.clone(),
+                    ui_amount_string: Some(balance.ui_token_amount.ui_amount_string.clone()),
+                },
+            },
+            None => TokenBalance {
+                account_index: known.account_index,
+                mint: known.mint.clone(),
+                owner: known.owner.clone().unwrap_or("".to_string()),
+                ui_token_amount: UiTokenAmount {
+                    ui_amount: None,
+                    decimals: known.ui_token_amount.decimals,
+                    amount: "0".to_string(),
+                    ui_amount_string: Some("0".to_string()),
+                },
+            },
+        }
+    }
+
     // check nft transfer
     fn check_nft_transfer(
         info: &mut TransactionInfo,
@@ -801,50 +1062,30 @@ impl TransactionInfo {
         }
     }
 
+    /// flag swaps by the known dex programs invoked in `logs`, falling back to trade keywords
+    /// in the logs of unknown programs with a lowered confidence
+    fn detect_swap(info: &mut TransactionInfo, logs: &[String]) {
+        if logs
+            .iter()
+            .any(|log| DEX_PROGRAM_IDS.iter().any(|program| log.contains(program)))
+        {
+            info.is_swap = true;
+        } else if logs
+            .iter()
+            .any(|log| DEX_LOG_KEYWORDS.iter().any(|keyword| log.contains(keyword)))
+        {
+            info.is_swap = true;
+            info.confidence = info.confidence.min(KEYWORD_SWAP_CONFIDENCE);
+        }
+    }
+
     /// check dex transaction
     fn check_dex_transaction(
         info: &mut TransactionInfo,
         meta: &solana_transaction_status::UiTransactionStatusMeta,
     ) {
         if let logs = &meta.log_messages {
-            let dex_keywords = [
-                "Buy",
-                "buy",
-                "Sell",
-                "sell",
-                "swap",
-                "Swap",
-                "liquidity",
-                "Liquidity",
-                "pool",
-                "Pool",
-                "raydium",
-                "Raydium",
-                "orca",
-                "Orca",
-                "serum",
-                "Serum",
-                "market",
-                "Market",
-                "trade",
-                "Trade",
-                "Pump",
-                "pump",
-                "Pumpswap",
-                "pumpswap",
-                "pump.fun",
-                "Pump.fun",
-                "meteora",
-                "Meteora",
-            ];
-            // dex
-            for log in logs.clone().unwrap_or(vec![]) {
-                if dex_keywords.iter().any(|&keyword| log.contains(keyword)) {
-                    if (!info.is_swap) {
-                        info.is_swap = true;
-                    }
-                }
-            }
+            Self::detect_swap(info, logs.clone().unwrap_or(vec![]).as_slice());
             for log in logs.clone().unwrap_or(vec![]) {
                 // raydium
                 if log.contains(RAYDIUM_V4_POOL_PROGRAM_ID)
@@ -904,12 +1145,24 @@ impl TransactionInfo {
                 if log.contains(METEORA_DAMM_V2_PROGRAM_ID)
                     || log.contains(METEORA_DLMM_V2_PROGRAM_ID)
                     || log.contains(METEORA_POOL_PROGRAM_ID)
+                    || log.contains(METEORA_DYNAMIC_BOND_CURVE_PROGRAM_ID)
                 {
-                    info.dex_program_type = Some(DexProgramType::Meteora);
                     info.transaction_type = Some(TransactionType::Swap);
                     // pool
+                    for log in logs.clone().unwrap_or(vec![]) {
+                        if log.contains(METEORA_DYNAMIC_BOND_CURVE_PROGRAM_ID) {
+                            info.dex_program_type = Some(DexProgramType::MeteoraDbc);
+                            info.dex_program_id =
+                                Some(METEORA_DYNAMIC_BOND_CURVE_PROGRAM_ID.to_string());
+                            info.dex_pool_program_id =
+                                Some(METEORA_DYNAMIC_BOND_CURVE_PROGRAM_ID.to_string());
+                            info.dex_pool_program_name =
+                                Some("meteora-dynamic-bond-curve-pool".to_string());
+                        }
+                    }
                     for log in logs.clone().unwrap_or(vec![]) {
                         if log.contains(METEORA_DAMM_V2_PROGRAM_ID) {
+                            info.dex_program_type = Some(DexProgramType::MeteoraDammV2);
                             info.dex_program_id = Some(METEORA_DAMM_V2_PROGRAM_ID.to_string());
                             info.dex_pool_program_id = Some(METEORA_DAMM_V2_PROGRAM_ID.to_string());
                             info.dex_pool_program_name = Some("meteora-damm-v2-pool".to_string());
@@ -926,6 +1179,7 @@ impl TransactionInfo {
                     }
                     for log in logs.clone().unwrap_or(vec![]) {
                         if log.contains(METEORA_DLMM_V2_PROGRAM_ID) {
+                            info.dex_program_type = Some(DexProgramType::MeteoraDlmm);
                             info.dex_program_id = Some(METEORA_DLMM_V2_PROGRAM_ID.to_string());
                             info.dex_pool_program_id = Some(METEORA_DLMM_V2_PROGRAM_ID.to_string());
                             info.dex_pool_program_name = Some("meteora-dlmm-v2-pool".to_string());
@@ -934,6 +1188,7 @@ impl TransactionInfo {
                     }
                     for log in logs.clone().unwrap_or(vec![]) {
                         if log.contains(METEORA_POOL_PROGRAM_ID) {
+                            info.dex_program_type = Some(DexProgramType::MeteoraDammV1);
                             info.dex_program_id = Some(METEORA_POOL_PROGRAM_ID.to_string());
                             info.dex_pool_program_id = Some(METEORA_POOL_PROGRAM_ID.to_string());
                             info.dex_pool_program_name = Some("meteora-pool".to_string());
@@ -952,51 +1207,14 @@ impl TransactionInfo {
                 }
                 if log.contains(ORCA_WHIRLPOOLS_PROGRAM_ID) {
                     info.dex_program_type = Some(DexProgramType::Orca);
-                    for log in logs.clone().unwrap_or(vec![]) {
-                        if log.contains(ORCA_WHIRLPOOLS_PROGRAM_ID) {
-                            info.dex_program_id = Some(ORCA_WHIRLPOOLS_PROGRAM_ID.to_string());
-                            info.dex_pool_program_id = Some(ORCA_WHIRLPOOLS_PROGRAM_ID.to_string());
-                            info.dex_pool_program_name = Some("orca-whirl-pools".to_string());
-                            info.transaction_type = Some(TransactionType::Swap);
-                            for log in logs.clone().unwrap_or(vec![]) {
-                                if (log.contains("IncreaseLiquidity")) {
-                                    info.transaction_type = Some(TransactionType::AddLiquidity);
-                                }
-                                if (log.contains("DecreaseLiquidity")) {
-                                    info.transaction_type = Some(TransactionType::RemoveLiquidity);
-                                }
-                            }
-                        }
-                    }
+                    info.dex_program_id = Some(ORCA_WHIRLPOOLS_PROGRAM_ID.to_string());
+                    info.dex_pool_program_id = Some(ORCA_WHIRLPOOLS_PROGRAM_ID.to_string());
+                    info.dex_pool_program_name = Some("orca-whirl-pools".to_string());
+                    Self::classify_orca_transaction(info);
                     return;
                 }
             }
             // pump
-            let pump_keywords = [
-                "Buy",
-                "buy",
-                "Sell",
-                "sell",
-                "swap",
-                "Swap",
-                "liquidity",
-                "Liquidity",
-                "pool",
-                "Pool",
-                "Pump",
-                "pump",
-                "Pumpswap",
-                "pumpswap",
-                "pump.fun",
-                "Pump.fun",
-            ];
-            for log in logs.clone().unwrap_or(vec![]) {
-                if pump_keywords.iter().any(|&keyword| log.contains(keyword)) {
-                    if (!info.is_swap) {
-                        info.is_swap = true;
-                    }
-                }
-            }
             for log in logs.clone().unwrap_or(vec![]) {
                 if log.contains(PUMP_AAM_PROGRAM_ID) {
                     info.dex_program_id = Some(PUMP_AAM_PROGRAM_ID.to_string());
@@ -1038,6 +1256,115 @@ impl TransactionInfo {
         }
     }
 
+    /// classify a whirlpool transaction from the instructions the program logged itself
+    /// liquidity changes take precedence over opening/closing the position they happen in
+    fn classify_orca_transaction(info: &mut TransactionInfo) {
+        let names = Self::program_instruction_names(&info.logs, ORCA_WHIRLPOOLS_PROGRAM_ID);
+        let has = |prefix: &str| names.iter().any(|name| name.starts_with(prefix));
+        let transaction_type = if has("IncreaseLiquidity") {
+            TransactionType::AddLiquidity
+        } else if has("DecreaseLiquidity") {
+            TransactionType::RemoveLiquidity
+        } else if has("OpenPosition") {
+            TransactionType::OpenPosition
+        } else if has("ClosePosition") {
+            TransactionType::ClosePosition
+        } else {
+            TransactionType::Swap
+        };
+        if transaction_type == TransactionType::Swap
+            && let Some((input_mint, output_mint)) = Self::orca_swap_mints(info)
+        {
+            info.input_mint = Some(input_mint);
+            info.output_mint = Some(output_mint);
+        }
+        info.transaction_type = Some(transaction_type);
+    }
+
+    /// names of the instructions logged by `program_id` itself ("Program log: Instruction: <name>")
+    fn program_instruction_names(logs: &[String], program_id: &str) -> Vec<String> {
+        Self::program_logs(logs, program_id)
+            .filter_map(|log| log.strip_prefix("Program log: Instruction: "))
+            .map(str::to_string)
+            .collect()
+    }
+
+    /// decoded payloads of the `Program data:` logs (anchor events) emitted by `program_id` itself
+    pub(crate) fn program_data(logs: &[String], program_id: &str) -> Vec<Vec<u8>> {
+        use base64::Engine;
+        Self::program_logs(logs, program_id)
+            .filter_map(|log| log.strip_prefix("Program data: "))
+            .filter_map(|encoded| {
+                base64::engine::general_purpose::STANDARD
+                    .decode(encoded)
+                    .ok()
+            })
+            .collect()
+    }
+
+    /// the logs `program_id` wrote itself, i.e. while it was the innermost invoked program,
+    /// the invoke / success / failed lines of the runtime are left out
+    fn program_logs<'a>(logs: &'a [String], program_id: &str) -> impl Iterator<Item = &'a str> {
+        let mut invoke_stack: Vec<&str> = Vec::new();
+        logs.iter().filter_map(move |log| {
+            if let Some(rest) = log.strip_prefix("Program ") {
+                if let Some((invoked, _)) = rest.split_once(" invoke [") {
+                    invoke_stack.push(invoked);
+                    return None;
+                }
+                if rest.ends_with(" success") || rest.contains(" failed") {
+                    invoke_stack.pop();
+                    return None;
+                }
+            }
+            (invoke_stack.last() == Some(&program_id)).then_some(log.as_str())
+        })
+    }
+
+    /// (input mint, output mint) of a top-level whirlpool swap / swap_v2 instruction
+    /// routed swaps, where the whirlpool is one hop of an aggregator, are not decoded
+    fn orca_swap_mints(info: &TransactionInfo) -> Option<(String, String)> {
+        let mint_of = |account: &str| {
+            let index = info
+                .raw_account_keys
+                .iter()
+                .position(|key| key == account)?;
+            info.raw_pre_token_balances
+                .iter()
+                .chain(&info.raw_post_token_balances)
+                .find(|balance| balance.account_index as usize == index)
+                .map(|balance| balance.mint.clone())
+        };
+        for instruction in &info.instructions {
+            if instruction.program_id != ORCA_WHIRLPOOLS_PROGRAM_ID {
+                continue;
+            }
+            let Ok(data) = bs58::decode(&instruction.data).into_vec() else {
+                continue;
+            };
+            // discriminator, amount, other_amount_threshold, sqrt_price_limit, amount_specified_is_input, a_to_b
+            if data.len() < 42 {
+                continue;
+            }
+            let accounts = &instruction.accounts;
+            let (mint_a, mint_b) = if data[..8] == ORCA_SWAP_DISCRIMINATOR && accounts.len() > 6 {
+                // token_owner_account_a, token_vault_a, token_owner_account_b, token_vault_b at 3..=6
+                (mint_of(&accounts[4])?, mint_of(&accounts[6])?)
+            } else if data[..8] == ORCA_SWAP_V2_DISCRIMINATOR && accounts.len() > 6 {
+                // token_mint_a, token_mint_b at 5 and 6
+                (accounts[5].clone(), accounts[6].clone())
+            } else {
+                continue;
+            };
+            return Some(if data[41] != 0 {
+                (mint_a, mint_b)
+            } else {
+                (mint_b, mint_a)
+            });
+        }
+        None
+    }
+
     fn extract_transfer_info(
         info: &mut TransactionInfo,
         parsed_msg: &solana_transaction_status::UiParsedMessage,
@@ -1051,38 +1378,35 @@ impl TransactionInfo {
                             solana_transaction_status::UiParsedInstruction::Parsed(
                                 parsed_instruction,
                             ) => {
-                                if parsed_instruction.program_id == "system" {
-                                    if let serde_json::Value::Object(parsed_obj) =
+                                if parsed_instruction.program == "system"
+                                    && let serde_json::Value::Object(parsed_obj) =
                                         &parsed_instruction.parsed
-                                    {
-                                        if let Some(serde_json::Value::String(type_str)) =
-                                            parsed_obj.get("type")
-                                        {
-                                            if type_str == "transfer" {
-                                                Self::extract_parsed_transfer_info(
-                                                    info, parsed_obj,
-                                                );
-                                            }
-                                        }
-                                    }
+                                    && let Some(serde_json::Value::String(type_str)) =
+                                        parsed_obj.get("type")
+                                    && type_str == "transfer"
+                                {
+                                    Self::extract_parsed_transfer_info(info, parsed_obj);
                                 }
                             }
                             _ => {}
                         }
                     }
                     solana_transaction_status::UiInstruction::Compiled(compiled_inst) => {
-                        if compiled_inst.program_id_index == 0 {
-                            if let Some(transfer_info) =
-                                Self::extract_compiled_transfer_info(compiled_inst, parsed_msg)
-                            {
-                                info.transaction_type = Some(TransactionType::Transfer);
-                                info.program_id = "system".to_string();
-                                info.from = transfer_info.from;
-                                info.to = transfer_info.to;
-                                info.value = transfer_info.amount.to_string();
-                                info.value_sol =
-                                    transfer_info.amount as f64 / LAMPORTS_PER_SOL as f64;
-                            }
+                        let program_id = info
+                            .raw_account_keys
+                            .get(compiled_inst.program_id_index as usize);
+                        if program_id.map(String::as_str) == Some(SYSTEM_PROGRAM_ID)
+                            && let Some(transfer_info) = Self::extract_compiled_transfer_info(
+                                compiled_inst,
+                                &info.raw_account_keys,
+                            )
+                        {
+                            info.transaction_type = Some(TransactionType::Transfer);
+                            info.program_id = "system".to_string();
+                            info.from = transfer_info.from;
+                            info.to = transfer_info.to;
+                            info.value = transfer_info.amount.to_string();
+                            info.value_sol = lamports_to_sol(transfer_info.amount);
                         }
                     }
                 }
@@ -1106,68 +1430,44 @@ impl TransactionInfo {
                 info.from = from.to_string();
                 info.to = to.to_string();
                 info.value = lamports.to_string();
-                info.value_sol = lamports as f64 / LAMPORTS_PER_SOL as f64;
+                info.value_sol = lamports_to_sol(lamports);
             }
         }
     }
 
     // extract transfer information from compilation instructions
+    // account indices are resolved against the static keys followed by the lookup table keys
     fn extract_compiled_transfer_info(
         compiled_inst: &solana_transaction_status::UiCompiledInstruction,
-        parsed_msg: &solana_transaction_status::UiParsedMessage,
+        account_keys: &[String],
     ) -> Option<CompiledTransferInfo> {
         // System transfer command data format:
         // First 4 bytes: Command identifier (2 indicates transfer)
         // Last 8 bytes: Lamports count
-        let data = &compiled_inst.data;
-        if data.len() >= 12 {
-            let instruction_id = u32::from_le_bytes([
-                data.as_bytes()[0],
-                data.as_bytes()[1],
-                data.as_bytes()[2],
-                data.as_bytes()[3],
-            ]);
-            if instruction_id == 2 {
-                let lamports = u64::from_le_bytes([
-                    data.as_bytes()[4],
-                    data.as_bytes()[5],
-                    data.as_bytes()[6],
-                    data.as_bytes()[7],
-                    data.as_bytes()[8],
-                    data.as_bytes()[9],
-                    data.as_bytes()[10],
-                    data.as_bytes()[11],
-                ]);
-                if let (Some(from_index), Some(to_index)) =
-                    (compiled_inst.accounts.get(0), compiled_inst.accounts.get(1))
-                {
-                    if let account_keys = &parsed_msg.account_keys {
-                        if let (Some(from_acc), Some(to_acc)) = (
-                            account_keys.get(*from_index as usize),
-                            account_keys.get(*to_index as usize),
-                        ) {
-                            if let (from_pubkey, to_pubkey) = (&from_acc.pubkey, &to_acc.pubkey) {
-                                return Some(CompiledTransferInfo {
-                                    from: from_pubkey.clone(),
-                                    to: to_pubkey.clone(),
-                                    amount: lamports,
-                                });
-                            }
-                        }
-                    }
-                }
-            }
+        let data = bs58::decode(&compiled_inst.data).into_vec().ok()?;
+        if data.len() < 12 || u32::from_le_bytes(data[..4].try_into().ok()?) != 2 {
+            return None;
         }
-        None
+        let lamports = u64::from_le_bytes(data[4..12].try_into().ok()?);
+        let from = account_keys.get(*compiled_inst.accounts.first()? as usize)?;
+        let to = account_keys.get(*compiled_inst.accounts.get(1)? as usize)?;
+        Some(CompiledTransferInfo {
+            from: from.clone(),
+            to: to.clone(),
+            amount: lamports,
+        })
     }
 
+    /// program id of an instruction, compiled instructions are resolved against `account_keys`
     fn extract_program_id_from_ui_instruction(
         inst: &solana_transaction_status::UiInstruction,
+        account_keys: &[String],
     ) -> String {
         match inst {
-            solana_transaction_status::UiInstruction::Compiled(compiled) => {
-                compiled.program_id_index.to_string()
-            }
+            solana_transaction_status::UiInstruction::Compiled(compiled) => account_keys
+                .get(compiled.program_id_index as usize)
+                .cloned()
+                .unwrap_or_default(),
             solana_transaction_status::UiInstruction::Parsed(parsed) => match parsed {
                 UiParsedInstruction::Parsed(parsed_instruction) => {
                     parsed_instruction.program_id.clone()
@@ -1179,14 +1479,21 @@ impl TransactionInfo {
         }
     }
 
+    /// accounts of an instruction, compiled instructions are resolved against `account_keys`
     fn extract_accounts_from_ui_instruction(
         inst: &solana_transaction_status::UiInstruction,
+        account_keys: &[String],
     ) -> Vec<String> {
         match inst {
             solana_transaction_status::UiInstruction::Compiled(compiled) => compiled
                 .accounts
                 .iter()
-                .map(|idx| idx.to_string())
+                .map(|index| {
+                    account_keys
+                        .get(*index as usize)
+                        .cloned()
+                        .unwrap_or_default()
+                })
                 .collect(),
             solana_transaction_status::UiInstruction::Parsed(parsed) => match parsed {
                 solana_transaction_status::UiParsedInstruction::Parsed(parsed_instruction) => {
@@ -1305,6 +1612,7 @@ impl Default for TransactionInfo {
             version: 0,
             compute_units_consumed: None,
             compute_unit_price: None,
+            compute_unit_limit: None,
             log_index: 0,
             data: None,
             logs: Vec::new(),
@@ -1355,6 +1663,9 @@ impl Default for TransactionInfo {
             raw_pre_token_balances: Vec::new(),
             raw_post_token_balances: Vec::new(),
             raw_log_messages: Vec::new(),
+            // cache
+            extra_quotes: vec![],
+            net_token_changes_cache: NetTokenChangesCache::default(),
         }
     }
 }
@@ -1364,14 +1675,246 @@ impl TransactionInfo {
         self.status == "success"
     }
 
+    /// whether the transaction was executed with an error, all its changes except the fee were reverted
+    pub fn is_failed(&self) -> bool {
+        self.status == "failed"
+    }
+
+    /// seconds since the block time, None when the rpc reported no block time
+    /// a block time ahead of the local clock counts as 0
+    pub fn age_seconds(&self) -> Option<u64> {
+        Some(now_unix_secs().saturating_sub(self.block_time?.max(0) as u64))
+    }
+
+    /// block time as a utc date time, None when the rpc reported no block time
+    pub fn datetime_utc(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.block_time?, 0)
+    }
+
+    /// whether the transaction landed within the last `within`, false without a block time
+    pub fn is_recent(&self, within: std::time::Duration) -> bool {
+        self.age_seconds()
+            .is_some_and(|age| std::time::Duration::from_secs(age) <= within)
+    }
+
+    /// raw balance change of every token account in the transaction, unchanged accounts included
+    /// an account missing from the pre or post balances (created or closed) counts as 0 there
+    /// accounts are ordered by first appearance in the post balances, then the pre balances
+    pub fn token_account_changes(&self) -> Vec<TokenAccountChange> {
+        let mut changes: Vec<TokenAccountChange> = Vec::new();
+        let balances = [
+            (&self.raw_post_token_balances, 1),
+            (&self.raw_pre_token_balances, -1),
+        ];
+        for (balances, sign) in balances {
+            for balance in balances {
+                let amount = balance.ui_token_amount.amount.parse::<i128>().unwrap_or(0);
+                match changes
+                    .iter_mut()
+                    .find(|change| change.account_index == balance.account_index)
+                {
+                    Some(change) => change.delta += sign * amount,
+                    None => changes.push(TokenAccountChange {
+                        account_index: balance.account_index,
+                        owner: balance.owner.clone(),
+                        mint: balance.mint.clone(),
+                        decimals: balance.ui_token_amount.decimals,
+                        delta: sign * amount,
+                    }),
+                }
+            }
+        }
+        changes
+    }
+
+    /// token balance changes of every mint `owner` holds in the transaction, as
+    /// (mint, raw delta, decimals); positive means received, several token accounts
+    /// of the same mint are added up and unchanged mints are left out
+    /// mints are ordered by first appearance in the post balances, then the pre balances
+    pub fn balance_changes_for_owner(&self, owner: &str) -> Vec<(String, i128, u8)> {
+        let mut changes: Vec<(String, i128, u8)> = Vec::new();
+        for account in self.token_account_changes() {
+            if account.owner.as_deref() != Some(owner) {
+                continue;
+            }
+            match changes
+                .iter_mut()
+                .find(|(mint, _, _)| *mint == account.mint)
+            {
+                Some((_, delta, _)) => *delta += account.delta,
+                None => changes.push((account.mint, account.delta, account.decimals)),
+            }
+        }
+        changes.retain(|(_, delta, _)| *delta != 0);
+        changes
+    }
+
+    /// Determine whether the transaction only moves value between accounts of one owner,
+    /// e.g. tokens shuffled between two of its token accounts
+    /// Every token account whose balance changed has the same owner and the changes net to zero
+    /// per mint; SOL only goes to the owner and its token accounts (rent of new ones), so the
+    /// fee and that rent are the only cost
+    /// Returns false for failed transactions and transactions moving no tokens
+    pub fn is_self_transfer(&self) -> bool {
+        if self.is_failed() {
+            return false;
+        }
+        let mut changes = self.token_account_changes();
+        changes.retain(|change| change.delta != 0);
+        let Some(owner) = changes.first().and_then(|change| change.owner.as_deref()) else {
+            return false;
+        };
+        if changes
+            .iter()
+            .any(|change| change.owner.as_deref() != Some(owner))
+            || !self.balance_changes_for_owner(owner).is_empty()
+        {
+            return false;
+        }
+        let owner_accounts: Vec<usize> = self
+            .raw_pre_token_balances
+            .iter()
+            .chain(&self.raw_post_token_balances)
+            .filter(|balance| balance.owner.as_deref() == Some(owner))
+            .map(|balance| balance.account_index as usize)
+            .chain(self.raw_account_keys.iter().position(|key| key == owner))
+            .collect();
+        self.raw_pre_balances
+            .iter()
+            .zip(&self.raw_post_balances)
+            .enumerate()
+            .filter(|(_, (pre, post))| post > pre)
+            .all(|(index, _)| owner_accounts.contains(&index))
+    }
+
+    /// split the fee into the base fee (5000 lamports per signature) and the priority fee
+    /// the priority fee is compute unit price * requested limit, or the rest of the fee when no
+    /// limit was requested; it is None when the rpc did not report the consumed compute units
+    pub fn fee_breakdown(&self) -> FeeBreakdown {
+        let base_fee = LAMPORTS_PER_SIGNATURE * self.signers.len().max(1) as u64;
+        let priority_fee = match (self.compute_units_consumed, self.compute_unit_price) {
+            (None, _) => None,
+            (Some(_), None | Some(0)) => Some(0),
+            (Some(_), Some(price)) => Some(match self.compute_unit_limit {
+                Some(limit) => (price as u128 * limit as u128).div_ceil(1_000_000) as u64,
+                None => self.fee.saturating_sub(base_fee),
+            }),
+        };
+        FeeBreakdown {
+            base_fee,
+            priority_fee,
+            total: self.fee,
+        }
+    }
+
     pub fn is_token_transfer(&self) -> bool {
         self.token_mint.is_some()
     }
 
+    /// Deprecated: `balance_change` already includes the fee, use `net_sol_change_for`
     pub fn get_net_amount(&self) -> i64 {
         self.balance_change - self.fee as i64
     }
 
+    /// Get the economic SOL change of `owner` in lamports, without fees and rent
+    /// The change is the owner's post balance minus its pre balance, plus:
+    /// - the transaction fee, when `owner` is the fee payer
+    /// - the rent of the associated token accounts `owner` funded and that still hold it after the transaction
+    ///   (rent of an account created and closed in the same transaction came back already)
+    ///
+    /// Returns 0 when `owner` is not an account of the transaction
+    pub fn net_sol_change_for(&self, owner: &str) -> i64 {
+        let Some(index) = self.raw_account_keys.iter().position(|key| key == owner) else {
+            return 0;
+        };
+        let (Some(pre), Some(post)) = (
+            self.raw_pre_balances.get(index),
+            self.raw_post_balances.get(index),
+        ) else {
+            return 0;
+        };
+        let mut change = *post as i64 - *pre as i64;
+        if owner == self.fee_payer {
+            change += self.fee as i64;
+        }
+        change + self.token_account_rent_paid_by(owner) as i64
+    }
+
+    /// set the input and output mints and raw amounts of a swap from the fee payer's net changes
+    /// (see `fee_payer_swap_changes`): the input is the one mint it lost and the output the one
+    /// mint it gained
+    /// mints already read from the swap instruction are replaced; they are kept when the fee
+    /// payer lost or gained several mints, e.g. paid a tip in SOL, or none
+    fn resolve_swap_legs(&mut self) {
+        if !self.is_swap
+            || self.is_failed()
+            || !matches!(self.transaction_type, None | Some(TransactionType::Swap))
+        {
+            return;
+        }
+        let (inputs, outputs): (Vec<_>, Vec<_>) = self
+            .fee_payer_swap_changes()
+            .into_iter()
+            .partition(|(_, delta, _)| *delta < 0);
+        let ([(input_mint, input, _)], [(output_mint, output, _)]) =
+            (inputs.as_slice(), outputs.as_slice())
+        else {
+            return;
+        };
+        self.input_mint = Some(input_mint.clone());
+        self.input_amount = u64::try_from(input.unsigned_abs()).ok();
+        self.output_mint = Some(output_mint.clone());
+        self.output_amount = u64::try_from(output.unsigned_abs()).ok();
+    }
+
+    /// net changes of the fee payer as (mint, raw delta, decimals), native SOL counted as WSOL
+    /// so wrapping and unwrapping around a swap nets out; fees and token account rent are left
+    /// out and unchanged mints are dropped
+    pub(crate) fn fee_payer_swap_changes(&self) -> Vec<(String, i128, u8)> {
+        let mut changes = self.balance_changes_for_owner(&self.fee_payer);
+        let sol = self.net_sol_change_for(&self.fee_payer) as i128;
+        match changes.iter_mut().find(|(mint, _, _)| mint == WSOL) {
+            Some((_, delta, _)) => *delta += sol,
+            None => changes.push((WSOL.to_string(), sol, 9)),
+        }
+        changes.retain(|(_, delta, _)| *delta != 0);
+        changes
+    }
+
+    /// lamports still held by the associated token accounts `funder` created in the transaction
+    fn token_account_rent_paid_by(&self, funder: &str) -> u64 {
+        let created = self.created_token_accounts();
+        let mut counted: Vec<String> = Vec::new();
+        let mut rent = 0;
+        for instruction in self.instructions_for_program(ASSOCIATED_TOKEN_PROGRAM_ID) {
+            let funded_by = match instruction.program.as_str() {
+                // accounts: funder, ata, wallet, mint, system program, token program
+                "compiled" | "partially_decoded" => instruction.accounts.first().cloned(),
+                _ => serde_json::from_str::<Value>(&instruction.data)
+                    .ok()
+                    .and_then(|parsed| parsed["info"]["source"].as_str().map(str::to_string)),
+            };
+            let Some((ata, _, _)) = Self::associated_token_account_creation(instruction) else {
+                continue;
+            };
+            if funded_by.as_deref() != Some(funder)
+                || counted.contains(&ata)
+                || !created.iter().any(|(account, _, _)| *account == ata)
+            {
+                continue;
+            }
+            rent += self
+                .raw_account_keys
+                .iter()
+                .position(|key| *key == ata)
+                .and_then(|index| self.raw_post_balances.get(index))
+                .copied()
+                .unwrap_or(0);
+            counted.push(ata);
+        }
+        rent
+    }
+
     pub fn is_high_value(&self) -> bool {
         self.value_sol > 1000.0
     }
@@ -1385,10 +1928,10 @@ impl TransactionInfo {
                 return true;
             }
         }
-        if let Some(dex_program_id) = &self.dex_program_id {
-            if dex_program_id == PUMP_BOND_CURVE_PROGRAM_ID {
-                return true;
-            }
+        if let Some(dex_program_id) = &self.dex_program_id
+            && dex_program_id == PUMP_BOND_CURVE_PROGRAM_ID
+        {
+            return true;
         }
         for instruction in &self.instructions {
             if instruction.program_id == PUMP_BOND_CURVE_PROGRAM_ID {
@@ -1398,6 +1941,15 @@ impl TransactionInfo {
         false
     }
 
+    /// Check if the transaction graduates a pump.fun token: the bond curve's liquidity is
+    /// withdrawn (`Migrate`, or `Withdraw` for the older raydium migration) and a pool is created
+    pub fn is_bond_curve_migration(&self) -> bool {
+        let withdrawn = Self::program_instruction_names(&self.logs, PUMP_BOND_CURVE_PROGRAM_ID)
+            .iter()
+            .any(|name| name == "Migrate" || name == "Withdraw");
+        withdrawn && self.is_pool_creation()
+    }
+
     /// is meteora dbc trade
     pub fn is_meteora_dbc_trade(&self) -> bool {
         for log in &self.logs {
@@ -1405,10 +1957,10 @@ impl TransactionInfo {
                 return true;
             }
         }
-        if let Some(dex_program_id) = &self.dex_program_id {
-            if dex_program_id == METEORA_DYNAMIC_BOND_CURVE_PROGRAM_ID {
-                return true;
-            }
+        if let Some(dex_program_id) = &self.dex_program_id
+            && dex_program_id == METEORA_DYNAMIC_BOND_CURVE_PROGRAM_ID
+        {
+            return true;
         }
         for instruction in &self.instructions {
             if instruction.program_id == METEORA_DYNAMIC_BOND_CURVE_PROGRAM_ID {
@@ -1430,32 +1982,138 @@ impl TransactionInfo {
                 return true;
             }
         }
-        if let Some(dex_program_id) = &self.dex_program_id {
-            if dex_program_id == RAYDIUM_LAUNCHPAD_PROGRAM_ID {
-                return true;
-            }
+        if let Some(dex_program_id) = &self.dex_program_id
+            && dex_program_id == RAYDIUM_LAUNCHPAD_PROGRAM_ID
+        {
+            return true;
         }
         for instruction in &self.instructions {
             if instruction.program_id == RAYDIUM_LAUNCHPAD_PROGRAM_ID {
                 return true;
             }
         }
-        if let Some(dex_type) = &self.dex_program_type {
-            if *dex_type == DexProgramType::Raydium {
-                for log in &self.logs {
-                    if log.contains("launchpad")
-                        || log.contains("Launchpad")
-                        || log.contains("IDO")
-                        || log.contains("ido")
-                    {
-                        return true;
-                    }
+        if let Some(dex_type) = &self.dex_program_type
+            && *dex_type == DexProgramType::Raydium
+        {
+            for log in &self.logs {
+                if log.contains("launchpad")
+                    || log.contains("Launchpad")
+                    || log.contains("IDO")
+                    || log.contains("ido")
+                {
+                    return true;
                 }
             }
         }
         false
     }
 
+    /// Get the associated token accounts the transaction created, as (ata, mint, owner)
+    /// Detected from associated token program `Create` / `CreateIdempotent` instructions,
+    /// idempotent creations of an account that already existed are skipped
+    /// The fee payer of the instruction paid the account's rent, which shows up as a SOL decrease
+    pub fn created_token_accounts(&self) -> Vec<(String, String, String)> {
+        if self.is_failed() {
+            return vec![];
+        }
+        let mut created: Vec<(String, String, String)> = Vec::new();
+        for instruction in self.instructions_for_program(ASSOCIATED_TOKEN_PROGRAM_ID) {
+            let Some((ata, mint, owner)) = Self::associated_token_account_creation(instruction)
+            else {
+                continue;
+            };
+            let existed = self
+                .raw_account_keys
+                .iter()
+                .position(|key| *key == ata)
+                .and_then(|index| self.raw_pre_balances.get(index))
+                .is_some_and(|lamports| *lamports > 0);
+            if !existed && !created.iter().any(|(account, _, _)| *account == ata) {
+                created.push((ata, mint, owner));
+            }
+        }
+        created
+    }
+
+    /// (ata, mint, owner) of an associated token program `Create` or `CreateIdempotent`
+    fn associated_token_account_creation(
+        instruction: &InstructionInfo,
+    ) -> Option<(String, String, String)> {
+        match instruction.program.as_str() {
+            "compiled" | "partially_decoded" => {
+                // no data or 0 is Create, 1 is CreateIdempotent, 2 is RecoverNested
+                let data = bs58::decode(&instruction.data).into_vec().ok()?;
+                if data.first().is_some_and(|tag| *tag > 1) {
+                    return None;
+                }
+                // accounts: funder, ata, wallet, mint, system program, token program
+                let account = |index: usize| instruction.accounts.get(index).cloned();
+                Some((account(1)?, account(3)?, account(2)?))
+            }
+            _ => {
+                let parsed: Value = serde_json::from_str(&instruction.data).ok()?;
+                if parsed["type"] != "create" && parsed["type"] != "createIdempotent" {
+                    return None;
+                }
+                let field = |name: &str| parsed["info"][name].as_str().map(str::to_string);
+                Some((field("account")?, field("mint")?, field("wallet")?))
+            }
+        }
+    }
+
+    /// Get every instruction, top-level and inner, that invoked `program_id`, in execution order
+    pub fn instructions_for_program(&self, program_id: &str) -> Vec<&InstructionInfo> {
+        let mut matched = Vec::new();
+        for (index, instruction) in self.instructions.iter().enumerate() {
+            if instruction.program_id == program_id {
+                matched.push(instruction);
+            }
+            for inner in self
+                .inner_instructions
+                .iter()
+                .filter(|inner| inner.index as usize == index)
+            {
+                matched.extend(
+                    inner
+                        .instructions
+                        .iter()
+                        .filter(|instruction| instruction.program_id == program_id),
+                );
+            }
+        }
+        matched
+    }
+
+    /// Get the cpi tree of the transaction, one root per top-level instruction
+    /// Nesting comes from the inner instructions' stack heights; transactions recorded
+    /// without stack heights hang every inner instruction directly under its top-level instruction
+    pub fn cpi_tree(&self) -> Vec<CpiNode> {
+        fn children(instructions: &[InstructionInfo], parent: Option<usize>) -> Vec<CpiNode> {
+            instructions
+                .iter()
+                .enumerate()
+                .filter(|(_, instruction)| instruction.parent_index == parent)
+                .map(|(index, instruction)| CpiNode {
+                    instruction: instruction.clone(),
+                    children: children(instructions, Some(index)),
+                })
+                .collect()
+        }
+        self.instructions
+            .iter()
+            .enumerate()
+            .map(|(index, instruction)| CpiNode {
+                instruction: instruction.clone(),
+                children: self
+                    .inner_instructions
+                    .iter()
+                    .find(|inner| inner.index as usize == index)
+                    .map(|inner| children(&inner.instructions, None))
+                    .unwrap_or_default(),
+            })
+            .collect()
+    }
+
     // is vote program
     pub fn is_vote_program(&self) -> bool {
         use crate::global::VOTE_PROGRAM_ID;
@@ -1556,6 +2214,25 @@ pub enum TransactionStatus {
     Pending,
 }
 
+/// anchor discriminator of the whirlpool `swap` instruction
+const ORCA_SWAP_DISCRIMINATOR: [u8; 8] = [248, 198, 158, 145, 225, 117, 135, 200];
+/// anchor discriminator of the whirlpool `swap_v2` instruction
+const ORCA_SWAP_V2_DISCRIMINATOR: [u8; 8] = [43, 4, 237, 11, 26, 201, 30, 98];
+
+/// lamports charged per transaction signature
+const LAMPORTS_PER_SIGNATURE: u64 = 5000;
+/// confidence of a swap only detected by keywords in the logs
+const KEYWORD_SWAP_CONFIDENCE: f64 = 0.5;
+
+/// fee of a transaction split into its base and priority parts, in lamports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    pub base_fee: u64,
+    /// None when the priority fee cannot be determined
+    pub priority_fee: Option<u64>,
+    pub total: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstructionInfo {
     pub program_id: String,
@@ -1563,6 +2240,17 @@ pub struct InstructionInfo {
     pub data: String,
     pub stack_height: Option<u32>,
     pub program: String,
+    /// position of the invoking instruction in the same inner instruction list,
+    /// None for top-level instructions and the ones the top-level instruction invoked directly
+    #[serde(default)]
+    pub parent_index: Option<usize>,
+}
+
+/// an instruction and the instructions it invoked through cpi
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CpiNode {
+    pub instruction: InstructionInfo,
+    pub children: Vec<CpiNode>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1592,3 +2280,719 @@ struct CompiledTransferInfo {
     to: String,
     amount: u64,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{load_fixture, load_transaction_info};
+
+    #[test]
+    fn test_balance_changes_for_owner() {
+        let info = load_transaction_info("sol_payment_recent");
+        let mint = "HApAAsiiTtwo6d1dgWRfRac5uRgJP3LjTVNDaLTAuaGf".to_string();
+        assert_eq!(
+            info.balance_changes_for_owner("Ao7YT34edAyDLWfWvzBdDDNksLzooC2hWzPK33hZfxwE"),
+            vec![(mint.clone(), -250_000_000, 6)]
+        );
+        assert_eq!(
+            info.balance_changes_for_owner("3Fav7BV2tWLjwB9kBHnbmb6ibehugwuvimSMCUgwyNbR"),
+            vec![(mint, 250_000_000, 6)]
+        );
+        assert!(
+            info.balance_changes_for_owner("So11111111111111111111111111111111111111112")
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_block_time_helpers() {
+        use std::time::Duration;
+        let mut info = load_transaction_info("raydium_v4_swap_buy");
+        assert_eq!(
+            info.datetime_utc().unwrap().to_rfc3339(),
+            "2025-10-17T00:03:20+00:00"
+        );
+        assert!(info.age_seconds().unwrap() > 3600);
+        assert!(!info.is_recent(Duration::from_secs(3600)));
+        let now = now_unix_secs() as i64;
+        info.block_time = Some(now - 10);
+        assert!(info.age_seconds().unwrap() < 60);
+        assert!(info.is_recent(Duration::from_secs(60)));
+        info.block_time = Some(now + 100);
+        assert_eq!(info.age_seconds(), Some(0));
+        info.block_time = None;
+        assert_eq!(info.age_seconds(), None);
+        assert_eq!(info.datetime_utc(), None);
+        assert!(!info.is_recent(Duration::from_secs(60)));
+    }
+
+    #[test]
+    fn test_from_raw_transaction() {
+        use solana_sdk::{
+            hash::Hash,
+            instruction::{AccountMeta, Instruction},
+            message::{Message, VersionedMessage, v0},
+            pubkey::Pubkey,
+            signature::Signature,
+        };
+        let payer = Pubkey::new_unique();
+        let recipient = Pubkey::new_unique();
+        let system_program = Pubkey::from_str_const("11111111111111111111111111111111");
+        let mut transfer_data = vec![2, 0, 0, 0];
+        transfer_data.extend_from_slice(&1_500_000u64.to_le_bytes());
+        let instructions = [
+            Instruction::new_with_bytes(
+                Pubkey::from_str_const(crate::global::COMPUTE_BUDGET_PROGRAM_ID),
+                &[2, 0x40, 0x0d, 0x03, 0x00],
+                vec![],
+            ),
+            Instruction::new_with_bytes(
+                system_program,
+                &transfer_data,
+                vec![
+                    AccountMeta::new(payer, true),
+                    AccountMeta::new(recipient, false),
+                ],
+            ),
+        ];
+        let messages = [
+            VersionedMessage::Legacy(Message::new(&instructions, Some(&payer))),
+            VersionedMessage::V0(
+                v0::Message::try_compile(&payer, &instructions, &[], Hash::new_unique()).unwrap(),
+            ),
+        ];
+        for message in messages {
+            let tx = VersionedTransaction {
+                signatures: vec![Signature::from([7; 64])],
+                message,
+            };
+            let info =
+                TransactionInfo::from_raw_transaction(&bincode::serialize(&tx).unwrap()).unwrap();
+            assert_eq!(info.signature, Signature::from([7; 64]).to_string());
+            assert_eq!(info.fee_payer, payer.to_string());
+            assert_eq!(info.signers, vec![payer.to_string()]);
+            assert_eq!(info.instructions.len(), 2);
+            assert_eq!(info.instructions[1].program_id, system_program.to_string());
+            assert_eq!(info.compute_unit_limit, Some(200_000));
+            assert_eq!(
+                info.instructions[1].accounts,
+                vec![payer.to_string(), recipient.to_string()]
+            );
+            assert!(!info.is_failed());
+            assert!(info.raw_pre_balances.is_empty());
+        }
+        assert!(TransactionInfo::from_raw_transaction(&[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn test_from_encoded_transaction_raydium_swap() {
+        let info = load_transaction_info("raydium_v4_swap_buy");
+        assert!(info.is_successful());
+        assert!(info.is_swap());
+        assert_eq!(info.slot, 372_120_001);
+        assert_eq!(info.block_time, Some(1_760_659_400));
+        assert_eq!(info.fee, 55_000);
+        assert_eq!(info.version, 0);
+        assert_eq!(info.compute_units_consumed, Some(38_120));
+        assert_eq!(info.transaction_type, Some(TransactionType::Swap));
+        assert_eq!(info.dex_program_type, Some(DexProgramType::Raydium));
+        assert_eq!(
+            info.dex_program_id.as_deref(),
+            Some(RAYDIUM_V4_POOL_PROGRAM_ID)
+        );
+        assert_eq!(info.signer, info.raw_account_keys[0]);
+        assert_eq!(info.raw_pre_token_balances.len(), 4);
+        assert_eq!(info.raw_post_token_balances.len(), 4);
+    }
+
+    #[test]
+    fn test_from_encoded_transaction_pump_buy() {
+        let info = load_transaction_info("pump_bond_curve_buy");
+        assert!(info.is_swap());
+        assert!(info.is_pump_bond_curve_trade());
+        assert_eq!(info.dex_program_type, Some(DexProgramType::PumpBondCurve));
+        assert_eq!(info.transaction_type, Some(TransactionType::Swap));
+        assert!(!info.is_raydium_launchpad_trade());
+        assert!(!info.is_meteora_dbc_trade());
+    }
+
+    #[test]
+    fn test_fee_breakdown() {
+        let info = load_transaction_info("compute_budget_transfer");
+        assert_eq!(info.compute_unit_limit, Some(50_000));
+        assert_eq!(info.compute_unit_price, Some(2_000_000));
+        let breakdown = info.fee_breakdown();
+        assert_eq!(
+            breakdown,
+            FeeBreakdown {
+                base_fee: 5_000,
+                priority_fee: Some(100_000),
+                total: 105_000
+            }
+        );
+        assert_eq!(info.priority_fee, Some(100_000));
+        // unknown consumed compute units only leaves the base fee
+        let mut info = info;
+        info.compute_units_consumed = None;
+        assert_eq!(info.fee_breakdown().priority_fee, None);
+        assert_eq!(info.fee_breakdown().base_fee, 5_000);
+        // a zero compute unit price pays no priority fee
+        let info = load_transaction_info("raydium_v4_swap_buy");
+        assert_eq!(info.compute_unit_price, Some(0));
+        assert_eq!(info.fee_breakdown().priority_fee, Some(0));
+    }
+
+    #[test]
+    fn test_orca_whirlpool_swap() {
+        let info = load_transaction_info("orca_whirlpool_swap");
+        assert!(info.is_swap());
+        assert_eq!(info.dex_program_type, Some(DexProgramType::Orca));
+        assert_eq!(info.transaction_type, Some(TransactionType::Swap));
+        // b -> a: USDC in, X out
+        assert_eq!(
+            info.input_mint.as_deref(),
+            Some("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v")
+        );
+        assert_eq!(
+            info.output_mint.as_deref(),
+            Some("BAfC2uU9rA9ZMXanMS4Qc8Z7u4sHVzGbZFjgD6dYrLoy")
+        );
+        assert_eq!(info.instructions.len(), 2);
+        assert_eq!(info.instructions[1].program_id, ORCA_WHIRLPOOLS_PROGRAM_ID);
+    }
+
+    #[test]
+    fn test_orca_whirlpool_increase_liquidity() {
+        let info = load_transaction_info("orca_whirlpool_increase_liquidity");
+        assert_eq!(info.dex_program_type, Some(DexProgramType::Orca));
+        // the position is opened and funded in the same transaction
+        assert_eq!(info.transaction_type, Some(TransactionType::AddLiquidity));
+        assert_eq!(info.input_mint, None);
+        assert_eq!(info.is_single_sided_liquidity(), Some(true));
+        // the token program's own instructions are not attributed to the whirlpool
+        assert_eq!(
+            TransactionInfo::program_instruction_names(&info.logs, ORCA_WHIRLPOOLS_PROGRAM_ID),
+            vec!["OpenPosition", "IncreaseLiquidity"]
+        );
+    }
+
+    #[test]
+    fn test_meteora_program_types() {
+        let cases = [
+            (
+                "meteora_dlmm_swap",
+                DexProgramType::MeteoraDlmm,
+                METEORA_DLMM_V2_PROGRAM_ID,
+                "meteora-dlmm-v2-pool",
+            ),
+            (
+                "meteora_damm_v1_swap",
+                DexProgramType::MeteoraDammV1,
+                METEORA_POOL_PROGRAM_ID,
+                "meteora-pool",
+            ),
+            (
+                "meteora_damm_v2_swap",
+                DexProgramType::MeteoraDammV2,
+                METEORA_DAMM_V2_PROGRAM_ID,
+                "meteora-damm-v2-pool",
+            ),
+            (
+                "meteora_dbc_swap",
+                DexProgramType::MeteoraDbc,
+                METEORA_DYNAMIC_BOND_CURVE_PROGRAM_ID,
+                "meteora-dynamic-bond-curve-pool",
+            ),
+        ];
+        for (fixture, dex_program_type, program_id, pool_name) in cases {
+            let info = load_transaction_info(fixture);
+            assert_eq!(info.dex_program_type, Some(dex_program_type), "{}", fixture);
+            assert!(dex_program_type.is_meteora());
+            assert_eq!(info.dex_program_id.as_deref(), Some(program_id));
+            assert_eq!(info.dex_pool_program_id.as_deref(), Some(program_id));
+            assert_eq!(info.dex_pool_program_name.as_deref(), Some(pool_name));
+            assert_eq!(info.transaction_type, Some(TransactionType::Swap));
+        }
+        assert!(!DexProgramType::Orca.is_meteora());
+    }
+
+    #[test]
+    fn test_pump_bond_curve_migration() {
+        let info = load_transaction_info("pump_bond_curve_migration");
+        assert!(info.is_bond_curve_migration());
+        assert_eq!(info.transaction_type, Some(TransactionType::Migration));
+        assert_eq!(
+            info.get_pool_creation_program_id().as_deref(),
+            Some(PUMP_AAM_PROGRAM_ID)
+        );
+        // a trade on the curve and a pool created outside pump.fun are not migrations
+        let buy = load_transaction_info("pump_bond_curve_buy");
+        assert!(!buy.is_bond_curve_migration());
+        assert_eq!(buy.transaction_type, Some(TransactionType::Swap));
+        let pool_create = load_transaction_info("raydium_v4_pool_create");
+        assert!(!pool_create.is_bond_curve_migration());
+        assert_eq!(
+            pool_create.transaction_type,
+            Some(TransactionType::PoolCreate)
+        );
+    }
+
+    #[test]
+    fn test_token_balances_paired_by_account_index() {
+        let info = load_transaction_info("token_transfer_reordered_balances");
+        let payer = "7wMGttEwvNh5Kt7EXMbumyspEQHo5Hy7ehy76UXT2b1U";
+        let recipient = "7r7S7kAg8oTQh8kBHaFg8fzxydi5xAKZeqH9nWAruuKq";
+        let mint = "H5hdaY3USVvMpdnxFA9Q52ev9PS9z5PNA7qctjAKp74y";
+        // the changed accounts are appended after the balances returned by the rpc
+        let changes: Vec<(u8, &str, &str, &str)> = info.pre_token_balances[2..]
+            .iter()
+            .zip(&info.post_token_balances[3..])
+            .map(|(pre, post)| {
+                assert_eq!(pre.account_index, post.account_index);
+                (
+                    pre.account_index,
+                    post.owner.as_str(),
+                    pre.ui_token_amount.amount.as_str(),
+                    post.ui_token_amount.amount.as_str(),
+                )
+            })
+            .collect();
+        // the payer's second account is unchanged and the created account starts empty
+        assert_eq!(
+            changes,
+            vec![
+                (1, payer, "250000000", "150000000"),
+                (2, recipient, "0", "100000000")
+            ]
+        );
+        assert_eq!(info.get_max_amount_for_mint(mint), Some(100_000_000));
+    }
+
+    #[test]
+    fn test_max_amount_uses_mint_decimals() {
+        let mint = "B2Fq8Yxo3V7uQ5Lq3CkzC8XG4GFBL2j9P5wVWDT9zKQ4";
+        let balance = |amount: &str| TokenBalance {
+            account_index: 1,
+            mint: mint.to_string(),
+            owner: "FjELqXm4oWTtbEJcLn6Lam4p8vM1d3Aq4pB9a8QfEeYb".to_string(),
+            ui_token_amount: UiTokenAmount {
+                ui_amount: None,
+                decimals: 9,
+                amount: amount.to_string(),
+                ui_amount_string: None,
+            },
+        };
+        let mut info = TransactionInfo {
+            logs: vec![format!("Program log: bought 1.5 {}", mint)],
+            pre_token_balances: vec![balance("0")],
+            post_token_balances: vec![balance("0")],
+            ..Default::default()
+        };
+        assert_eq!(info.get_max_amount_for_mint(mint), Some(1_500_000_000));
+        // without a balance entry the decimals are unknown and 6 is assumed
+        info.pre_token_balances.clear();
+        info.post_token_balances.clear();
+        assert_eq!(info.get_max_amount_for_mint(mint), Some(1_500_000));
+    }
+
+    #[test]
+    fn test_swap_detection_confidence() {
+        // a known dex program marks the swap with full confidence
+        let info = load_transaction_info("raydium_v4_swap_buy");
+        assert!(info.is_swap());
+        assert_eq!(info.confidence, 1.0);
+        let info = load_transaction_info("token_payment");
+        assert!(!info.is_swap());
+        assert_eq!(info.confidence, 1.0);
+        // an unknown program only logging a trade keyword is a low confidence swap
+        let mut tx = serde_json::to_value(load_fixture("token_payment")).unwrap();
+        tx["meta"]["logMessages"][1] = "Program log: Instruction: Swap".into();
+        let tx = serde_json::from_value(tx).unwrap();
+        let info = TransactionInfo::from_encoded_transaction(&tx, "signature");
+        assert!(info.is_swap());
+        assert_eq!(info.confidence, KEYWORD_SWAP_CONFIDENCE);
+    }
+
+    #[test]
+    fn test_created_token_accounts() {
+        // CreateIdempotent of the output token account before a raydium swap
+        let info = load_transaction_info("raydium_v4_swap_buy_new_ata");
+        assert_eq!(
+            info.created_token_accounts(),
+            vec![(
+                "Az8187BRfg6TmxyVHLjVNc7rDzwTfc5inouuWyBoNySP".to_string(),
+                "B2FqYRbPsFmrNNbW5bXgmnfmmQD57vD2MzyA1Yv6v6Ek".to_string(),
+                "FjELXLGGTNQEZhazTK2k5GHT6Hee1c4ZSHezU7gkc4os".to_string()
+            )]
+        );
+        // Create, paid by the sender for the recipient
+        let info = load_transaction_info("token_transfer_reordered_balances");
+        assert_eq!(
+            info.created_token_accounts(),
+            vec![(
+                "39QyECUEMGgPTFAwTA7VxiSugGopguV5bpqVsNqG6HyW".to_string(),
+                "H5hdaY3USVvMpdnxFA9Q52ev9PS9z5PNA7qctjAKp74y".to_string(),
+                "7r7S7kAg8oTQh8kBHaFg8fzxydi5xAKZeqH9nWAruuKq".to_string()
+            )]
+        );
+        assert!(
+            load_transaction_info("raydium_v4_swap_buy")
+                .created_token_accounts()
+                .is_empty()
+        );
+        // an idempotent creation of an account that already held rent creates nothing
+        let mut tx = serde_json::to_value(load_fixture("raydium_v4_swap_buy_new_ata")).unwrap();
+        tx["meta"]["preBalances"][5] = 2_039_280.into();
+        let tx = serde_json::from_value(tx).unwrap();
+        let info = TransactionInfo::from_encoded_transaction(&tx, "signature");
+        assert!(info.created_token_accounts().is_empty());
+    }
+
+    #[test]
+    fn test_token_account_changes() {
+        // the recipient's account is created, so it only has a post balance
+        let info = load_transaction_info("token_transfer_reordered_balances");
+        let mint = "H5hdaY3USVvMpdnxFA9Q52ev9PS9z5PNA7qctjAKp74y";
+        let change = |account_index: u8, owner: &str, delta: i128| TokenAccountChange {
+            account_index,
+            owner: Some(owner.to_string()),
+            mint: mint.to_string(),
+            decimals: 6,
+            delta,
+        };
+        assert_eq!(
+            info.token_account_changes(),
+            vec![
+                change(
+                    2,
+                    "7r7S7kAg8oTQh8kBHaFg8fzxydi5xAKZeqH9nWAruuKq",
+                    100_000_000
+                ),
+                change(3, "7wMGttEwvNh5Kt7EXMbumyspEQHo5Hy7ehy76UXT2b1U", 0),
+                change(
+                    1,
+                    "7wMGttEwvNh5Kt7EXMbumyspEQHo5Hy7ehy76UXT2b1U",
+                    -100_000_000
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_net_sol_change_for() {
+        // the signer paid 55000 lamports of fees and 2039280 of rent for the new token ata,
+        // the SOL it swapped went through its WSOL account
+        let info = load_transaction_info("raydium_v4_swap_buy_new_ata");
+        let signer = info.signer.clone();
+        assert_eq!(info.balance_change, -2_094_280);
+        assert_eq!(info.net_sol_change_for(&signer), 0);
+        assert_eq!(
+            info.net_sol_change_for("7axiZ3KRZwrCryiDVLPFBKMpVwRaPzJHAs3Fq3j1rHQ4"),
+            2_000_000_000
+        );
+        assert_eq!(
+            info.net_sol_change_for("DFADmoGd82NFGhqup22ipgbKTfDitXBNyrDxiX4eEX9x"),
+            -2_000_000_000
+        );
+        assert_eq!(info.net_sol_change_for("unknown"), 0);
+        // a plain transfer is the amount sent, without the fee
+        let info = load_transaction_info("sol_transfer");
+        assert_eq!(
+            info.net_sol_change_for(&info.fee_payer.clone()),
+            info.balance_change + info.fee as i64
+        );
+    }
+
+    #[test]
+    fn test_is_self_transfer() {
+        // the owner moves 100 tokens from one of its token accounts into a new one it creates
+        let mut tx =
+            serde_json::to_value(load_fixture("token_transfer_reordered_balances")).unwrap();
+        tx["meta"]["postTokenBalances"][0]["owner"] =
+            "7wMGttEwvNh5Kt7EXMbumyspEQHo5Hy7ehy76UXT2b1U".into();
+        let tx = serde_json::from_value(tx).unwrap();
+        let info = TransactionInfo::from_encoded_transaction(&tx, "signature");
+        assert!(info.is_self_transfer());
+        // the same transfer to another owner
+        assert!(!load_transaction_info("token_transfer_reordered_balances").is_self_transfer());
+        // swaps and plain SOL transfers are not
+        assert!(!load_transaction_info("raydium_v4_swap_buy").is_self_transfer());
+        assert!(!load_transaction_info("sol_transfer").is_self_transfer());
+    }
+
+    #[test]
+    fn test_swap_legs_are_resolved() {
+        let legs = |info: &TransactionInfo| {
+            (
+                info.input_mint.clone().unwrap(),
+                info.input_amount.unwrap(),
+                info.output_mint.clone().unwrap(),
+                info.output_amount.unwrap(),
+            )
+        };
+        // SOL wrapped into a temporary WSOL account, swapped and the account closed
+        let info = load_transaction_info("raydium_v4_swap_buy_wrapped_wsol");
+        assert_eq!(
+            legs(&info),
+            (
+                WSOL.to_string(),
+                2_000_000_000,
+                "HxFaXBQ8ggSrqUGXieCGiSZBwJsghZTKgPgDtnf21sik".to_string(),
+                1_000_000_000_000
+            )
+        );
+        // a sell for SOL
+        let info = load_transaction_info("pump_amm_sell");
+        assert_eq!(
+            legs(&info),
+            (
+                "9eU21qY11yiohyYTuWWfnP2Zgi7RZqMXgc1g1yMmLCgM".to_string(),
+                1_000_000_000_000,
+                WSOL.to_string(),
+                29_925_000
+            )
+        );
+        // a route is its first input and last output
+        let info = load_transaction_info("jupiter_route_three_hops");
+        assert_eq!(
+            legs(&info),
+            (
+                "FrinYNv6U7pKFVetWjtgyvaKtkewrK7nozJGreJh6Vkx".to_string(),
+                10_000_000_000,
+                "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+                120_900_000
+            )
+        );
+        // the fee payer also sends 0.5 SOL away, so it lost two mints and the
+        // mints read from the whirlpool instruction are kept
+        let info = load_transaction_info("orca_whirlpool_swap");
+        assert_eq!(info.input_amount, Some(150_000_000));
+        let mut tx = serde_json::to_value(load_fixture("orca_whirlpool_swap")).unwrap();
+        tx["meta"]["postBalances"][0] = 1_499_975_000u64.into();
+        let tx = serde_json::from_value(tx).unwrap();
+        let info = TransactionInfo::from_encoded_transaction(&tx, "signature");
+        assert_eq!(
+            info.input_mint.as_deref(),
+            Some("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v")
+        );
+        assert_eq!(
+            info.output_mint.as_deref(),
+            Some("BAfC2uU9rA9ZMXanMS4Qc8Z7u4sHVzGbZFjgD6dYrLoy")
+        );
+        assert_eq!(info.input_amount, None);
+        // failed swaps and transfers have no legs
+        let info = load_transaction_info("raydium_v4_swap_failed");
+        assert_eq!((info.input_amount, info.output_amount), (None, None));
+        let info = load_transaction_info("sol_transfer");
+        assert_eq!((info.input_mint, info.output_mint), (None, None));
+    }
+
+    #[test]
+    fn test_cpi_tree() {
+        use crate::global::SPL_TOKEN_PROGRAM_V1;
+        const JUPITER_V6_PROGRAM_ID: &str = "JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4";
+        let info = load_transaction_info("jupiter_route_sell");
+        let inner = &info.inner_instructions[0];
+        assert_eq!(inner.index, 1);
+        assert_eq!(inner.instructions[0].program_id, ORCA_WHIRLPOOLS_PROGRAM_ID);
+        assert_eq!(inner.instructions[0].stack_height, Some(2));
+        assert_eq!(
+            inner
+                .instructions
+                .iter()
+                .map(|i| i.parent_index)
+                .collect::<Vec<_>>(),
+            vec![None, Some(0), Some(0), None, None, Some(4), Some(4), None]
+        );
+        let tree = info.cpi_tree();
+        assert_eq!(tree.len(), 2);
+        assert!(tree[0].children.is_empty());
+        let route = &tree[1];
+        assert_eq!(route.instruction.program_id, JUPITER_V6_PROGRAM_ID);
+        let hops: Vec<(&str, usize)> = route
+            .children
+            .iter()
+            .map(|node| (node.instruction.program_id.as_str(), node.children.len()))
+            .collect();
+        assert_eq!(
+            hops,
+            vec![
+                (ORCA_WHIRLPOOLS_PROGRAM_ID, 2),
+                (JUPITER_V6_PROGRAM_ID, 0),
+                (RAYDIUM_CLMM_POOL_PROGRAM_ID, 2),
+                (JUPITER_V6_PROGRAM_ID, 0),
+            ]
+        );
+        assert_eq!(
+            route.children[2].children[0].instruction.program_id,
+            SPL_TOKEN_PROGRAM_V1
+        );
+    }
+
+    #[test]
+    fn test_instructions_for_program() {
+        use crate::global::SPL_TOKEN_PROGRAM_V1;
+        let info = load_transaction_info("jupiter_route_sell");
+        let jupiter = info.instructions_for_program("JUP6LkbZbjS1jKKwapdHNy74zcZ3tLUZoi5QNyVTaV4");
+        assert_eq!(
+            jupiter.iter().map(|i| i.stack_height).collect::<Vec<_>>(),
+            vec![None, Some(2), Some(2)]
+        );
+        assert_eq!(info.instructions_for_program(SPL_TOKEN_PROGRAM_V1).len(), 4);
+        assert!(
+            info.instructions_for_program(crate::global::VOTE_PROGRAM_ID)
+                .is_empty()
+        );
+        // a compiled instruction inside a parsed message resolves its program id
+        let mut json = serde_json::to_value(load_fixture("sol_payment_recent")).unwrap();
+        json["transaction"]["message"]["instructions"][2] = serde_json::json!({
+            "programIdIndex": 6,
+            "accounts": [2, 0, 0],
+            "data": "A",
+            "stackHeight": null
+        });
+        let tx: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_value(json).unwrap();
+        let info = TransactionInfo::from_encoded_transaction(&tx, "signature");
+        let token = info.instructions_for_program(SPL_TOKEN_PROGRAM_V1);
+        assert_eq!(token.len(), 2);
+        assert_eq!(token[1].program, "compiled");
+        assert_eq!(info.instructions[2].program_id, SPL_TOKEN_PROGRAM_V1);
+    }
+
+    #[test]
+    fn test_compiled_instructions_resolve_account_keys() {
+        let mut json = serde_json::to_value(load_fixture("sol_payment_recent")).unwrap();
+        let message = &mut json["transaction"]["message"];
+        message["accountKeys"]
+            .as_array_mut()
+            .unwrap()
+            .push(serde_json::json!({
+                "pubkey": PUMP_BOND_CURVE_PROGRAM_ID,
+                "writable": false,
+                "signer": false,
+                "source": "transaction"
+            }));
+        message["instructions"][2] = serde_json::json!({
+            "programIdIndex": 7,
+            "accounts": [0, 3],
+            "data": "A",
+            "stackHeight": null
+        });
+        json["meta"]["logMessages"] = serde_json::json!([]);
+        let tx: EncodedConfirmedTransactionWithStatusMeta = serde_json::from_value(json).unwrap();
+        let info = TransactionInfo::from_encoded_transaction(&tx, "signature");
+        // detected from the instruction alone, the logs are empty
+        assert!(info.is_pump_bond_curve_trade());
+        assert_eq!(
+            info.instructions[2].accounts,
+            vec![
+                "Ao7YT34edAyDLWfWvzBdDDNksLzooC2hWzPK33hZfxwE",
+                "69bG5jU6aY5jrPQHCWE7kgb2ruj1d7A2m4DHJnANSF11"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_involved_accounts_are_complete_and_unique() {
+        // static keys first, then the lookup table keys
+        let info = load_transaction_info("raydium_clmm_swap_lut");
+        assert_eq!(info.involved_accounts, info.raw_account_keys);
+        assert_eq!(
+            info.involved_accounts[0],
+            "EAh1u1Mn6cMawzUP2G6ec45XcQ5jj4mGrf67WgX4iHqR"
+        );
+        assert_eq!(info.involved_accounts.len(), 11);
+        // a key loaded twice, not next to itself, is listed once at its first position
+        let mut tx = serde_json::to_value(load_fixture("raydium_clmm_swap_lut")).unwrap();
+        tx["meta"]["loadedAddresses"]["readonly"]
+            .as_array_mut()
+            .unwrap()
+            .push("C5LA7dNeM7unoukgWfAD4BRiorj4ihUqwUsDCirxfvsd".into());
+        let tx = serde_json::from_value(tx).unwrap();
+        let deduped = TransactionInfo::from_encoded_transaction(&tx, "signature");
+        assert_eq!(deduped.involved_accounts, info.involved_accounts);
+        // keys of transactions without lookup tables are kept too
+        let info = load_transaction_info("sol_transfer");
+        assert!(!info.involved_accounts.is_empty());
+        assert_eq!(info.involved_accounts, info.raw_account_keys);
+    }
+
+    #[test]
+    fn test_lookup_table_accounts_are_resolved() {
+        let tx = load_fixture("raydium_clmm_swap_lut");
+        let info = TransactionInfo::from_encoded_transaction(&tx, "signature");
+        // static keys, then the writable and readonly keys loaded from the lookup table
+        assert_eq!(info.raw_account_keys.len(), info.raw_pre_balances.len());
+        assert_eq!(
+            info.raw_account_keys[4],
+            "C5LA7dNeM7unoukgWfAD4BRiorj4ihUqwUsDCirxfvsd"
+        );
+        assert_eq!(
+            info.raw_account_keys[10],
+            "HnzRdkduvVwXSNDC8Nj8foEJGJ266LUEmbtfeTw1dkt8"
+        );
+        assert_eq!(
+            info.instructions[0].program_id,
+            RAYDIUM_CLMM_POOL_PROGRAM_ID
+        );
+        assert_eq!(info.instructions[0].accounts[1], info.raw_account_keys[10]);
+        // the received usdc account only exists in the lookup table
+        assert_eq!(
+            info.get_received_token_sol(),
+            Some((
+                "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v".to_string(),
+                180_000_000
+            ))
+        );
+        // a compiled system transfer to a lookup table account
+        let transfer = UiCompiledInstruction {
+            program_id_index: 11,
+            accounts: vec![0, 4],
+            data: bs58::encode([&2u32.to_le_bytes()[..], &5_000u64.to_le_bytes()].concat())
+                .into_string(),
+            stack_height: None,
+        };
+        let mut account_keys = info.raw_account_keys.clone();
+        account_keys.push(SYSTEM_PROGRAM_ID.to_string());
+        let transfer_info =
+            TransactionInfo::extract_compiled_transfer_info(&transfer, &account_keys).unwrap();
+        assert_eq!(transfer_info.from, info.raw_account_keys[0]);
+        assert_eq!(transfer_info.to, info.raw_account_keys[4]);
+        assert_eq!(transfer_info.amount, 5_000);
+    }
+
+    #[test]
+    fn test_max_amount_is_exact_for_large_amounts() {
+        let mint = "B2Fq8Yxo3V7uQ5Lq3CkzC8XG4GFBL2j9P5wVWDT9zKQ4";
+        let balance = TokenBalance {
+            account_index: 1,
+            mint: mint.to_string(),
+            owner: "FjELqXm4oWTtbEJcLn6Lam4p8vM1d3Aq4pB9a8QfEeYb".to_string(),
+            ui_token_amount: UiTokenAmount {
+                ui_amount: None,
+                decimals: 9,
+                amount: "0".to_string(),
+                ui_amount_string: None,
+            },
+        };
+        let info = TransactionInfo {
+            logs: vec![format!(
+                "Program log: bought 123,456,789.123456789 {}",
+                mint
+            )],
+            pre_token_balances: vec![balance.clone()],
+            post_token_balances: vec![balance],
+            ..Default::default()
+        };
+        // f64 cannot hold 18 significant digits
+        let amount_f64: f64 = "123456789.123456789".parse().unwrap();
+        assert_ne!(
+            crate::tool::ui_to_raw(amount_f64, 9),
+            123_456_789_123_456_789
+        );
+        assert_eq!(
+            info.get_max_amount_for_mint(mint),
+            Some(123_456_789_123_456_789)
+        );
+    }
+}
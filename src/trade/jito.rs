@@ -0,0 +1,89 @@
+use serde_json::Value;
+
+use crate::global::{JITO_TIP_ACCOUNTS, SYSTEM_PROGRAM_ID};
+use crate::trade::info::{InstructionInfo, TransactionInfo};
+
+/// system program `Transfer` instruction tag
+const SYSTEM_TRANSFER: u32 = 2;
+
+impl TransactionInfo {
+    /// Get the lamports the transaction tipped the jito tip accounts
+    /// Sums the system transfers, top-level and inner, to any of `JITO_TIP_ACCOUNTS`, so tip
+    /// spend can be told apart from the rest of the signer's SOL balance change
+    /// Returns None for failed transactions and transactions without a tip
+    pub fn jito_tip(&self) -> Option<u64> {
+        if self.is_failed() {
+            return None;
+        }
+        let tips: Vec<u64> = self
+            .instructions_for_program(SYSTEM_PROGRAM_ID)
+            .into_iter()
+            .filter_map(Self::system_transfer)
+            .filter(|(destination, _)| JITO_TIP_ACCOUNTS.contains(&destination.as_str()))
+            .map(|(_, lamports)| lamports)
+            .collect();
+        if tips.is_empty() {
+            return None;
+        }
+        Some(tips.iter().sum())
+    }
+
+    /// Check if the transaction pays a jito tip, i.e. is part of a jito bundle
+    pub fn is_jito_bundle_tip(&self) -> bool {
+        self.jito_tip().is_some()
+    }
+
+    /// (destination, lamports) of a system program `Transfer`
+    pub(crate) fn system_transfer(instruction: &InstructionInfo) -> Option<(String, u64)> {
+        match instruction.program.as_str() {
+            "compiled" | "partially_decoded" => {
+                // u32 tag, u64 lamports; accounts: source, destination
+                let data = bs58::decode(&instruction.data).into_vec().ok()?;
+                if data.len() < 12
+                    || u32::from_le_bytes(data[..4].try_into().ok()?) != SYSTEM_TRANSFER
+                {
+                    return None;
+                }
+                Some((
+                    instruction.accounts.get(1)?.clone(),
+                    u64::from_le_bytes(data[4..12].try_into().ok()?),
+                ))
+            }
+            _ => {
+                let parsed: Value = serde_json::from_str(&instruction.data).ok()?;
+                if parsed["type"] != "transfer" {
+                    return None;
+                }
+                Some((
+                    parsed["info"]["destination"].as_str()?.to_string(),
+                    parsed["info"]["lamports"].as_u64()?,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::load_transaction_info;
+
+    #[test]
+    fn test_jito_tip() {
+        let info = load_transaction_info("jito_tip_transfer");
+        assert_eq!(info.jito_tip(), Some(1_000_000));
+        assert!(info.is_jito_bundle_tip());
+    }
+
+    #[test]
+    fn test_transaction_without_jito_tip() {
+        for fixture in [
+            "compute_budget_transfer",
+            "sol_transfer",
+            "raydium_v4_swap_buy",
+        ] {
+            let info = load_transaction_info(fixture);
+            assert_eq!(info.jito_tip(), None);
+            assert!(!info.is_jito_bundle_tip());
+        }
+    }
+}
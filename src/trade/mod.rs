@@ -1,29 +1,90 @@
+pub mod bubblegum;
+pub mod events;
 pub mod info;
+pub mod jito;
 pub mod pool;
+pub mod pump_amm;
+pub mod route;
 pub mod signer_sol;
-use std::{str::FromStr, sync::Arc};
+pub mod swap;
+pub mod transfer_fee;
+pub mod wsol;
+use std::{collections::VecDeque, str::FromStr, sync::Arc, time::Duration};
 
-use futures::future::join_all;
+use futures::{Stream, StreamExt, future::join_all, stream};
+use serde_json::json;
 use solana_client::{
-    nonblocking::rpc_client::RpcClient, rpc_client::GetConfirmedSignaturesForAddress2Config,
-    rpc_config::RpcTransactionConfig, rpc_response::RpcConfirmedTransactionStatusWithSignature,
+    nonblocking::rpc_client::RpcClient,
+    rpc_client::GetConfirmedSignaturesForAddress2Config,
+    rpc_config::{RpcSendTransactionConfig, RpcSimulateTransactionConfig, RpcTransactionConfig},
+    rpc_request::RpcRequest,
+    rpc_response::RpcConfirmedTransactionStatusWithSignature,
+};
+use solana_commitment_config::CommitmentConfig;
+use solana_sdk::signature::{Keypair, Signature, Signer};
+use solana_sdk::{
+    hash::Hash, instruction::Instruction, message::Message, pubkey::Pubkey,
+    transaction::Transaction,
+};
+use solana_transaction_status::{
+    EncodedConfirmedTransactionWithStatusMeta, TransactionStatus, UiTransactionEncoding,
 };
-use solana_sdk::signature::Signature;
-use solana_sdk::{message::Message, pubkey::Pubkey};
-use solana_transaction_status::{EncodedConfirmedTransactionWithStatusMeta, UiTransactionEncoding};
 
+use crate::global::{COMPUTE_BUDGET_PROGRAM_ID, MAX_SIGNATURES_PER_REQUEST};
+use crate::tool::{percentile, rpc::signature_limit};
 use crate::trade::info::TransactionInfo;
-use crate::types::{UnifiedError, UnifiedResult};
+use crate::types::{FlowSummary, SignatureHistory, SimulationResult, TradeError, TxOptions};
+
+/// transactions checked at once by `Trade::get_transactions_vec_containing_address`
+pub const DEFAULT_CONTAINS_CONCURRENCY: usize = 8;
+/// signatures read at most by a full history scan, see `Trade::with_max_signatures`
+pub const DEFAULT_MAX_SIGNATURES: usize = 10_000;
+/// compute budget program `SetComputeUnitLimit` instruction tag
+const SET_COMPUTE_UNIT_LIMIT: u8 = 2;
+/// compute budget program `SetComputeUnitPrice` instruction tag
+const SET_COMPUTE_UNIT_PRICE: u8 = 3;
+/// pause between signature status checks of a sent transaction
+const SEND_STATUS_POLL_INTERVAL: Duration = Duration::from_millis(500);
 
 pub struct Trade {
     client: Arc<RpcClient>,
+    // commitment of signature and transaction lookups, None uses the rpc node's default (finalized)
+    commitment: Option<CommitmentConfig>,
+    // most signatures a history scan reads before stopping
+    max_signatures: usize,
 }
 impl Trade {
     pub fn new(client: Arc<RpcClient>) -> Self {
-        Self { client: client }
+        Self {
+            client: client,
+            commitment: None,
+            max_signatures: DEFAULT_MAX_SIGNATURES,
+        }
+    }
+
+    /// set the most signatures a history scan reads (at least 1), `DEFAULT_MAX_SIGNATURES` by default
+    /// applies to `get_transactions_history_filtered`, the contains-address family,
+    /// the recipient and payer lookups, `get_total_payment_amount` and `get_flow_between`
+    pub fn with_max_signatures(mut self, max_signatures: usize) -> Self {
+        self.max_signatures = max_signatures.max(1);
+        self
+    }
+
+    /// get the most signatures a history scan reads
+    pub fn max_signatures(&self) -> usize {
+        self.max_signatures
+    }
+
+    /// set the commitment of signature history and transaction lookups
+    /// (confirmed or finalized, the rpc rejects processed for these requests)
+    pub fn with_commitment(mut self, commitment: CommitmentConfig) -> Self {
+        self.commitment = Some(commitment);
+        self
     }
     /// estimate fee
-    pub async fn estimate_fee(&self) -> Result<u64, String> {
+    /// prices an empty message, i.e. the minimum base fee of a transaction with one signature,
+    /// use `estimate_fee_for` for the cost of an actual transaction
+    pub async fn estimate_fee(&self) -> Result<u64, TradeError> {
         match self.client.get_latest_blockhash().await {
             Ok(blockhash) => {
                 match self
@@ -32,37 +93,259 @@ impl Trade {
                     .await
                 {
                     Ok(fee) => Ok(fee),
-                    Err(_e) => Err("setimate fee error".to_string()),
+                    Err(e) => Err(TradeError::RpcError(format!("estimate fee error: {:?}", e))),
+                }
+            }
+            Err(e) => Err(TradeError::RpcError(format!(
+                "get block hash error: {:?}",
+                e
+            ))),
+        }
+    }
+
+    /// estimate the fee of a message the caller built: the base fee of its signatures
+    /// plus the priority fee of its compute budget instructions
+    /// the message needs a recent block hash, the rpc can't price expired ones
+    ///
+    /// # Example
+    /// ```ignore
+    /// let blockhash = solana.last_block_hash().await?;
+    /// let message = Message::new_with_blockhash(&instructions, Some(&payer), &Hash::from_str(&blockhash)?);
+    /// let fee = trade.estimate_fee_for(&message).await?;
+    /// ```
+    pub async fn estimate_fee_for(&self, message: &Message) -> Result<u64, TradeError> {
+        self.client
+            .get_fee_for_message(message)
+            .await
+            .map_err(|e| TradeError::RpcError(format!("estimate fee error: {:?}", e)))
+    }
+
+    /// simulate a transaction without sending it, e.g. to catch failures and read the compute units
+    /// signatures are not verified and the blockhash is replaced with the latest one,
+    /// so an unsigned or stale transaction can be simulated
+    ///
+    /// # Example
+    /// ```ignore
+    /// let simulation = trade.simulate(&transaction).await?;
+    /// if simulation.err.is_none() {
+    ///     println!("{:?} compute units", simulation.units_consumed);
+    /// }
+    /// ```
+    pub async fn simulate(&self, tx: &Transaction) -> Result<SimulationResult, TradeError> {
+        use base64::Engine;
+        let config = RpcSimulateTransactionConfig {
+            sig_verify: false,
+            replace_recent_blockhash: true,
+            commitment: self.commitment,
+            ..Default::default()
+        };
+        let result = self
+            .client
+            .simulate_transaction_with_config(tx, config)
+            .await
+            .map_err(|e| TradeError::RpcError(format!("simulate transaction error: {:?}", e)))?
+            .value;
+        let return_data = match result.return_data {
+            Some(return_data) => Some((
+                return_data.program_id,
+                base64::engine::general_purpose::STANDARD
+                    .decode(&return_data.data.0)
+                    .map_err(|e| TradeError::ParseError(format!("return data error: {:?}", e)))?,
+            )),
+            None => None,
+        };
+        Ok(SimulationResult {
+            err: result.err.map(|e| format!("{:?}", e)),
+            logs: result.logs.unwrap_or_default(),
+            units_consumed: result.units_consumed,
+            return_data,
+        })
+    }
+
+    /// compute budget instructions to prepend for `opts`: the compute unit limit and price
+    /// without an explicit price the median of the recent prioritization fees is paid
+    pub(crate) async fn compute_budget_instructions(
+        &self,
+        opts: &TxOptions,
+    ) -> Result<Vec<Instruction>, TradeError> {
+        let program_id = Pubkey::from_str_const(COMPUTE_BUDGET_PROGRAM_ID);
+        let mut instructions = Vec::new();
+        if let Some(units) = opts.compute_unit_limit {
+            let mut data = vec![SET_COMPUTE_UNIT_LIMIT];
+            data.extend_from_slice(&units.to_le_bytes());
+            instructions.push(Instruction::new_with_bytes(program_id, &data, vec![]));
+        }
+        let price = match opts.priority_fee_micro_lamports {
+            Some(price) => price,
+            None => {
+                let fees = self
+                    .client
+                    .get_recent_prioritization_fees(&[])
+                    .await
+                    .map_err(|e| {
+                        TradeError::RpcError(format!("get prioritization fees error: {:?}", e))
+                    })?;
+                percentile(
+                    fees.into_iter().map(|fee| fee.prioritization_fee).collect(),
+                    50.0,
+                )
+            }
+        };
+        if price > 0 {
+            let mut data = vec![SET_COMPUTE_UNIT_PRICE];
+            data.extend_from_slice(&price.to_le_bytes());
+            instructions.push(Instruction::new_with_bytes(program_id, &data, vec![]));
+        }
+        Ok(instructions)
+    }
+
+    /// sign `instructions` with the latest block hash, send them and wait for the commitment
+    /// the compute budget instructions of `opts` are prepended; when the chain passes the hash's
+    /// last valid block height before the transaction lands, it is re-signed with a fresh hash
+    /// and sent again, up to `opts.max_retries` times
+    pub(crate) async fn send_with_fresh_blockhash(
+        &self,
+        instructions: &[Instruction],
+        keypair: &Keypair,
+        opts: &TxOptions,
+    ) -> Result<String, TradeError> {
+        let mut instructions_with_budget = self.compute_budget_instructions(opts).await?;
+        instructions_with_budget.extend_from_slice(instructions);
+        self.send_and_confirm_with_retry(
+            |_| Transaction::new_with_payer(&instructions_with_budget, Some(&keypair.pubkey())),
+            keypair,
+            opts,
+        )
+        .await
+    }
+
+    /// build a transaction for the latest block hash, sign it with `wallet`, send it and wait
+    /// for the commitment
+    /// when the block hash expires before the transaction lands, or the node does not know it yet,
+    /// a fresh hash is fetched and `build` is called again, up to `opts.max_retries` times;
+    /// any other error, e.g. insufficient funds or exceeded slippage, is returned right away
+    /// `build` may sign with other signers itself, the compute budget fields of `opts` are not
+    /// applied, add those instructions in `build`
+    ///
+    /// # Example
+    /// ```ignore
+    /// let signature = trade
+    ///     .send_and_confirm_with_retry(
+    ///         |_blockhash| Transaction::new_with_payer(&instructions, Some(&wallet.pubkey())),
+    ///         &wallet,
+    ///         &TxOptions { max_retries: 5, ..Default::default() },
+    ///     )
+    ///     .await?;
+    /// ```
+    pub async fn send_and_confirm_with_retry<F>(
+        &self,
+        build: F,
+        wallet: &Keypair,
+        opts: &TxOptions,
+    ) -> Result<String, TradeError>
+    where
+        F: Fn(Hash) -> Transaction,
+    {
+        let commitment = self.commitment.unwrap_or_else(|| self.client.commitment());
+        let config = RpcSendTransactionConfig {
+            skip_preflight: opts.skip_preflight,
+            preflight_commitment: Some(commitment.commitment),
+            ..Default::default()
+        };
+        for _ in 0..=opts.max_retries {
+            let (blockhash, last_valid_block_height) = self
+                .client
+                .get_latest_blockhash_with_commitment(commitment)
+                .await
+                .map_err(|e| TradeError::RpcError(format!("get block hash error: {:?}", e)))?;
+            let mut transaction = build(blockhash);
+            transaction
+                .try_partial_sign(&[wallet], blockhash)
+                .map_err(|e| TradeError::RpcError(format!("sign transaction error: {:?}", e)))?;
+            let signature = match self
+                .client
+                .send_transaction_with_config(&transaction, config)
+                .await
+            {
+                Ok(signature) => signature,
+                // preflight ran against a node that has not seen the hash yet
+                Err(e) if format!("{:?}", e).contains("BlockhashNotFound") => continue,
+                Err(e) => {
+                    return Err(TradeError::RpcError(format!(
+                        "send transaction error: {:?}",
+                        e
+                    )));
+                }
+            };
+            loop {
+                if let Some(status) = self.signature_status(&signature).await? {
+                    if let Some(err) = status.err {
+                        return Err(TradeError::RpcError(format!(
+                            "transaction {} failed: {:?}",
+                            signature, err
+                        )));
+                    }
+                    if status.satisfies_commitment(commitment) {
+                        return Ok(signature.to_string());
+                    }
+                    // landed, only the commitment is missing, so it no longer depends on the hash
+                    tokio::time::sleep(SEND_STATUS_POLL_INTERVAL).await;
+                    continue;
+                }
+                let block_height = self
+                    .client
+                    .get_block_height_with_commitment(commitment)
+                    .await
+                    .map_err(|e| {
+                        TradeError::RpcError(format!("get block height error: {:?}", e))
+                    })?;
+                // it may have landed between the status and the height request
+                if block_height > last_valid_block_height
+                    && self.signature_status(&signature).await?.is_none()
+                {
+                    break;
                 }
+                tokio::time::sleep(SEND_STATUS_POLL_INTERVAL).await;
             }
-            Err(e) => Err(format!("get block hash error: {:?}", e)),
         }
+        Err(TradeError::RpcError(format!(
+            "block hash expired before the transaction landed, gave up after {} attempts",
+            opts.max_retries + 1
+        )))
+    }
+
+    /// status of a sent transaction, None while no node has seen it
+    async fn signature_status(
+        &self,
+        signature: &Signature,
+    ) -> Result<Option<TransactionStatus>, TradeError> {
+        Ok(self
+            .client
+            .get_signature_statuses(&[*signature])
+            .await
+            .map_err(|e| TradeError::RpcError(format!("get signature status error: {:?}", e)))?
+            .value
+            .into_iter()
+            .next()
+            .flatten())
     }
 
     /// get the transaction records of the specified address based on the cursor.
+    /// `page_size` is clamped to 1..=1000, the most signatures the rpc returns per request
+    /// past the end of the history the page is empty and the next cursor None,
+    /// errors are only returned for invalid input and rpc failures
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let mut cursor: Option<String> = None;
     /// loop {
-    ///     match trade
-    ///         .get_transactions_history_by_cursor(
-    ///             "wallet address",
-    ///             cursor.clone(),
-    ///             page size,
-    ///         )
-    ///         .await
-    ///     {
-    ///         Ok(r) => {
-    ///             // r.0 is transaction history list
-    ///             if r.1.is_none() {
-    ///                 break;
-    ///             }
-    ///             cursor = r.1;
-    ///         }
-    ///         Err(_) => {
-    ///             break;
-    ///         }
+    ///     let (signatures, next_cursor) = trade
+    ///         .get_transactions_history_by_cursor("wallet address", cursor, page_size)
+    ///         .await?;
+    ///     // signatures is the transaction history list
+    ///     match next_cursor {
+    ///         Some(next) => cursor = Some(next),
+    ///         None => break,
     ///     }
     /// }
     /// ```
@@ -71,37 +354,34 @@ impl Trade {
         address: &str,
         cursor: Option<String>,
         page_size: u32,
-    ) -> UnifiedResult<
+    ) -> Result<
         (
             Vec<RpcConfirmedTransactionStatusWithSignature>,
             Option<String>,
         ),
-        String,
+        TradeError,
     > {
         match Pubkey::from_str(address) {
             Ok(address) => {
                 let before = match cursor {
-                    Some(c) => Some(Signature::from_str(&c).unwrap()),
+                    Some(c) => {
+                        Some(Signature::from_str(&c).map_err(|_| TradeError::InvalidSignature(c))?)
+                    }
                     None => None,
                 };
                 let config = GetConfirmedSignaturesForAddress2Config {
                     before: before,
                     until: None,
-                    limit: Some(page_size as usize),
-                    commitment: None,
+                    limit: Some(signature_limit(page_size as usize)),
+                    commitment: self.commitment,
                 };
                 let signatures: Vec<RpcConfirmedTransactionStatusWithSignature> = match self
                     .client
                     .get_signatures_for_address_with_config(&address, config)
                     .await
                 {
-                    Ok(signatures) => {
-                        if signatures.is_empty() {
-                            return Err(UnifiedError::Error("signatures is empty".to_string()));
-                        }
-                        signatures
-                    }
-                    _ => Vec::<RpcConfirmedTransactionStatusWithSignature>::new(),
+                    Ok(signatures) => signatures,
+                    Err(e) => return Err(TradeError::RpcError(format!("{:?}", e))),
                 };
                 let next_cursor = signatures.last().map(
                     |sig_info: &RpcConfirmedTransactionStatusWithSignature| {
@@ -110,64 +390,227 @@ impl Trade {
                 );
                 Ok((signatures, next_cursor))
             }
-            Err(_) => Err(UnifiedError::Error("address from string error".to_string())),
+            Err(_) => Err(TradeError::InvalidAddress(address.to_string())),
+        }
+    }
+
+    /// stream the signatures of an address one by one, newest first
+    /// pages of `page_size` are fetched lazily as the stream is polled, following the `before` cursor,
+    /// so only one page is held in memory and dropping the stream stops further requests
+    ///
+    /// # Example
+    /// ```ignore
+    /// use futures::{StreamExt, pin_mut};
+    /// let stream = trade.signatures_stream("wallet address", 1000);
+    /// pin_mut!(stream);
+    /// while let Some(signature) = stream.next().await {
+    ///     let signature = signature?;
+    /// }
+    /// ```
+    pub fn signatures_stream(
+        &self,
+        address: &str,
+        page_size: usize,
+    ) -> impl Stream<Item = Result<RpcConfirmedTransactionStatusWithSignature, TradeError>> + '_
+    {
+        let address = address.to_string();
+        let page_size = signature_limit(page_size);
+        // (buffered page, cursor for the next page, whether another page may exist)
+        let state = (
+            VecDeque::<RpcConfirmedTransactionStatusWithSignature>::new(),
+            None::<String>,
+            true,
+        );
+        stream::unfold(state, move |(mut page, mut cursor, mut has_more)| {
+            let address = address.clone();
+            async move {
+                if page.is_empty() && has_more {
+                    match self
+                        .get_transactions_history_by_cursor(
+                            &address,
+                            cursor.clone(),
+                            page_size as u32,
+                        )
+                        .await
+                    {
+                        Ok((signatures, next_cursor)) => {
+                            has_more = signatures.len() >= page_size && next_cursor.is_some();
+                            cursor = next_cursor;
+                            page.extend(signatures);
+                        }
+                        Err(e) => return Some((Err(e), (page, cursor, false))),
+                    }
+                }
+                page.pop_front()
+                    .map(|signature| (Ok(signature), (page, cursor, has_more)))
+            }
+        })
+    }
+
+    /// Get the oldest transaction of an address, e.g. to tell when a wallet was created
+    /// walks the `before` cursor to the end of the history, which can take many requests for busy addresses
+    /// with `max_pages` set at most that many pages of 1000 signatures are read and the oldest one seen is returned
+    /// returns None when the address has no transactions
+    ///
+    /// # Example
+    /// ```ignore
+    /// let first = trade.get_first_transaction("wallet address", Some(10)).await?;
+    /// if let Some(first) = first {
+    ///     println!("created at {:?}", first.block_time);
+    /// }
+    /// ```
+    pub async fn get_first_transaction(
+        &self,
+        address: &str,
+        max_pages: Option<usize>,
+    ) -> Result<Option<RpcConfirmedTransactionStatusWithSignature>, TradeError> {
+        self.first_transaction_with_page_size(address, max_pages, MAX_SIGNATURES_PER_REQUEST as u32)
+            .await
+    }
+
+    async fn first_transaction_with_page_size(
+        &self,
+        address: &str,
+        max_pages: Option<usize>,
+        page_size: u32,
+    ) -> Result<Option<RpcConfirmedTransactionStatusWithSignature>, TradeError> {
+        let mut oldest = None;
+        let mut cursor = None;
+        let mut pages = 0;
+        while max_pages.is_none_or(|max| pages < max) {
+            match self
+                .get_transactions_history_by_cursor(address, cursor, page_size)
+                .await
+            {
+                Ok((mut signatures, next_cursor)) => {
+                    pages += 1;
+                    let last_page = signatures.len() < page_size as usize;
+                    // a full last page is followed by an empty one, keep the oldest seen so far
+                    if let Some(signature) = signatures.pop() {
+                        oldest = Some(signature);
+                    }
+                    if last_page {
+                        break;
+                    }
+                    cursor = next_cursor;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        Ok(oldest)
+    }
+
+    /// Get the signatures of an address whose slot lies in `from_slot..=to_slot`, newest first
+    /// signatures come newest first, so paging stops at the first one below `from_slot`
+    /// instead of walking the whole history
+    ///
+    /// # Example
+    /// ```ignore
+    /// let epoch = solana.client_arc().get_epoch_schedule().await?;
+    /// let signatures = trade
+    ///     .get_transactions_in_slot_range("wallet address", epoch.get_first_slot_in_epoch(800), epoch.get_last_slot_in_epoch(800))
+    ///     .await?;
+    /// ```
+    pub async fn get_transactions_in_slot_range(
+        &self,
+        address: &str,
+        from_slot: u64,
+        to_slot: u64,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, TradeError> {
+        self.slot_range_with_page_size(
+            address,
+            from_slot,
+            to_slot,
+            MAX_SIGNATURES_PER_REQUEST as u32,
+        )
+        .await
+    }
+
+    async fn slot_range_with_page_size(
+        &self,
+        address: &str,
+        from_slot: u64,
+        to_slot: u64,
+        page_size: u32,
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, TradeError> {
+        let mut in_range = Vec::new();
+        if from_slot > to_slot {
+            return Ok(in_range);
+        }
+        let mut cursor = None;
+        loop {
+            let (signatures, next_cursor) = self
+                .get_transactions_history_by_cursor(address, cursor, page_size)
+                .await?;
+            let last_page = signatures.len() < page_size as usize;
+            for signature in signatures {
+                if signature.slot < from_slot {
+                    return Ok(in_range);
+                }
+                if signature.slot <= to_slot {
+                    in_range.push(signature);
+                }
+            }
+            if last_page || next_cursor.is_none() {
+                return Ok(in_range);
+            }
+            cursor = next_cursor;
         }
     }
 
     /// Get transaction records of a specified address and support filtering conditions
+    /// pages through the history newest first until it ends or `max_signatures` signatures were read,
+    /// `truncated` is set on the result when the cap stopped the scan
     ///
     /// # Params
-    /// client - client
     /// address - wallet
     /// filter - filter condition closure, returning true means retaining the transaction record
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let solana = Solana::new(Mode::DEV).unwrap();
-    /// let client = solana.client_arc();
-    /// let history = TradeHistory::get_filtered_transactions(
-    ///     &client,
+    /// let trade = solana.create_trade();
+    /// let history = trade.get_transactions_history_filtered(
     ///     "wallet address",
     ///     |sig_info| {
     ///         // return true to retain transaction information
     ///         true
     ///     },
-    /// ).await;
+    /// ).await?;
+    /// if history.truncated {
+    ///     // older signatures were not read, raise the cap with `with_max_signatures`
+    /// }
     /// ```
     pub async fn get_transactions_history_filtered<F>(
-        client: &Arc<RpcClient>,
+        &self,
         address: &str,
         filter: F,
-    ) -> UnifiedResult<Vec<RpcConfirmedTransactionStatusWithSignature>, String>
+    ) -> Result<SignatureHistory, TradeError>
     where
         F: Fn(&RpcConfirmedTransactionStatusWithSignature) -> bool,
     {
-        match Pubkey::from_str(address) {
-            Ok(pubkey) => {
-                let config = GetConfirmedSignaturesForAddress2Config {
-                    before: None,
-                    until: None,
-                    limit: None,
-                    commitment: None,
-                };
-                match client
-                    .get_signatures_for_address_with_config(&pubkey, config)
-                    .await
-                {
-                    Ok(signatures) => {
-                        let filtered: Vec<RpcConfirmedTransactionStatusWithSignature> = signatures
-                            .into_iter()
-                            .filter(|sig_info| filter(sig_info))
-                            .collect();
-                        Ok(filtered)
-                    }
-                    Err(e) => Err(UnifiedError::Error(format!(
-                        "failed to obtain transaction records: {:?}",
-                        e
-                    ))),
-                }
+        let mut history = SignatureHistory::default();
+        let mut read = 0usize;
+        let mut cursor = None;
+        loop {
+            let page_size = MAX_SIGNATURES_PER_REQUEST.min(self.max_signatures - read);
+            let (signatures, next_cursor) = self
+                .get_transactions_history_by_cursor(address, cursor, page_size as u32)
+                .await?;
+            read += signatures.len();
+            // a short page means the history is exhausted
+            let last_page = signatures.len() < page_size || next_cursor.is_none();
+            history
+                .signatures
+                .extend(signatures.into_iter().filter(|sig_info| filter(sig_info)));
+            if last_page {
+                return Ok(history);
             }
-            Err(_) => Err(UnifiedError::Error("address format error".to_string())),
+            if read >= self.max_signatures {
+                history.truncated = true;
+                return Ok(history);
+            }
+            cursor = next_cursor;
         }
     }
 
@@ -179,11 +622,11 @@ impl Trade {
     ///
     /// # Returns
     /// Ok(Some(RpcConfirmedTransactionStatusWithSignature)) - last transaction records containing address B
-    /// Ok(None) - does not contain address B
+    /// Ok(None) - address B is not in the newest `max_signatures` transactions
     /// Err - error
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let solana = Solana::new(Mode::DEV).unwrap();
     /// let trade = solana.create_trade();
     /// let contains = trade.contains_address_in_transactions(
@@ -195,15 +638,16 @@ impl Trade {
         &self,
         address_a: &str,
         address_b: &str,
-    ) -> UnifiedResult<Option<RpcConfirmedTransactionStatusWithSignature>, String> {
-        let all_transactions =
-            Self::get_transactions_history_filtered(&self.client, address_a, |_| true).await?;
+    ) -> Result<Option<RpcConfirmedTransactionStatusWithSignature>, TradeError> {
+        let all_transactions = self
+            .get_transactions_history_filtered(address_a, |_| true)
+            .await?;
         if all_transactions.is_empty() {
-            return Err(UnifiedError::Error("transactions is empty".to_string()));
+            return Err(TradeError::Empty);
         }
         let address_b_pubkey = match Pubkey::from_str(address_b) {
             Ok(pubkey) => pubkey,
-            Err(_) => return Err(UnifiedError::Error("address B format error".to_string())),
+            Err(_) => return Err(TradeError::InvalidAddress(address_b.to_string())),
         };
         let address_b_str = address_b_pubkey.to_string();
         for transaction in all_transactions {
@@ -218,16 +662,18 @@ impl Trade {
     }
 
     /// Get all transactions of address A that contain address B
+    /// transactions are checked `DEFAULT_CONTAINS_CONCURRENCY` at a time
     ///
     /// # Params
     /// address_a - main inquiry address
     /// address_b - address to include
     ///
     /// # Returns
-    /// contains a list of all transaction records for address B
+    /// contains a list of all transaction records for address B, newest first,
+    /// truncated when address A has more than `max_signatures` transactions
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let solana = Solana::new(Mode::DEV).unwrap();
     /// let trade = solana.create_trade();
     /// let related_transactions = trade.get_transactions_vec_containing_address(
@@ -239,23 +685,71 @@ impl Trade {
         &self,
         address_a: &str,
         address_b: &str,
-    ) -> UnifiedResult<Vec<RpcConfirmedTransactionStatusWithSignature>, String> {
-        let all_transactions =
-            Self::get_transactions_history_filtered(&self.client, address_a, |_| true).await?;
+    ) -> Result<SignatureHistory, TradeError> {
+        self.get_transactions_vec_containing_address_with_concurrency(
+            address_a,
+            address_b,
+            DEFAULT_CONTAINS_CONCURRENCY,
+        )
+        .await
+    }
+
+    /// Get all transactions of address A that contain address B, fetching up to
+    /// `concurrency` transactions at once (at least 1)
+    ///
+    /// # Params
+    /// address_a - main inquiry address
+    /// address_b - address to include
+    /// concurrency - maximum number of transaction requests in flight
+    ///
+    /// # Returns
+    /// contains a list of all transaction records for address B, newest first,
+    /// truncated when address A has more than `max_signatures` transactions
+    ///
+    /// # Example
+    /// ```ignore
+    /// let related_transactions = trade
+    ///     .get_transactions_vec_containing_address_with_concurrency("address a", "address b", 16)
+    ///     .await;
+    /// ```
+    pub async fn get_transactions_vec_containing_address_with_concurrency(
+        &self,
+        address_a: &str,
+        address_b: &str,
+        concurrency: usize,
+    ) -> Result<SignatureHistory, TradeError> {
+        let all_transactions = self
+            .get_transactions_history_filtered(address_a, |_| true)
+            .await?;
+        let truncated = all_transactions.truncated;
         if all_transactions.is_empty() {
-            return Ok(Vec::new());
+            return Ok(SignatureHistory::default());
         }
         let address_b_str = address_b.to_string();
-        let mut matching_transactions = Vec::new();
-        for transaction in all_transactions {
-            if self
-                .is_transaction_contains_address(&transaction.signature, &address_b_str)
-                .await
-            {
-                matching_transactions.push(transaction);
-            }
-        }
-        Ok(matching_transactions)
+        let mut matching_transactions: Vec<(usize, RpcConfirmedTransactionStatusWithSignature)> =
+            stream::iter(all_transactions.into_iter().enumerate())
+                .map(|(index, transaction)| {
+                    let address_b_str = &address_b_str;
+                    async move {
+                        let contains = self
+                            .is_transaction_contains_address(&transaction.signature, address_b_str)
+                            .await;
+                        contains.then_some((index, transaction))
+                    }
+                })
+                .buffer_unordered(concurrency.max(1))
+                .filter_map(|matched| async move { matched })
+                .collect()
+                .await;
+        // restore the newest-first order of the history
+        matching_transactions.sort_by_key(|(index, _)| *index);
+        Ok(SignatureHistory {
+            signatures: matching_transactions
+                .into_iter()
+                .map(|(_, transaction)| transaction)
+                .collect(),
+            truncated,
+        })
     }
 
     /// get the transaction record with address A as the payer and address B included
@@ -268,7 +762,7 @@ impl Trade {
     /// limit - Maximum number of transactions returned
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let transactions = trade.get_transactions_by_recipient_and_payer(
     ///     "Recipient address",
     ///     "payer",
@@ -280,12 +774,13 @@ impl Trade {
         address_a: &str,
         address_b: &str,
         limit: usize,
-    ) -> UnifiedResult<Vec<RpcConfirmedTransactionStatusWithSignature>, String> {
-        let all_transactions =
-            Self::get_transactions_history_filtered(&self.client, address_a, |_| true).await?;
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, TradeError> {
+        let all_transactions = self
+            .get_transactions_history_filtered(address_a, |_| true)
+            .await?;
         let mut matching_transactions = Vec::new();
         let address_b_pubkey = Pubkey::from_str(address_b)
-            .map_err(|_| UnifiedError::Error("address B format error".to_string()))?;
+            .map_err(|_| TradeError::InvalidAddress(address_b.to_string()))?;
         let address_b_str = address_b_pubkey.to_string();
         for transaction in all_transactions.into_iter().take(limit) {
             // Check if the transaction contains address B
@@ -325,19 +820,25 @@ impl Trade {
         address_a: &str,
         address_b: &str,
         limit: usize,
-    ) -> UnifiedResult<Vec<RpcConfirmedTransactionStatusWithSignature>, String> {
-        let candidate_transactions = self
-            .get_transactions_by_recipient_and_payer(address_a, address_b, limit * 2)
+    ) -> Result<Vec<RpcConfirmedTransactionStatusWithSignature>, TradeError> {
+        let all_transactions = self
+            .get_transactions_history_filtered(address_a, |_| true)
             .await?;
+        Pubkey::from_str(address_b)
+            .map_err(|_| TradeError::InvalidAddress(address_b.to_string()))?;
         let mut confirmed_transactions = Vec::new();
-        for transaction in candidate_transactions.into_iter().take(limit) {
+        // keep scanning until `limit` strict matches are found, not just `limit` candidates
+        for transaction in all_transactions {
+            if confirmed_transactions.len() >= limit {
+                break;
+            }
             match self.get_transaction_details(&transaction.signature).await {
                 Ok(tx_details) => {
                     let transaction_info = TransactionInfo::from_encoded_transaction(
                         &tx_details,
                         &transaction.signature,
                     );
-                    // Address A is the payer and Address B is the payer
+                    // Address A is the recipient and Address B is the payer
                     if Self::is_address_recipient_in_transaction(&transaction_info, address_a)
                         && Self::is_address_payer_in_transaction(&transaction_info, address_b)
                     {
@@ -351,6 +852,7 @@ impl Trade {
     }
 
     /// Determine whether address A is the recipient in the transaction
+    /// An address is a recipient when the token balance it owns grew in some mint, whoever paid the fee
     ///
     /// # Params
     /// transaction_info - transaction information
@@ -363,32 +865,10 @@ impl Trade {
         transaction_info: &TransactionInfo,
         address: &str,
     ) -> bool {
-        if transaction_info.balance_change > 0 {
-            if let Some(post_balance) = transaction_info
-                .post_token_balances
-                .iter()
-                .find(|balance| balance.owner == address)
-            {
-                if let Some(pre_balance) = transaction_info
-                    .pre_token_balances
-                    .iter()
-                    .find(|balance| balance.owner == address && balance.mint == post_balance.mint)
-                {
-                    let pre_amount = pre_balance
-                        .ui_token_amount
-                        .amount
-                        .parse::<u64>()
-                        .unwrap_or(0);
-                    let post_amount = post_balance
-                        .ui_token_amount
-                        .amount
-                        .parse::<u64>()
-                        .unwrap_or(0);
-                    return post_amount > pre_amount;
-                }
-            }
-        }
-        false
+        transaction_info
+            .balance_changes_for_owner(address)
+            .iter()
+            .any(|(_, change, _)| *change > 0)
     }
 
     /// Determine whether address B is the payer in the transaction
@@ -404,10 +884,8 @@ impl Trade {
         transaction_info: &TransactionInfo,
         address_b: &str,
     ) -> bool {
-        if transaction_info.signers.contains(&address_b.to_string()) {
-            return true;
-        }
-        false
+        transaction_info.fee_payer == address_b
+            || transaction_info.signers.contains(&address_b.to_string())
     }
 
     /// Quickly determine whether there is a payment relationship between two addresses (address B pays address A)
@@ -423,7 +901,7 @@ impl Trade {
         &self,
         address_a: &str,
         address_b: &str,
-    ) -> UnifiedResult<Option<String>, String> {
+    ) -> Result<Option<String>, TradeError> {
         let transactions = self
             .get_transactions_by_recipient_and_payer_strict(address_a, address_b, 1)
             .await?;
@@ -440,47 +918,116 @@ impl Trade {
     /// address_a - Recipient address
     /// address_b - Payer address
     /// time_range - Time range (seconds), None means all time
+    /// max_transactions - Maximum number of payments to add up, None means no limit
     ///
     /// # Returns
-    /// Total payment amount (lamports)
+    /// Total payment amount (lamports) over at most the newest `max_signatures` transactions of address A
     pub async fn get_total_payment_amount(
         &self,
         address_a: &str,
         address_b: &str,
         time_range: Option<u64>,
-    ) -> UnifiedResult<u64, String> {
-        let transactions = self
-            .get_transactions_by_recipient_and_payer_strict(address_a, address_b, 100)
-            .await?;
+        max_transactions: Option<usize>,
+    ) -> Result<u64, TradeError> {
+        Pubkey::from_str(address_b)
+            .map_err(|_| TradeError::InvalidAddress(address_b.to_string()))?;
+        let now = crate::tool::now_unix_secs();
+        let window_start = time_range.map(|range| now.saturating_sub(range) as i64);
         let mut total_amount = 0u64;
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_secs();
-        for transaction in transactions {
-            match self.get_transaction_details(&transaction.signature).await {
-                Ok(tx_details) => {
-                    let transaction_info = TransactionInfo::from_encoded_transaction(
-                        &tx_details,
-                        &transaction.signature,
-                    );
-                    if let Some(range) = time_range {
-                        if let Some(block_time) = transaction_info.block_time {
-                            if (now - block_time as u64) > range {
-                                continue;
+        let mut counted = 0usize;
+        let mut read = 0usize;
+        let mut cursor: Option<String> = None;
+        'pages: loop {
+            let page_size = MAX_SIGNATURES_PER_REQUEST.min(self.max_signatures - read);
+            let (transactions, next_cursor) = self
+                .get_transactions_history_by_cursor(address_a, cursor, page_size as u32)
+                .await?;
+            read += transactions.len();
+            // a short page means the history is exhausted
+            let last_page = transactions.len() < page_size || read >= self.max_signatures;
+            for transaction in transactions {
+                if max_transactions.is_some_and(|max| counted >= max) {
+                    break 'pages;
+                }
+                // signatures come newest first, so everything after this one is out of range too
+                if let (Some(start), Some(block_time)) = (window_start, transaction.block_time)
+                    && block_time < start
+                {
+                    break 'pages;
+                }
+                match self.get_transaction_details(&transaction.signature).await {
+                    Ok(tx_details) => {
+                        let transaction_info = TransactionInfo::from_encoded_transaction(
+                            &tx_details,
+                            &transaction.signature,
+                        );
+                        if Self::is_address_recipient_in_transaction(&transaction_info, address_a)
+                            && Self::is_address_payer_in_transaction(&transaction_info, address_b)
+                        {
+                            if let Ok(amount) = transaction_info.value.parse::<u64>() {
+                                total_amount += amount;
                             }
+                            counted += 1;
                         }
                     }
-                    if let Ok(amount) = transaction_info.value.parse::<u64>() {
-                        total_amount += amount;
-                    }
+                    Err(_) => continue,
                 }
-                Err(_) => continue,
+            }
+            match next_cursor {
+                Some(next) if !last_page => cursor = Some(next),
+                _ => break,
             }
         }
         Ok(total_amount)
     }
 
+    /// Get the payments in both directions between address X and address Y
+    /// each direction uses the same strict check as `get_transactions_by_recipient_and_payer_strict`
+    ///
+    /// # Params
+    /// addr_x - First address, its history is scanned
+    /// addr_y - Second address
+    /// limit - Maximum number of matching transactions, both directions together
+    ///
+    /// # Returns
+    /// Totals (lamports) and transactions of X to Y and Y to X, newest first
+    pub async fn get_flow_between(
+        &self,
+        addr_x: &str,
+        addr_y: &str,
+        limit: usize,
+    ) -> Result<FlowSummary, TradeError> {
+        let all_transactions = self
+            .get_transactions_history_filtered(addr_x, |_| true)
+            .await?;
+        Pubkey::from_str(addr_y).map_err(|_| TradeError::InvalidAddress(addr_y.to_string()))?;
+        let mut flow = FlowSummary::default();
+        for transaction in all_transactions {
+            if flow.x_to_y_transactions.len() + flow.y_to_x_transactions.len() >= limit {
+                break;
+            }
+            let Ok(tx_details) = self.get_transaction_details(&transaction.signature).await else {
+                continue;
+            };
+            let transaction_info =
+                TransactionInfo::from_encoded_transaction(&tx_details, &transaction.signature);
+            let amount = transaction_info.value.parse::<u64>().unwrap_or(0);
+            if Self::is_address_recipient_in_transaction(&transaction_info, addr_y)
+                && Self::is_address_payer_in_transaction(&transaction_info, addr_x)
+            {
+                flow.x_to_y += amount;
+                flow.x_to_y_transactions.push(transaction);
+            } else if Self::is_address_recipient_in_transaction(&transaction_info, addr_x)
+                && Self::is_address_payer_in_transaction(&transaction_info, addr_y)
+            {
+                flow.y_to_x += amount;
+                flow.y_to_x_transactions.push(transaction);
+            }
+        }
+        flow.net_flow = flow.x_to_y as i128 - flow.y_to_x as i128;
+        Ok(flow)
+    }
+
     /// checks whether a single transaction contains a specified address
     async fn is_transaction_contains_address(&self, signature: &str, target_address: &str) -> bool {
         match self.get_transaction_details(signature).await {
@@ -498,7 +1045,7 @@ impl Trade {
     /// signature - transaction signature hash string
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let solana = Solana::new(solana_trader::types::Mode::DEV).unwrap();
     /// let trade = solana.create_trade();
     /// let transaction_info = trade.get_transaction_details("transaction signature").await;
@@ -506,27 +1053,52 @@ impl Trade {
     pub async fn get_transaction_details(
         &self,
         signature: &str,
-    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, String> {
-        let signature = match Signature::from_str(&signature) {
-            Ok(signature) => signature,
-            Err(_) => todo!(),
-        };
+    ) -> Result<EncodedConfirmedTransactionWithStatusMeta, TradeError> {
+        self.get_transaction_details_optional(signature)
+            .await?
+            .ok_or_else(|| {
+                TradeError::RpcError(format!("get transaction error: {} not found", signature))
+            })
+    }
+
+    /// get transaction details, telling a missing transaction apart from a failed request
+    /// e.g. to poll a just sent signature until the rpc node has it
+    ///
+    /// # params
+    /// signature - transaction signature hash string
+    ///
+    /// # Returns
+    /// Ok(None) when the rpc node has no such transaction (not landed yet or pruned),
+    /// Err when the request or the parsing of its response failed
+    ///
+    /// # Example
+    /// ```ignore
+    /// let transaction = loop {
+    ///     match trade.get_transaction_details_optional(&signature).await? {
+    ///         Some(transaction) => break transaction,
+    ///         None => tokio::time::sleep(Duration::from_millis(500)).await,
+    ///     }
+    /// };
+    /// ```
+    pub async fn get_transaction_details_optional(
+        &self,
+        signature: &str,
+    ) -> Result<Option<EncodedConfirmedTransactionWithStatusMeta>, TradeError> {
+        let signature = Signature::from_str(signature)
+            .map_err(|_| TradeError::InvalidSignature(signature.to_string()))?;
         let config = RpcTransactionConfig {
             encoding: Some(UiTransactionEncoding::Json),
-            commitment: None,
+            commitment: self.commitment,
             max_supported_transaction_version: Some(0),
         };
-        match self
-            .client
-            .get_transaction_with_config(&signature, config)
+        // `get_transaction_with_config` fails on the null result of an unknown transaction
+        self.client
+            .send(
+                RpcRequest::GetTransaction,
+                json!([signature.to_string(), config]),
+            )
             .await
-        {
-            Ok(transaction) => Ok(transaction),
-            Err(e) => {
-                // get tade info error
-                Err(format!("get tade info error: {:?}", e))
-            }
-        }
+            .map_err(|e| TradeError::RpcError(format!("get transaction error: {:?}", e)))
     }
 
     /// Get transaction details in batch
@@ -535,17 +1107,17 @@ impl Trade {
     /// signatures - Array of transaction signature strings (slice)
     ///
     /// # Returns
-    /// `Result<Vec<EncodedConfirmedTransactionWithStatusMeta>, String>`
+    /// `Result<Vec<EncodedConfirmedTransactionWithStatusMeta>, TradeError>`
     /// - `Ok(transactions)`: List of successfully retrieved transaction details
     /// - `Err(error)`: Error during batch query process (e.g., network error)
     ///
     /// # Features
     /// - Executes multiple transaction queries in parallel for improved efficiency
-    /// - Automatically filters failed queries, returning only successful transactions
+    /// - Returns one (signature, result) pair per input signature, in input order
     /// - Individual query failures do not affect other queries
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let solana = Solana::new(solana_trader::types::Mode::MAIN).unwrap();
     /// let trade = solana.create_trade();
     ///
@@ -556,59 +1128,77 @@ impl Trade {
     /// ];
     ///
     /// // Batch query transactions
-    /// let transactions = trade.get_transaction_details_batch(&signatures).await?;
+    /// let transactions = trade.get_transaction_details_batch(signatures).await?;
     ///
-    /// // Process each transaction
-    /// for tx in transactions {
-    ///     let tx_info = TransactionInfo::from_encoded_transaction(&tx, "signature");
-    ///     println!("Transaction slot: {}", tx_info.slot);
+    /// // Process each transaction, results keep the input order
+    /// for (signature, result) in transactions {
+    ///     match result {
+    ///         Ok(tx) => {
+    ///             let tx_info = TransactionInfo::from_encoded_transaction(&tx, &signature);
+    ///             println!("Transaction slot: {}", tx_info.slot);
+    ///         }
+    ///         Err(e) => println!("{} failed: {}", signature, e),
+    ///     }
     /// }
     /// ```
     ///
     /// # Performance Recommendations
     /// - Recommended to query no more than 50 transaction signatures at once to avoid RPC limits
     /// - For large numbers of queries, consider batching them
-    /// - Failed queries output error messages to stderr without interrupting the entire batch operation
+    /// - Failed queries are returned in place with their error without interrupting the entire batch operation
     pub async fn get_transaction_details_batch(
         &self,
         signatures: Vec<&str>,
-    ) -> Result<Vec<EncodedConfirmedTransactionWithStatusMeta>, String> {
+    ) -> Result<
+        Vec<(
+            String,
+            Result<EncodedConfirmedTransactionWithStatusMeta, TradeError>,
+        )>,
+        TradeError,
+    > {
+        Ok(self
+            .fetch_transactions(&signatures)
+            .await
+            .into_iter()
+            .map(|(signature, result)| (signature.to_string(), result))
+            .collect())
+    }
+
+    /// fetch transactions in parallel, each result paired with its signature in input order
+    pub(crate) async fn fetch_transactions<'a>(
+        &self,
+        signatures: &[&'a str],
+    ) -> Vec<(
+        &'a str,
+        Result<EncodedConfirmedTransactionWithStatusMeta, TradeError>,
+    )> {
         let mut futures = Vec::new();
-        for signature in signatures {
-            let signature_str = signature.to_string();
+        for signature in signatures.iter().copied() {
             let client = self.client.clone();
+            let commitment = self.commitment;
             let future = async move {
-                match Signature::from_str(&signature_str) {
+                let result = match Signature::from_str(signature) {
                     Ok(sig) => {
                         let config = RpcTransactionConfig {
                             encoding: Some(UiTransactionEncoding::Json),
-                            commitment: None,
+                            commitment,
                             max_supported_transaction_version: Some(0),
                         };
                         match client.get_transaction_with_config(&sig, config).await {
                             Ok(transaction) => Ok(transaction),
-                            Err(e) => Err(format!(
+                            Err(e) => Err(TradeError::RpcError(format!(
                                 "get transaction error for {}: {:?}",
-                                signature_str, e
-                            )),
+                                signature, e
+                            ))),
                         }
                     }
-                    Err(_) => Err(format!("invalid signature: {}", signature_str)),
-                }
+                    Err(_) => Err(TradeError::InvalidSignature(signature.to_string())),
+                };
+                (signature, result)
             };
             futures.push(future);
         }
-        let results = join_all(futures).await;
-        let mut successful_transactions = Vec::new();
-        for result in results {
-            match result {
-                Ok(tx) => successful_transactions.push(tx),
-                Err(e) => {
-                    eprintln!("Transaction query error: {}", e);
-                }
-            }
-        }
-        Ok(successful_transactions)
+        join_all(futures).await
     }
 
     /// get transaction details
@@ -617,7 +1207,7 @@ impl Trade {
     /// signature - transaction signature hash string
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let solana = Solana::new(solana_trader::types::Mode::DEV).unwrap();
     /// let trade = solana.create_trade();
     /// let transaction_info = trade.get_transaction_details("transaction signature").await;
@@ -625,24 +1215,22 @@ impl Trade {
     pub async fn get_transaction_display_details(
         &self,
         signature: &str,
-    ) -> UnifiedResult<TransactionInfo, String> {
+    ) -> Result<TransactionInfo, TradeError> {
         Ok(TransactionInfo::from_encoded_transaction(
-            &self
-                .get_transaction_details(signature)
-                .await
-                .map_err(|e| format!("get transaction details error {:?}", e))
-                .unwrap(),
+            &self.get_transaction_details(signature).await?,
             signature,
         ))
     }
 
     /// get transaction details in batch
+    /// signatures that are invalid or fail to load are skipped,
+    /// every returned info keeps the signature it was fetched with
     ///
     /// # params
     /// signatures - transaction signature hash string array
     ///
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let solana = Solana::new(solana_trader::types::Mode::DEV).unwrap();
     /// let trade = solana.create_trade();
     /// let transaction_infos = trade.get_transaction_display_details_batch(&["signature1", "signature2"]).await;
@@ -650,23 +1238,13 @@ impl Trade {
     pub async fn get_transaction_display_details_batch(
         &self,
         signatures: Vec<&str>,
-    ) -> UnifiedResult<Vec<TransactionInfo>, String> {
-        let raw_transactions = self
-            .get_transaction_details_batch(signatures.clone())
-            .await
-            .map_err(|e| format!("get batch transaction details error: {}", e))
-            .unwrap();
-        let transaction_infos = raw_transactions
-            .iter()
-            .enumerate()
-            .filter_map(|(i, tx)| {
-                if i < signatures.len() {
-                    Some(TransactionInfo::from_encoded_transaction(tx, signatures[i]))
-                } else {
-                    None
-                }
-            })
-            .collect();
+    ) -> Result<Vec<TransactionInfo>, TradeError> {
+        let mut transaction_infos = Vec::new();
+        for (signature, result) in self.get_transaction_details_batch(signatures).await? {
+            if let Ok(tx) = result {
+                transaction_infos.push(TransactionInfo::from_encoded_transaction(&tx, &signature));
+            }
+        }
         Ok(transaction_infos)
     }
 }
@@ -674,8 +1252,784 @@ impl Trade {
 #[cfg(test)]
 mod tests {
     use crate::Solana;
+    use crate::fixtures::{
+        accept_transactions, fixture_rpc, fixture_solana, latest_blockhash, mock_solana,
+        preflight_failure, sent_transaction, signature_statuses,
+    };
+    use crate::global::COMPUTE_BUDGET_PROGRAM_ID;
+    use crate::mock::MockRpc;
+    use crate::types::{TradeError, TxOptions};
+    use futures::StreamExt;
+    use serde_json::{Value, json};
+    use solana_client::rpc_request::RpcRequest;
+    use solana_sdk::hash::Hash;
+    use solana_sdk::instruction::Instruction;
+    use solana_sdk::message::Message;
+    use solana_sdk::pubkey::Pubkey;
+    use solana_sdk::signature::{Keypair, Signature, Signer};
+    use solana_sdk::transaction::{Transaction, TransactionError};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[tokio::test]
+    async fn test_trade_errors_are_typed() {
+        let trade = mock_solana(&MockRpc::new()).create_trade();
+        assert_eq!(
+            trade.get_transaction_details("not-a-signature").await.err(),
+            Some(TradeError::InvalidSignature("not-a-signature".to_string()))
+        );
+        assert_eq!(
+            trade
+                .get_transactions_history_filtered("bad address", |_| true)
+                .await
+                .err(),
+            Some(TradeError::InvalidAddress("bad address".to_string()))
+        );
+        assert!(matches!(
+            trade
+                .get_transactions_history_by_cursor(
+                    "So11111111111111111111111111111111111111112",
+                    Some("bad cursor".to_string()),
+                    10
+                )
+                .await,
+            Err(TradeError::InvalidSignature(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_simulate() {
+        let program_id = solana_sdk::pubkey::Pubkey::new_unique();
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::SimulateTransaction,
+            json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "err": null,
+                    "logs": [format!("Program {} invoke [1]", program_id)],
+                    "accounts": null,
+                    "unitsConsumed": 1234,
+                    "returnData": { "programId": program_id.to_string(), "data": ["AQID", "base64"] }
+                }
+            }),
+        );
+        let trade = mock_solana(&rpc).create_trade();
+        let payer = solana_sdk::pubkey::Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(program_id, &[], vec![]);
+        let tx = Transaction::new_unsigned(Message::new(&[instruction], Some(&payer)));
+        let simulation = trade.simulate(&tx).await.unwrap();
+        assert_eq!(simulation.err, None);
+        assert_eq!(simulation.logs.len(), 1);
+        assert_eq!(simulation.units_consumed, Some(1234));
+        assert_eq!(
+            simulation.return_data,
+            Some((program_id.to_string(), vec![1, 2, 3]))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_estimate_fee_for() {
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetFeeForMessage,
+            json!({ "context": { "slot": 1 }, "value": 15_000 }),
+        );
+        let trade = mock_solana(&rpc).create_trade();
+        let payer = Pubkey::new_unique();
+        let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+        let message =
+            Message::new_with_blockhash(&[instruction], Some(&payer), &Hash::new_unique());
+        assert_eq!(trade.estimate_fee_for(&message).await.unwrap(), 15_000);
+    }
+
+    #[tokio::test]
+    async fn test_send_with_fresh_blockhash_resigns_expired_transaction() {
+        let keypair = Keypair::new();
+        let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+        let fresh = Hash::new_unique();
+        // no priority fee, so only the instruction itself is signed
+        let landed = Transaction::new_signed_with_payer(
+            std::slice::from_ref(&instruction),
+            Some(&keypair.pubkey()),
+            &[&keypair],
+            fresh,
+        );
+        let landed = landed.signatures[0].to_string();
+        let hashes = AtomicUsize::new(0);
+        let rpc = MockRpc::new()
+            // the first hash is only valid until height 100, the chain is at 1234
+            .with_handler(RpcRequest::GetLatestBlockhash, move |_| {
+                Ok(match hashes.fetch_add(1, Ordering::Relaxed) {
+                    0 => latest_blockhash(Hash::new_unique(), 100),
+                    _ => latest_blockhash(fresh, 2_000),
+                })
+            })
+            .with_response(RpcRequest::GetBlockHeight, json!(1234));
+        accept_transactions(&rpc);
+        // only the transaction signed with the fresh hash lands
+        let status = landed.clone();
+        rpc.set_handler(RpcRequest::GetSignatureStatuses, move |params| {
+            Ok(signature_statuses(
+                (params[0][0] == status.as_str()).then_some("finalized"),
+            ))
+        });
+        let trade = mock_solana(&rpc).create_trade();
+        let opts = TxOptions {
+            priority_fee_micro_lamports: Some(0),
+            ..Default::default()
+        };
+        let signature = trade
+            .send_with_fresh_blockhash(std::slice::from_ref(&instruction), &keypair, &opts)
+            .await
+            .unwrap();
+        assert_eq!(signature, landed);
+        // landed but not yet finalized when the hash expires, it is waited for, not re-signed
+        let polls = AtomicUsize::new(0);
+        let rpc = MockRpc::new()
+            .with_response(RpcRequest::GetLatestBlockhash, latest_blockhash(fresh, 100))
+            .with_response(RpcRequest::GetBlockHeight, json!(1234))
+            .with_handler(RpcRequest::GetSignatureStatuses, move |_| {
+                Ok(signature_statuses(Some(
+                    match polls.fetch_add(1, Ordering::Relaxed) {
+                        0 => "processed",
+                        _ => "finalized",
+                    },
+                )))
+            });
+        accept_transactions(&rpc);
+        let signature = mock_solana(&rpc)
+            .create_trade()
+            .send_with_fresh_blockhash(std::slice::from_ref(&instruction), &keypair, &opts)
+            .await
+            .unwrap();
+        assert_eq!(signature, landed);
+        let requests = rpc.requests();
+        let count = |sent: RpcRequest| requests.iter().filter(|(r, _)| *r == sent).count();
+        assert_eq!(count(RpcRequest::SendTransaction), 1);
+        assert_eq!(count(RpcRequest::GetSignatureStatuses), 2);
+    }
+
+    /// send one instruction through `rpc` with up to 5 retries, returning the result, the
+    /// number of built transactions and the number of sent ones
+    async fn send_with_retry(rpc: &MockRpc) -> (Result<String, TradeError>, usize, usize) {
+        let keypair = Keypair::new();
+        let instruction = Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]);
+        let built = AtomicUsize::new(0);
+        let result = mock_solana(rpc)
+            .create_trade()
+            .send_and_confirm_with_retry(
+                |_| {
+                    built.fetch_add(1, Ordering::Relaxed);
+                    Transaction::new_with_payer(
+                        std::slice::from_ref(&instruction),
+                        Some(&keypair.pubkey()),
+                    )
+                },
+                &keypair,
+                &TxOptions {
+                    max_retries: 5,
+                    ..Default::default()
+                },
+            )
+            .await;
+        let sends = rpc
+            .requests()
+            .iter()
+            .filter(|(request, _)| *request == RpcRequest::SendTransaction)
+            .count();
+        (result, built.load(Ordering::Relaxed), sends)
+    }
+
+    #[tokio::test]
+    async fn test_send_and_confirm_with_retry_stops_on_other_errors() {
+        // the transaction is rejected by the node, which is not retried
+        let rpc = MockRpc::new()
+            .with_response(
+                RpcRequest::GetLatestBlockhash,
+                latest_blockhash(Hash::new_unique(), 2_000),
+            )
+            .with_handler(RpcRequest::SendTransaction, |_| {
+                Err(preflight_failure(TransactionError::InsufficientFundsForFee))
+            });
+        let (result, built, sends) = send_with_retry(&rpc).await;
+        assert!(matches!(
+            result,
+            Err(TradeError::RpcError(e)) if e.contains("InsufficientFundsForFee")
+        ));
+        assert_eq!((built, sends), (1, 1));
+    }
+
+    #[tokio::test]
+    async fn test_send_and_confirm_with_retry_retries_unknown_blockhash() {
+        let sends = AtomicUsize::new(0);
+        // the first node has not seen the hash yet, the second accepts the transaction
+        let rpc = MockRpc::new()
+            .with_response(
+                RpcRequest::GetLatestBlockhash,
+                latest_blockhash(Hash::new_unique(), 2_000),
+            )
+            .with_response(
+                RpcRequest::GetSignatureStatuses,
+                signature_statuses(Some("finalized")),
+            )
+            .with_handler(RpcRequest::SendTransaction, move |params| {
+                match sends.fetch_add(1, Ordering::Relaxed) {
+                    0 => Err(preflight_failure(TransactionError::BlockhashNotFound)),
+                    _ => Ok(json!(sent_transaction(params).signatures[0].to_string())),
+                }
+            });
+        let (result, built, sends) = send_with_retry(&rpc).await;
+        assert!(result.is_ok());
+        assert_eq!((built, sends), (2, 2));
+    }
+
+    #[tokio::test]
+    async fn test_send_and_confirm_with_retry_keeps_landed_transaction() {
+        let polls = AtomicUsize::new(0);
+        // the hash expires (valid until 100, the chain is at 1234) while the transaction is
+        // unseen, it shows up when polled again and finalizes later
+        let rpc = MockRpc::new()
+            .with_response(
+                RpcRequest::GetLatestBlockhash,
+                latest_blockhash(Hash::new_unique(), 100),
+            )
+            .with_response(RpcRequest::GetBlockHeight, json!(1234))
+            .with_handler(RpcRequest::GetSignatureStatuses, move |_| {
+                Ok(signature_statuses(
+                    match polls.fetch_add(1, Ordering::Relaxed) {
+                        0 => None,
+                        1 => Some("confirmed"),
+                        _ => Some("finalized"),
+                    },
+                ))
+            });
+        accept_transactions(&rpc);
+        let (result, built, sends) = send_with_retry(&rpc).await;
+        assert_eq!((built, sends), (1, 1));
+        let (_, sent) = rpc
+            .requests()
+            .into_iter()
+            .find(|(request, _)| *request == RpcRequest::SendTransaction)
+            .unwrap();
+        assert_eq!(
+            result.unwrap(),
+            sent_transaction(&sent).signatures[0].to_string()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_transaction_details_batch_keeps_signatures() {
+        let found = Signature::from([1u8; 64]).to_string();
+        let missing = Signature::from([2u8; 64]).to_string();
+        let trade = fixture_solana(&[(found.as_str(), "sol_transfer")]).create_trade();
+        let signatures = vec!["not a signature", missing.as_str(), found.as_str()];
+        let results = trade
+            .get_transaction_details_batch(signatures.clone())
+            .await
+            .unwrap();
+        let returned: Vec<&str> = results.iter().map(|(s, _)| s.as_str()).collect();
+        assert_eq!(returned, signatures);
+        assert!(matches!(results[0].1, Err(TradeError::InvalidSignature(_))));
+        assert!(results[1].1.is_err());
+        assert!(results[2].1.is_ok());
+        // failures before a success do not shift the signature onto the wrong transaction
+        let infos = trade
+            .get_transaction_display_details_batch(signatures)
+            .await
+            .unwrap();
+        assert_eq!(infos.len(), 1);
+        assert_eq!(infos[0].signature, found);
+    }
+
+    #[tokio::test]
+    async fn test_get_transaction_details_optional() {
+        let signature = Signature::from([1u8; 64]).to_string();
+        let trade = fixture_solana(&[(signature.as_str(), "sol_transfer")]).create_trade();
+        assert!(
+            trade
+                .get_transaction_details_optional(&signature)
+                .await
+                .unwrap()
+                .is_some()
+        );
+        let rpc = MockRpc::new();
+        rpc.set_response(RpcRequest::GetTransaction, Value::Null);
+        let trade = mock_solana(&rpc).create_trade();
+        assert!(
+            trade
+                .get_transaction_details_optional(&signature)
+                .await
+                .unwrap()
+                .is_none()
+        );
+        // not found is still an error for the non optional variant
+        let rpc = MockRpc::new();
+        rpc.set_response(RpcRequest::GetTransaction, Value::Null);
+        let trade = mock_solana(&rpc).create_trade();
+        assert!(trade.get_transaction_details(&signature).await.is_err());
+        let rpc = MockRpc::new();
+        rpc.set_response(RpcRequest::GetTransaction, json!("not a transaction"));
+        let trade = mock_solana(&rpc).create_trade();
+        assert!(matches!(
+            trade.get_transaction_details_optional(&signature).await,
+            Err(TradeError::RpcError(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_compute_budget_instructions() {
+        let rpc = MockRpc::new().with_response(
+            RpcRequest::GetRecentPrioritizationFees,
+            json!([
+                { "slot": 100, "prioritizationFee": 5_000 },
+                { "slot": 101, "prioritizationFee": 10_000 },
+                { "slot": 102, "prioritizationFee": 20_000 }
+            ]),
+        );
+        let trade = mock_solana(&rpc).create_trade();
+        let opts = TxOptions {
+            priority_fee_micro_lamports: Some(5_000),
+            compute_unit_limit: Some(200_000),
+            ..Default::default()
+        };
+        let instructions = trade.compute_budget_instructions(&opts).await.unwrap();
+        assert_eq!(instructions.len(), 2);
+        assert!(instructions.iter().all(
+            |i| i.program_id.to_string() == COMPUTE_BUDGET_PROGRAM_ID && i.accounts.is_empty()
+        ));
+        assert_eq!(
+            instructions[0].data,
+            [&[2u8][..], &200_000u32.to_le_bytes()].concat()
+        );
+        assert_eq!(
+            instructions[1].data,
+            [&[3u8][..], &5_000u64.to_le_bytes()].concat()
+        );
+        // the default pays the median of the recent prioritization fees
+        let instructions = trade
+            .compute_budget_instructions(&TxOptions::default())
+            .await
+            .unwrap();
+        assert_eq!(instructions.len(), 1);
+        assert_eq!(
+            instructions[0].data,
+            [&[3u8][..], &10_000u64.to_le_bytes()].concat()
+        );
+        let opts = TxOptions {
+            priority_fee_micro_lamports: Some(0),
+            ..Default::default()
+        };
+        assert!(
+            trade
+                .compute_budget_instructions(&opts)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_recipient_and_payer_strict_returns_limit_matches() {
+        let recipient = "3Fav7BV2tWLjwB9kBHnbmb6ibehugwuvimSMCUgwyNbR";
+        let payer = "Ao7YT34edAyDLWfWvzBdDDNksLzooC2hWzPK33hZfxwE";
+        let signatures: Vec<String> = (1..=5u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        // the newest entry mentions the payer but was paid by a relayer, so only the loose check passes
+        let trade = fixture_solana(&[
+            (&signatures[0], "token_payment_relayed"),
+            (&signatures[1], "token_payment"),
+            (&signatures[2], "token_payment"),
+            (&signatures[3], "token_payment"),
+            (&signatures[4], "token_payment"),
+        ])
+        .create_trade();
+        let matches = trade
+            .get_transactions_by_recipient_and_payer_strict(recipient, payer, 3)
+            .await
+            .unwrap();
+        let matched: Vec<&str> = matches.iter().map(|t| t.signature.as_str()).collect();
+        assert_eq!(
+            matched,
+            vec![
+                signatures[1].as_str(),
+                signatures[2].as_str(),
+                signatures[3].as_str()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_token_recipient_is_not_the_fee_payer() {
+        use crate::fixtures::load_transaction_info;
+        // the sender pays the fee and creates the recipient's token account
+        let info = load_transaction_info("token_transfer_reordered_balances");
+        assert!(info.balance_change < 0);
+        assert!(info.is_recipient("7r7S7kAg8oTQh8kBHaFg8fzxydi5xAKZeqH9nWAruuKq"));
+        assert!(!info.is_recipient("7wMGttEwvNh5Kt7EXMbumyspEQHo5Hy7ehy76UXT2b1U"));
+        assert!(!info.is_recipient("3Np2hzKtsF68bY8jJ7JZfn17EaimVZijtmVdTJbbkJXX"));
+    }
+
+    #[tokio::test]
+    async fn test_get_flow_between() {
+        let x = "Ao7YT34edAyDLWfWvzBdDDNksLzooC2hWzPK33hZfxwE";
+        let y = "3Fav7BV2tWLjwB9kBHnbmb6ibehugwuvimSMCUgwyNbR";
+        let signatures: Vec<String> = (1..=4u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        let history = [
+            (signatures[0].as_str(), "sol_payment_reverse"),
+            (signatures[1].as_str(), "token_payment_relayed"),
+            (signatures[2].as_str(), "sol_payment_recent"),
+            (signatures[3].as_str(), "sol_payment_recent"),
+        ];
+        let trade = fixture_solana(&history).create_trade();
+        let flow = trade.get_flow_between(x, y, 10).await.unwrap();
+        let payment = crate::fixtures::load_transaction_info("sol_payment_recent")
+            .value
+            .parse::<u64>()
+            .unwrap();
+        assert!(payment > 0);
+        assert_eq!(flow.x_to_y, 2 * payment);
+        assert_eq!(flow.y_to_x, payment);
+        assert_eq!(flow.net_flow, payment as i128);
+        let x_to_y: Vec<&str> = flow
+            .x_to_y_transactions
+            .iter()
+            .map(|t| t.signature.as_str())
+            .collect();
+        assert_eq!(x_to_y, vec![signatures[2].as_str(), signatures[3].as_str()]);
+        assert_eq!(flow.y_to_x_transactions[0].signature, signatures[0]);
+        // the limit counts matches from both directions
+        let trade = fixture_solana(&history).create_trade();
+        let flow = trade.get_flow_between(x, y, 2).await.unwrap();
+        assert_eq!(
+            flow.x_to_y_transactions.len() + flow.y_to_x_transactions.len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_total_payment_amount_stops_early() {
+        let recipient = "3Fav7BV2tWLjwB9kBHnbmb6ibehugwuvimSMCUgwyNbR";
+        let payer = "Ao7YT34edAyDLWfWvzBdDDNksLzooC2hWzPK33hZfxwE";
+        let signatures: Vec<String> = (1..=5u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        let history = [
+            (signatures[0].as_str(), "sol_payment_recent"),
+            (signatures[1].as_str(), "sol_payment_recent"),
+            (signatures[2].as_str(), "sol_payment_recent"),
+            (signatures[3].as_str(), "sol_payment_old"),
+            (signatures[4].as_str(), "sol_payment_old"),
+        ];
+        // one signature page plus one transaction fetch per scanned payment
+        let trade = fixture_solana(&history).create_trade();
+        assert_eq!(
+            trade
+                .get_total_payment_amount(recipient, payer, None, None)
+                .await
+                .unwrap(),
+            // three recent payments of 0.001 SOL and two old ones of 0.003 SOL
+            3 * 1_000_000 + 2 * 3_000_000
+        );
+        assert_eq!(trade.client.get_transport_stats().request_count, 1 + 5);
+        let trade = fixture_solana(&history).create_trade();
+        assert_eq!(
+            trade
+                .get_total_payment_amount(recipient, payer, None, Some(2))
+                .await
+                .unwrap(),
+            2 * 1_000_000
+        );
+        assert_eq!(trade.client.get_transport_stats().request_count, 1 + 2);
+        // the window only covers the recent payments, the old ones are never fetched
+        let trade = fixture_solana(&history).create_trade();
+        let now = crate::tool::now_unix_secs();
+        let window = now - 1_760_660_400 + 3600;
+        assert_eq!(
+            trade
+                .get_total_payment_amount(recipient, payer, Some(window), None)
+                .await
+                .unwrap(),
+            3 * 1_000_000
+        );
+        assert_eq!(trade.client.get_transport_stats().request_count, 1 + 3);
+    }
+
+    #[tokio::test]
+    async fn test_transactions_containing_address_keep_history_order() {
+        let pool_authority = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+        let signatures: Vec<String> = (1..=6u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        let trade = fixture_solana(&[
+            (&signatures[0], "raydium_v4_swap_buy"),
+            (&signatures[1], "sol_transfer"),
+            (&signatures[2], "raydium_v4_swap_failed"),
+            (&signatures[3], "orca_whirlpool_swap"),
+            (&signatures[4], "raydium_v4_pool_create"),
+            (&signatures[5], "token_payment"),
+        ])
+        .create_trade();
+        let expected = vec![
+            signatures[0].as_str(),
+            signatures[2].as_str(),
+            signatures[4].as_str(),
+        ];
+        for concurrency in [0, 1, 4, 16] {
+            let matches = trade
+                .get_transactions_vec_containing_address_with_concurrency(
+                    "5dQ9CmV1yyM7zZMGErPCPi8pCBXLmMdCTUTXFekvNjtT",
+                    pool_authority,
+                    concurrency,
+                )
+                .await
+                .unwrap();
+            let matched: Vec<&str> = matches.iter().map(|t| t.signature.as_str()).collect();
+            assert_eq!(matched, expected, "concurrency {}", concurrency);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_history_scans_stop_at_max_signatures() {
+        let signatures: Vec<String> = (1..=5u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        let history: Vec<(&str, &str)> = signatures
+            .iter()
+            .map(|s| (s.as_str(), "sol_transfer"))
+            .collect();
+        let address = "5dQ9CmV1yyM7zZMGErPCPi8pCBXLmMdCTUTXFekvNjtT";
+        let rpc = fixture_rpc(&history);
+        let trade = mock_solana(&rpc).create_trade();
+        assert_eq!(trade.max_signatures(), super::DEFAULT_MAX_SIGNATURES);
+        let all = trade
+            .get_transactions_history_filtered(address, |_| true)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 5);
+        assert!(!all.truncated);
+        let trade = trade.with_max_signatures(2);
+        let earlier = rpc.requests().len();
+        let capped = trade
+            .get_transactions_history_filtered(address, |_| true)
+            .await
+            .unwrap();
+        assert!(capped.truncated);
+        let read: Vec<&str> = capped.iter().map(|s| s.signature.as_str()).collect();
+        assert_eq!(read, vec![signatures[0].as_str(), signatures[1].as_str()]);
+        // the cap also bounds the page size, nothing past it is requested
+        let requests = &rpc.requests()[earlier..];
+        assert_eq!(requests.len(), 1);
+        assert_eq!(requests[0].1[1]["limit"], 2);
+        let contains = trade
+            .get_transactions_vec_containing_address(address, &signatures[0])
+            .await
+            .unwrap();
+        assert!(contains.truncated);
+    }
+
+    #[tokio::test]
+    async fn test_signatures_stream_pages_lazily() {
+        let signatures: Vec<String> = (1..=5u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        let history: Vec<(&str, &str)> = signatures
+            .iter()
+            .map(|s| (s.as_str(), "sol_transfer"))
+            .collect();
+        let address = "5dQ9CmV1yyM7zZMGErPCPi8pCBXLmMdCTUTXFekvNjtT";
+        // pages of 2, 2 and 1, the short last page ends the stream
+        let trade = fixture_solana(&history).create_trade();
+        let streamed: Vec<String> = trade
+            .signatures_stream(address, 2)
+            .map(|s| s.unwrap().signature)
+            .collect()
+            .await;
+        assert_eq!(streamed, signatures);
+        assert_eq!(trade.client.get_transport_stats().request_count, 3);
+        // stopping early only fetches the pages that were needed
+        let trade = fixture_solana(&history).create_trade();
+        let first: Vec<_> = trade.signatures_stream(address, 2).take(3).collect().await;
+        assert_eq!(first.len(), 3);
+        assert_eq!(trade.client.get_transport_stats().request_count, 2);
+        let errors: Vec<_> = trade.signatures_stream("bad address", 2).collect().await;
+        assert!(matches!(
+            errors.as_slice(),
+            [Err(TradeError::InvalidAddress(_))]
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_display_details_batch_skips_failed_signatures() {
+        let signatures: Vec<String> = (1..=3u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        let trade = fixture_solana(&[
+            (&signatures[0], "sol_transfer"),
+            (&signatures[1], "raydium_v4_swap_buy"),
+        ])
+        .create_trade();
+        // an invalid signature and one the rpc does not know
+        let infos = trade
+            .get_transaction_display_details_batch(vec![
+                &signatures[0],
+                "not a signature",
+                &signatures[2],
+                &signatures[1],
+            ])
+            .await
+            .unwrap();
+        assert_eq!(
+            infos
+                .iter()
+                .map(|info| (info.signature.as_str(), info.is_swap()))
+                .collect::<Vec<_>>(),
+            vec![
+                (signatures[0].as_str(), false),
+                (signatures[1].as_str(), true)
+            ]
+        );
+        assert!(
+            trade
+                .get_transaction_display_details(&signatures[2])
+                .await
+                .is_err()
+        );
+        assert!(matches!(
+            trade
+                .get_transaction_display_details("not a signature")
+                .await,
+            Err(TradeError::InvalidSignature(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_first_transaction() {
+        let signatures: Vec<String> = (1..=5u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        let mut history: Vec<(&str, &str)> = signatures
+            .iter()
+            .map(|s| (s.as_str(), "sol_transfer"))
+            .collect();
+        history[4].1 = "token_payment";
+        let address = "5dQ9CmV1yyM7zZMGErPCPi8pCBXLmMdCTUTXFekvNjtT";
+        let trade = fixture_solana(&history).create_trade();
+        let first = trade
+            .first_transaction_with_page_size(address, None, 2)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.signature, signatures[4]);
+        assert_eq!(
+            first.block_time,
+            crate::fixtures::load_fixture("token_payment").block_time
+        );
+        assert_eq!(trade.client.get_transport_stats().request_count, 3);
+        // the cap returns the oldest signature seen so far
+        let trade = fixture_solana(&history).create_trade();
+        let first = trade
+            .first_transaction_with_page_size(address, Some(2), 2)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.signature, signatures[3]);
+        assert_eq!(trade.client.get_transport_stats().request_count, 2);
+        // a history that ends on a full page is followed by an empty one
+        let trade = fixture_solana(&history[..4]).create_trade();
+        let first = trade
+            .first_transaction_with_page_size(address, None, 2)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(first.signature, signatures[3]);
+        let trade = fixture_solana(&[]).create_trade();
+        assert!(
+            trade
+                .get_first_transaction(address, None)
+                .await
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_get_transactions_in_slot_range() {
+        let signatures: Vec<String> = (1..=6u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        // newest first, like the rpc returns them
+        let fixtures = [
+            "pump_amm_sell",
+            "meteora_dlmm_swap",
+            "raydium_clmm_swap",
+            "bubblegum_transfer",
+            "sol_transfer",
+            "sol_payment_old",
+        ];
+        let history: Vec<(&str, &str)> = signatures
+            .iter()
+            .map(String::as_str)
+            .zip(fixtures)
+            .collect();
+        let address = "5dQ9CmV1yyM7zZMGErPCPi8pCBXLmMdCTUTXFekvNjtT";
+        let trade = fixture_solana(&history).create_trade();
+        let in_range = trade
+            .slot_range_with_page_size(address, 372_126_000, 372_200_000, 2)
+            .await
+            .unwrap();
+        let in_range: Vec<&str> = in_range.iter().map(|s| s.signature.as_str()).collect();
+        assert_eq!(in_range, [&signatures[1], &signatures[2], &signatures[3]]);
+        // stopped at sol_transfer, the page holding sol_payment_old is never fetched
+        assert_eq!(trade.client.get_transport_stats().request_count, 3);
+        let trade = fixture_solana(&history).create_trade();
+        assert!(
+            trade
+                .get_transactions_in_slot_range(address, 400_000_000, 500_000_000)
+                .await
+                .unwrap()
+                .is_empty()
+        );
+        let trade = fixture_solana(&history).create_trade();
+        assert_eq!(
+            trade
+                .get_transactions_in_slot_range(address, 0, u64::MAX)
+                .await
+                .unwrap()
+                .len(),
+            6
+        );
+    }
+
+    #[tokio::test]
+    async fn test_history_by_cursor_past_the_end_is_empty() {
+        let signatures: Vec<String> = (1..=2u8)
+            .map(|i| Signature::from([i; 64]).to_string())
+            .collect();
+        let history: Vec<(&str, &str)> = signatures
+            .iter()
+            .map(|s| (s.as_str(), "sol_transfer"))
+            .collect();
+        let address = "5dQ9CmV1yyM7zZMGErPCPi8pCBXLmMdCTUTXFekvNjtT";
+        let trade = fixture_solana(&history).create_trade();
+        let (page, cursor) = trade
+            .get_transactions_history_by_cursor(address, None, 2)
+            .await
+            .unwrap();
+        assert_eq!(page.len(), 2);
+        assert_eq!(cursor.as_deref(), Some(signatures[1].as_str()));
+        let (page, cursor) = trade
+            .get_transactions_history_by_cursor(address, cursor, 2)
+            .await
+            .unwrap();
+        assert!(page.is_empty());
+        assert_eq!(cursor, None);
+    }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_get_transaction_display_details_batch() -> Result<(), ()> {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let trade = solana.create_trade();
@@ -711,6 +2065,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_parse_trade_info() -> Result<(), ()> {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let trade = solana.create_trade();
@@ -720,6 +2075,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_get_token_quote_ratio() -> Result<(), ()> {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let trade = solana.create_trade();
@@ -729,6 +2085,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_is_dbc_trade() -> Result<(), ()> {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let trade = solana.create_trade();
@@ -738,6 +2095,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_is_pump_trade() -> Result<(), ()> {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let trade = solana.create_trade();
@@ -747,6 +2105,7 @@ mod tests {
     }
 
     #[tokio::test]
+    #[cfg_attr(not(feature = "network-tests"), ignore)]
     async fn test_is_raylaunchpad_trade() -> Result<(), ()> {
         let solana = Solana::new(crate::types::Mode::MAIN).unwrap();
         let trade = solana.create_trade();
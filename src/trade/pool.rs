@@ -1,6 +1,16 @@
 use crate::trade::info::TransactionInfo;
-use crate::global::{SOL, USD_1, USDC, USDT, WSOL};
-use crate::types::Direction;
+use crate::global::{
+    METEORA_DAMM_V2_PROGRAM_ID, METEORA_DLMM_V2_PROGRAM_ID, METEORA_POOL_PROGRAM_ID,
+    ORCA_WHIRLPOOLS_PROGRAM_ID, PUMP_AAM_PROGRAM_ID, RAYDIUM_CLMM_POOL_PROGRAM_ID, RAYDIUM_CPMM_POOL_PROGRAM_ID,
+    QUOTES, RAYDIUM_V4_POOL_PROGRAM_ID, SOL, SYSTEM_PROGRAM_ID, USD_1, USDC, USDT, WSOL,
+};
+use crate::tool::{raw_to_ui, raw_to_ui_signed};
+use crate::types::{Direction, Price, TransactionType};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+/// one side of a swap as (mint, amount in token units)
+type SwapLeg = (String, f64);
 
 impl TransactionInfo {
     /// Get the final settlement quote token address (considering aggregator swaps)
@@ -31,73 +41,82 @@ impl TransactionInfo {
         if self.has_token(USDT) {
             return USDT.to_string();
         }
+        if let Some(quote) = self.extra_quotes.iter().find(|mint| self.has_token(mint)) {
+            return quote.clone();
+        }
         SOL.to_string()
     }
     
+    /// Get the signer's net token balance change per mint (in lamports/raw units)
+    /// Positive means received, negative means spent; mints are ordered by first appearance
+    /// Memoized until the signer, fee payer or token balances change
+    pub fn net_token_changes(&self) -> Vec<(String, i64)> {
+        self.net_token_changes_cache
+            .get_or_compute(self.net_token_changes_fingerprint(), || {
+                let signer_address = if !self.signer.is_empty() {
+                    &self.signer
+                } else if !self.fee_payer.is_empty() {
+                    &self.fee_payer
+                } else {
+                    return Vec::new();
+                };
+                let mut changes: Vec<(String, i64)> = Vec::new();
+                for account in self.token_account_changes() {
+                    if account.owner.as_ref() != Some(signer_address) {
+                        continue;
+                    }
+                    match changes.iter_mut().find(|(mint, _)| *mint == account.mint) {
+                        Some((_, change)) => *change += account.delta as i64,
+                        None => changes.push((account.mint, account.delta as i64)),
+                    }
+                }
+                changes
+            })
+    }
+
+    /// hash of the inputs of `net_token_changes`
+    fn net_token_changes_fingerprint(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        (&self.signer, &self.fee_payer).hash(&mut hasher);
+        for balances in [&self.raw_pre_token_balances, &self.raw_post_token_balances] {
+            balances.len().hash(&mut hasher);
+            for balance in balances {
+                (
+                    balance.account_index,
+                    &balance.mint,
+                    &balance.owner,
+                    &balance.ui_token_amount.amount,
+                )
+                    .hash(&mut hasher);
+            }
+        }
+        hasher.finish()
+    }
+
     /// Get the token that the signer finally received (considering aggregator trades)
     fn get_final_received_token(&self) -> Option<(String, u64)> {
-        let signer_address = if !self.signer.is_empty() {
-            &self.signer
-        } else if !self.fee_payer.is_empty() {
-            &self.fee_payer
-        } else {
-            return None;
-        };
-        let mut max_received_token: Option<String> = None;
-        let mut max_received_amount = 0u64;
-        for post_balance in &self.raw_post_token_balances {
-            if post_balance.owner.as_ref() != Some(signer_address) {
-                continue;
-            }
-            let pre_amount = self.raw_pre_token_balances
-                .iter()
-                .find(|pre| pre.mint == post_balance.mint && pre.owner.as_ref() == Some(signer_address))
-                .and_then(|pre| pre.ui_token_amount.amount.parse::<u64>().ok())
-                .unwrap_or(0);
-            let post_amount = post_balance.ui_token_amount.amount.parse::<u64>().unwrap_or(0);
-            if post_amount > pre_amount {
-                let increase = post_amount - pre_amount;
-                if increase > max_received_amount {
-                    max_received_amount = increase;
-                    max_received_token = Some(post_balance.mint.clone());
-                }
+        let mut max_received: Option<(String, u64)> = None;
+        for (mint, change) in self.net_token_changes() {
+            let is_max = max_received.as_ref().is_none_or(|(_, max)| change as u64 > *max);
+            if change > 0 && is_max {
+                max_received = Some((mint, change as u64));
             }
         }
-        max_received_token.map(|token| (token, max_received_amount))
+        max_received
     }
-    
+
     /// Get the token that the signer finally spent (considering aggregator trades)
     fn get_final_spent_token(&self) -> Option<(String, u64)> {
-        let signer_address = if !self.signer.is_empty() {
-            &self.signer
-        } else if !self.fee_payer.is_empty() {
-            &self.fee_payer
-        } else {
-            return None;
-        };
-        let mut max_spent_token: Option<String> = None;
-        let mut max_spent_amount = 0u64;
-        for pre_balance in &self.raw_pre_token_balances {
-            if pre_balance.owner.as_ref() != Some(signer_address) {
-                continue;
-            }
-            let post_amount = self.raw_post_token_balances
-                .iter()
-                .find(|post| post.mint == pre_balance.mint && post.owner.as_ref() == Some(signer_address))
-                .and_then(|post| post.ui_token_amount.amount.parse::<u64>().ok())
-                .unwrap_or(0);
-            let pre_amount = pre_balance.ui_token_amount.amount.parse::<u64>().unwrap_or(0);
-            if pre_amount > post_amount {
-                let decrease = pre_amount - post_amount;
-                if decrease > max_spent_amount {
-                    max_spent_amount = decrease;
-                    max_spent_token = Some(pre_balance.mint.clone());
-                }
+        let mut max_spent: Option<(String, u64)> = None;
+        for (mint, change) in self.net_token_changes() {
+            let is_max = max_spent.as_ref().is_none_or(|(_, max)| change.unsigned_abs() > *max);
+            if change < 0 && is_max {
+                max_spent = Some((mint, change.unsigned_abs()));
             }
         }
-        max_spent_token.map(|token| (token, max_spent_amount))
+        max_spent
     }
-    
+
     /// Check if there's SOL or WSOL activity in the transaction
     fn has_sol_or_wsol_activity(&self) -> bool {
         if self.has_sol_activity() {
@@ -126,13 +145,20 @@ impl TransactionInfo {
             .any(|b| b.mint == mint)
     }
     
-    /// Check if a token is a quote token (including WSOL)
-    fn is_quote_token(&self, mint: &str) -> bool {
-        mint == SOL || 
-        mint == WSOL || // WSOL
-        mint == USDC || 
-        mint == USDT || 
-        mint == USD_1
+    /// Check if a token is a quote token (including WSOL and any extra quotes)
+    pub(crate) fn is_quote_token(&self, mint: &str) -> bool {
+        QUOTES.contains(&mint) || self.extra_quotes.iter().any(|quote| quote == mint)
+    }
+
+    /// Recognize `mints` as quote tokens in addition to `QUOTES` (e.g. PYUSD, USDe, EURC)
+    /// Affects quote/base detection, the quote ratio and the swap direction
+    pub fn with_extra_quotes(mut self, mints: &[&str]) -> Self {
+        for mint in mints {
+            if !self.is_quote_token(mint) {
+                self.extra_quotes.push(mint.to_string());
+            }
+        }
+        self
     }
     
     /// Get all tokens involved in the transaction
@@ -176,6 +202,9 @@ impl TransactionInfo {
     /// Calculate signer's base token balance change (in token units with decimals)
     /// Positive means received base tokens, negative means spent base tokens
     pub fn get_signer_base_token_change_decimal(&self) -> Option<f64> {
+        if self.is_failed() {
+            return Some(0.0);
+        }
         if let Some(base_token) = self.get_pool_base_token_address() {
             self.get_signer_token_balance_change_decimal(&base_token)
         } else {
@@ -186,10 +215,19 @@ impl TransactionInfo {
     /// Calculate signer's quote token balance change (in token units with decimals)
     /// Positive means received quote tokens, negative means spent quote tokens
     pub fn get_signer_quote_token_change_decimal(&self) -> Option<f64> {
+        if self.is_failed() {
+            return Some(0.0);
+        }
         let quote_token = self.get_pool_quote_token_address().unwrap_or("".to_string());
         match quote_token.as_str() {
-            SOL | WSOL => {
-                Some(self.get_signer_net_sol_income_sol())
+            SOL => Some(self.get_signer_net_sol_income_sol()),
+            WSOL => {
+                // WSOL is a token account: use its balance change net of the SOL
+                // wrapped into and unwrapped out of it in the same transaction
+                match self.get_signer_wsol_change_lamports() {
+                    0 => Some(self.get_signer_net_sol_income_sol()),
+                    change => Some(raw_to_ui_signed(change, 9)),
+                }
             }
             _ => {
                 self.get_signer_token_balance_change_decimal(&quote_token)
@@ -199,6 +237,9 @@ impl TransactionInfo {
     
     /// Calculate signer's base token balance change (in lamports/raw units)
     pub fn get_signer_base_token_change_lamports(&self) -> i64 {
+        if self.is_failed() {
+            return 0;
+        }
         if let Some(base_token) = self.get_pool_base_token_address() {
             self.get_signer_token_balance_change_lamports(&base_token)
         } else {
@@ -208,61 +249,47 @@ impl TransactionInfo {
     
     /// Calculate signer's quote token balance change (in lamports/raw units)
     pub fn get_signer_quote_token_change_lamports(&self) -> i64 {
+        if self.is_failed() {
+            return 0;
+        }
         let quote_token = self.get_pool_quote_token_address().unwrap_or("".to_string());
         match quote_token.as_str() {
             SOL => self.get_signer_net_sol_income_lamports(),
+            WSOL => match self.get_signer_wsol_change_lamports() {
+                0 => self.get_signer_net_sol_income_lamports(),
+                change => change,
+            },
             _ => self.get_signer_token_balance_change_lamports(&quote_token),
         }
     }
+
+    /// Get signer's WSOL balance change with wrapped SOL subtracted and unwrapped SOL added back
+    fn get_signer_wsol_change_lamports(&self) -> i64 {
+        self.get_signer_token_balance_change_lamports(WSOL)
+            - self.wsol_wrapped().unwrap_or(0) as i64
+            + self.wsol_unwrapped().unwrap_or(0) as i64
+    }
     
     /// Get signer's token balance change for a specific mint address (in lamports/raw units)
     fn get_signer_token_balance_change_lamports(&self, mint: &str) -> i64 {
-        let signer_address = if !self.signer.is_empty() {
-            &self.signer
-        } else if !self.fee_payer.is_empty() {
-            &self.fee_payer
-        } else {
-            return 0;
-        };
-        let pre_amount = self.raw_pre_token_balances
-            .iter()
-            .find(|b| b.mint == mint && b.owner.as_ref() == Some(signer_address))
-            .and_then(|b| b.ui_token_amount.amount.parse::<u64>().ok())
-            .unwrap_or(0);
-        let post_amount = self.raw_post_token_balances
+        self.net_token_changes()
             .iter()
-            .find(|b| b.mint == mint && b.owner.as_ref() == Some(signer_address))
-            .and_then(|b| b.ui_token_amount.amount.parse::<u64>().ok())
-            .unwrap_or(0);
-        post_amount as i64 - pre_amount as i64
+            .find(|(m, _)| m == mint)
+            .map(|(_, change)| *change)
+            .unwrap_or(0)
     }
-    
+
     /// Get signer's token balance change for a specific mint address (in token units with decimals)
-   fn get_signer_token_balance_change_decimal(&self, mint: &str) -> Option<f64> {
-        let signer_address = if !self.signer.is_empty() {
-            &self.signer
-        } else if !self.fee_payer.is_empty() {
-            &self.fee_payer
-        } else {
+    fn get_signer_token_balance_change_decimal(&self, mint: &str) -> Option<f64> {
+        if self.signer.is_empty() && self.fee_payer.is_empty() {
             return None;
-        };
+        }
         let decimals = self.get_token_decimals(mint)?;
-        let pre_amount = self.raw_pre_token_balances
-            .iter()
-            .find(|b| b.mint == mint && b.owner.as_ref() == Some(signer_address))
-            .and_then(|b| b.ui_token_amount.amount.parse::<u64>().ok())
-            .unwrap_or(0);
-        let post_amount = self.raw_post_token_balances
-            .iter()
-            .find(|b| b.mint == mint && b.owner.as_ref() == Some(signer_address))
-            .and_then(|b| b.ui_token_amount.amount.parse::<u64>().ok())
-            .unwrap_or(0);
-        let change = post_amount as f64 - pre_amount as f64;
-        Some(change / 10_u64.pow(decimals as u32) as f64)
+        Some(raw_to_ui_signed(self.get_signer_token_balance_change_lamports(mint), decimals))
     }
 
     /// Get token decimals for a specific mint
-    fn get_token_decimals(&self, mint: &str) -> Option<u8> {
+    pub(crate) fn get_token_decimals(&self, mint: &str) -> Option<u8> {
         // First look in token balances
         for balance in self.raw_pre_token_balances.iter().chain(&self.raw_post_token_balances) {
             if balance.mint == mint {
@@ -278,24 +305,71 @@ impl TransactionInfo {
     }
     
     /// Determine if signer is buying or selling base token
+    ///
+    /// Decision order:
+    /// 1. the legs are the token the signer finally spent and the token it finally received,
+    ///    native SOL stands in for a side where no token moved
+    /// 2. if exactly one leg is a quote token (`QUOTES` or `extra_quotes`), spending it is a buy and receiving it is a sell
+    /// 3. otherwise the leg with the larger USD value is treated as the quote, stablecoins count as 1 USD
+    /// 4. anything else is `Direction::Unknown`
+    ///
+    /// Failed transactions are always `Direction::Unknown`
     pub fn get_direction(&self) -> Direction {
-        if let (Some(base_change), Some(quote_change)) = (
-            self.get_signer_base_token_change_decimal(),
-            self.get_signer_quote_token_change_decimal()
-        ) {
-            match (base_change, quote_change) {
-                (base, quote) if base > 0.0 && quote < 0.0 => Direction::Buy,  // Received base, spent quote
-                (base, quote) if base < 0.0 && quote > 0.0 => Direction::Sell, // Spent base, received quote
-                _ => Direction::Unknown,
-            }
-        } else {
-            Direction::Unknown
+        self.get_direction_with_prices(|_| None)
+    }
+
+    /// Same as `get_direction`, with `usd_price` supplying USD prices per mint (e.g. from Pyth)
+    /// for swaps where neither or both legs are quote tokens
+    pub fn get_direction_with_prices<F>(&self, usd_price: F) -> Direction
+    where
+        F: Fn(&str) -> Option<f64>,
+    {
+        if self.is_failed() {
+            return Direction::Unknown;
+        }
+        let (spent, received) = match self.get_swap_legs() {
+            (Some(spent), Some(received)) => (spent, received),
+            _ => return Direction::Unknown,
+        };
+        match (self.is_quote_token(&spent.0), self.is_quote_token(&received.0)) {
+            (true, false) => return Direction::Buy,
+            (false, true) => return Direction::Sell,
+            _ => {}
+        }
+        let usd_value = |(mint, amount): &(String, f64)| match mint.as_str() {
+            USDC | USDT | USD_1 => Some(*amount),
+            _ => usd_price(mint).map(|price| price * amount),
+        };
+        match (usd_value(&spent), usd_value(&received)) {
+            (Some(spent), Some(received)) if spent > received => Direction::Buy,
+            (Some(spent), Some(received)) if received > spent => Direction::Sell,
+            _ => Direction::Unknown,
         }
     }
+
+    /// The signer's (spent, received) legs
+    fn get_swap_legs(&self) -> (Option<SwapLeg>, Option<SwapLeg>) {
+        let net_sol = self.get_signer_net_sol_income_sol();
+        let leg = |token: Option<(String, u64)>, sol_amount: f64| match token {
+            Some((mint, raw)) => {
+                let decimals = self.get_token_decimals(&mint)?;
+                Some((mint, raw_to_ui(raw, decimals)))
+            }
+            None if sol_amount > 0.0 => Some((SOL.to_string(), sol_amount)),
+            None => None,
+        };
+        (
+            leg(self.get_final_spent_token(), -net_sol),
+            leg(self.get_final_received_token(), net_sol),
+        )
+    }
     
     /// Get aggregator swap path information
     pub fn get_aggregator_path_info(&self) -> Vec<SwapStep> {
         let mut steps = Vec::new();
+        if self.is_failed() {
+            return steps;
+        }
         for log in &self.raw_log_messages {
             if log.contains("Swap") && log.contains("for") && log.contains("on") {
                 // Parse swap log, e.g.: "Swap 50,132.799581 fih for 175.275832 USD1 on Raydium CPMM"
@@ -386,19 +460,75 @@ impl TransactionInfo {
         None
     }
 
-    /// Calculate the token quote ratio (price): quote token amount per base token unit
-    /// Returns the price in quote tokens per 1 base token
-    /// Formula: price = abs(quote_change) / abs(base_change)
-    pub fn get_token_quote_ratio(&self) -> Option<f64> {
-        let base_change = self.get_signer_base_token_change_decimal()?;
-        let quote_change = self.get_signer_quote_token_change_decimal()?;
-        let base_abs = base_change.abs();
-        let quote_abs = quote_change.abs();
+    /// Get the owners of the token accounts the signer traded against (pools, vaults, route hops)
+    /// An owner is a counterparty when one of its accounts moved opposite to the signer's change of that mint
+    /// Ordered by account index; the signer, fee payer and system program are excluded
+    pub fn counterparties(&self) -> Vec<String> {
+        let signer_changes = self.net_token_changes();
+        let mut accounts = self.token_account_changes();
+        accounts.sort_by_key(|account| account.account_index);
+        let mut counterparties: Vec<String> = Vec::new();
+        for account in accounts {
+            let Some(owner) = &account.owner else {
+                continue;
+            };
+            if owner == &self.signer || owner == &self.fee_payer || owner == SYSTEM_PROGRAM_ID || counterparties.contains(owner) {
+                continue;
+            }
+            let Some((_, signer_change)) = signer_changes.iter().find(|(mint, _)| mint == &account.mint) else {
+                continue;
+            };
+            if account.delta.signum() * (*signer_change as i128).signum() < 0 {
+                counterparties.push(owner.clone());
+            }
+        }
+        counterparties
+    }
+
+    /// Get the execution price of the trade with its base and quote mints
+    /// Formula: price = abs(quote_change) / abs(base_change), in quote tokens per 1 base token
+    /// PumpSwap and pump.fun bond curve trades use the amounts of their trade events instead of balance changes,
+    /// multi-hop jupiter routes the net input and output of the route (see `route_hops`)
+    pub fn get_price(&self) -> Option<Price> {
+        let price = |base_mint: &str, base_raw: u64, quote_mint: &str, quote_raw: u64| {
+            let quote_decimals = self.get_token_decimals(quote_mint)?;
+            let base = raw_to_ui(base_raw, self.get_token_decimals(base_mint)?);
+            let quote = raw_to_ui(quote_raw, quote_decimals);
+            (base > 0.0 && quote > 0.0).then(|| Price {
+                base_mint: base_mint.to_string(),
+                quote_mint: quote_mint.to_string(),
+                price: quote / base,
+                quote_decimals,
+            })
+        };
+        if let Some(pump_amm) = self.get_pump_amm_transaction_info() {
+            return price(&pump_amm.base_mint, pump_amm.base_amount, &pump_amm.quote_mint, pump_amm.quote_amount);
+        }
+        if let Some(pump) = self.get_pump_trade_event() {
+            return price(&pump.mint, pump.token_amount, SOL, pump.quote_amount());
+        }
+        if let Some(route) = self.jupiter_route_price() {
+            return Some(route);
+        }
+        let base_mint = self.get_pool_base_token_address()?;
+        let quote_mint = self.get_pool_quote_token_address()?;
+        let base_abs = self.get_signer_base_token_change_decimal()?.abs();
+        let quote_abs = self.get_signer_quote_token_change_decimal()?.abs();
         if base_abs <= 0.0 || quote_abs <= 0.0 {
             return None;
         }
-        let price = quote_abs / base_abs;
-        Some(price)
+        Some(Price {
+            quote_decimals: self.get_token_decimals(&quote_mint)?,
+            base_mint,
+            quote_mint,
+            price: quote_abs / base_abs,
+        })
+    }
+
+    /// Calculate the token quote ratio (price): quote token amount per base token unit
+    /// Returns the price in quote tokens per 1 base token, see `get_price` for the mints it is denominated in
+    pub fn get_token_quote_ratio(&self) -> Option<f64> {
+        self.get_price().map(|price| price.price)
     }
     
     /// Get formatted price as string (for display purposes)
@@ -409,13 +539,33 @@ impl TransactionInfo {
 
     /// Get the token that the signer actually received (with amount in lamports)
     /// Returns a tuple of (token_address, amount_in_lamports)
+    /// PumpSwap, pump.fun bond curve and raydium clmm trades are read from their events
     pub fn get_received_token_sol(&self) -> Option<(String, u64)> {
+        if let Some(pump_amm) = self.get_pump_amm_transaction_info() {
+            return Some(pump_amm.received_token());
+        }
+        if let Some(pump) = self.get_pump_trade_event() {
+            return Some(pump.received_token());
+        }
+        if let Some((_, received)) = self.raydium_clmm_swap_tokens() {
+            return Some(received);
+        }
         self.get_final_received_token()
     }
 
     /// Get the token that the signer actually spent (with amount in lamports)
     /// Returns a tuple of (token_address, amount_in_lamports)
+    /// PumpSwap, pump.fun bond curve and raydium clmm trades are read from their events
     pub fn get_spent_token_sol(&self) -> Option<(String, u64)> {
+        if let Some(pump_amm) = self.get_pump_amm_transaction_info() {
+            return Some(pump_amm.spent_token());
+        }
+        if let Some(pump) = self.get_pump_trade_event() {
+            return Some(pump.spent_token());
+        }
+        if let Some((spent, _)) = self.raydium_clmm_swap_tokens() {
+            return Some(spent);
+        }
         self.get_final_spent_token()
     }
 
@@ -432,6 +582,139 @@ impl TransactionInfo {
         self.get_spent_token_sol()
             .map(|(token_address, _)| token_address)
     }
+
+    /// Get the program id of the DEX whose pool-initialization instruction ran in this transaction
+    /// Returns None when the transaction does not create a liquidity pool
+    pub fn get_pool_creation_program_id(&self) -> Option<String> {
+        let mut invoke_stack: Vec<&str> = Vec::new();
+        for log in &self.logs {
+            if let Some(rest) = log.strip_prefix("Program ") {
+                if let Some((program_id, _)) = rest.split_once(" invoke [") {
+                    invoke_stack.push(program_id);
+                    continue;
+                }
+                if rest.ends_with(" success") || rest.contains(" failed") {
+                    invoke_stack.pop();
+                    continue;
+                }
+            }
+            let (Some(message), Some(&program_id)) =
+                (log.strip_prefix("Program log: "), invoke_stack.last())
+            else {
+                continue;
+            };
+            let is_init = POOL_CREATION_INSTRUCTIONS.iter().any(|(id, name)| {
+                *id == program_id
+                    && (message == *name
+                        || message.starts_with(&format!("{}:", name))
+                        || message.starts_with(&format!("{} ", name)))
+            });
+            if is_init {
+                return Some(program_id.to_string());
+            }
+        }
+        None
+    }
+
+    /// Check if the transaction creates a liquidity pool
+    pub fn is_pool_creation(&self) -> bool {
+        self.get_pool_creation_program_id().is_some()
+    }
+
+    /// Check if a liquidity change moved only one of the pool's tokens
+    /// (e.g. a concentrated position outside the current price)
+    /// Returns None when the transaction is not an add/remove liquidity
+    pub fn is_single_sided_liquidity(&self) -> Option<bool> {
+        let deposit = match self.transaction_type {
+            Some(TransactionType::AddLiquidity) => true,
+            Some(TransactionType::RemoveLiquidity) => false,
+            _ => return None,
+        };
+        let moved = self
+            .net_token_changes()
+            .iter()
+            .filter(|(_, change)| if deposit { *change < 0 } else { *change > 0 })
+            .count();
+        Some(moved == 1)
+    }
+
+    /// Get the initial reserves deposited into a newly created pool
+    /// The pool vaults are the token accounts created by this transaction (no pre balance)
+    /// and not owned by the signer; base is the non-quote mint of the two vaults
+    pub fn get_pool_initial_reserves(&self) -> Option<PoolReserves> {
+        if !self.is_pool_creation() {
+            return None;
+        }
+        let signer_address = if !self.signer.is_empty() {
+            &self.signer
+        } else {
+            &self.fee_payer
+        };
+        let vaults: Vec<_> = self
+            .raw_post_token_balances
+            .iter()
+            .filter(|b| {
+                b.owner.as_ref() != Some(signer_address)
+                    && !self
+                        .raw_pre_token_balances
+                        .iter()
+                        .any(|pre| pre.account_index == b.account_index)
+            })
+            .filter(|b| b.ui_token_amount.amount.parse::<u64>().unwrap_or(0) > 0)
+            .collect();
+        let quote = vaults.iter().find(|b| self.is_quote_token(&b.mint))?;
+        let base = vaults
+            .iter()
+            .find(|b| b.mint != quote.mint && !self.is_quote_token(&b.mint))
+            .or_else(|| vaults.iter().find(|b| b.mint != quote.mint))?;
+        let to_decimal =
+            |amount: &str, decimals: u8| raw_to_ui(amount.parse::<u64>().unwrap_or(0), decimals);
+        Some(PoolReserves {
+            base_mint: base.mint.clone(),
+            quote_mint: quote.mint.clone(),
+            base_reserve: to_decimal(&base.ui_token_amount.amount, base.ui_token_amount.decimals),
+            quote_reserve: to_decimal(&quote.ui_token_amount.amount, quote.ui_token_amount.decimals),
+        })
+    }
+
+    /// Get the initial price of a newly created pool: quote reserve per 1 base token
+    pub fn get_pool_initial_price(&self) -> Option<f64> {
+        let reserves = self.get_pool_initial_reserves()?;
+        if reserves.base_reserve <= 0.0 {
+            return None;
+        }
+        Some(reserves.quote_reserve / reserves.base_reserve)
+    }
+}
+
+/// Pool-initialization instruction logs per DEX program
+const POOL_CREATION_INSTRUCTIONS: [(&str, &str); 17] = [
+    (RAYDIUM_V4_POOL_PROGRAM_ID, "initialize2"),
+    (PUMP_AAM_PROGRAM_ID, "Instruction: CreatePool"),
+    (RAYDIUM_CPMM_POOL_PROGRAM_ID, "Instruction: Initialize"),
+    (RAYDIUM_CPMM_POOL_PROGRAM_ID, "Instruction: InitializeWithPermission"),
+    (RAYDIUM_CLMM_POOL_PROGRAM_ID, "Instruction: CreatePool"),
+    (ORCA_WHIRLPOOLS_PROGRAM_ID, "Instruction: InitializePool"),
+    (ORCA_WHIRLPOOLS_PROGRAM_ID, "Instruction: InitializePoolV2"),
+    (METEORA_DLMM_V2_PROGRAM_ID, "Instruction: InitializeLbPair"),
+    (METEORA_DLMM_V2_PROGRAM_ID, "Instruction: InitializeLbPair2"),
+    (METEORA_DLMM_V2_PROGRAM_ID, "Instruction: InitializeCustomizablePermissionlessLbPair"),
+    (METEORA_DLMM_V2_PROGRAM_ID, "Instruction: InitializeCustomizablePermissionlessLbPair2"),
+    (METEORA_DAMM_V2_PROGRAM_ID, "Instruction: InitializePool"),
+    (METEORA_DAMM_V2_PROGRAM_ID, "Instruction: InitializeCustomizablePool"),
+    (METEORA_DAMM_V2_PROGRAM_ID, "Instruction: InitializePoolWithDynamicConfig"),
+    (METEORA_POOL_PROGRAM_ID, "Instruction: InitializePermissionlessPool"),
+    (METEORA_POOL_PROGRAM_ID, "Instruction: InitializePermissionlessConstantProductPoolWithConfig"),
+    (METEORA_POOL_PROGRAM_ID, "Instruction: InitializePermissionlessConstantProductPoolWithConfig2"),
+];
+
+/// Initial reserves of a newly created pool (in token units with decimals)
+#[derive(Debug, Clone)]
+pub struct PoolReserves {
+    pub base_mint: String,
+    pub quote_mint: String,
+    pub base_reserve: f64,
+    pub quote_reserve: f64,
 }
 
 /// Swap step information
@@ -455,4 +738,276 @@ pub struct TokenInfo {
     pub direction: Direction,
     pub price: Option<f64>,
     pub aggregator_path: Vec<SwapStep>,
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::load_transaction_info;
+    use crate::trade::info::RawTokenBalance;
+
+    fn fixtures() -> Vec<TransactionInfo> {
+        vec![
+            load_transaction_info("raydium_v4_swap_buy"),
+            load_transaction_info("pump_bond_curve_buy"),
+            load_transaction_info("raydium_v4_pool_create"),
+            load_transaction_info("jupiter_route_sell"),
+            load_transaction_info("sol_transfer"),
+        ]
+    }
+
+    /// signer token change computed by walking the balances directly, as before the cache
+    fn walk_signer_token_change(info: &TransactionInfo, mint: &str) -> i64 {
+        let amount_of = |balances: &[RawTokenBalance]| {
+            balances
+                .iter()
+                .find(|b| b.mint == mint && b.owner.as_ref() == Some(&info.signer))
+                .and_then(|b| b.ui_token_amount.amount.parse::<u64>().ok())
+                .unwrap_or(0)
+        };
+        amount_of(&info.raw_post_token_balances) as i64 - amount_of(&info.raw_pre_token_balances) as i64
+    }
+
+    fn walk_final_token(info: &TransactionInfo, received: bool) -> Option<(String, u64)> {
+        let mut max: Option<(String, u64)> = None;
+        for mint in info.get_all_involved_tokens() {
+            let change = walk_signer_token_change(info, &mint);
+            let amount = if received { change } else { -change };
+            if amount > 0 && max.as_ref().is_none_or(|(_, m)| amount as u64 > *m) {
+                max = Some((mint, amount as u64));
+            }
+        }
+        max
+    }
+
+    #[test]
+    fn test_net_token_changes_match_balance_walk() {
+        for info in fixtures() {
+            for mint in info.get_all_involved_tokens() {
+                assert_eq!(
+                    info.get_signer_token_balance_change_lamports(&mint),
+                    walk_signer_token_change(&info, &mint)
+                );
+            }
+            assert_eq!(info.get_received_token_sol(), walk_final_token(&info, true));
+            assert_eq!(info.get_spent_token_sol(), walk_final_token(&info, false));
+            // memoized result is stable across calls
+            assert_eq!(info.net_token_changes(), info.clone().net_token_changes());
+        }
+    }
+
+    #[test]
+    fn test_net_token_changes_follow_their_inputs() {
+        let mut info = load_transaction_info("raydium_v4_swap_buy");
+        assert_eq!(info.get_direction(), Direction::Buy);
+        let bought = info.get_received_token_sol();
+        // the same swap seen from the pool authority's side
+        info.signer = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1".to_string();
+        assert_eq!(info.get_direction(), Direction::Sell);
+        assert_eq!(info.get_spent_token_sol(), bought);
+        assert_eq!(info.clone().net_token_changes(), info.net_token_changes());
+        // a balance edited after the first call, the vault no longer paid out the token
+        let vault = info
+            .raw_post_token_balances
+            .iter_mut()
+            .find(|balance| balance.account_index == 2)
+            .unwrap();
+        vault.ui_token_amount.amount = "150000000000000".to_string();
+        assert_eq!(
+            info.get_signer_token_balance_change_lamports(
+                "6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau"
+            ),
+            0
+        );
+    }
+
+    #[test]
+    fn test_raydium_swap_fixture() {
+        let info = load_transaction_info("raydium_v4_swap_buy");
+        assert!(info.is_swap());
+        assert_eq!(info.get_direction(), Direction::Buy);
+        assert_eq!(
+            info.get_pool_base_token_address().as_deref(),
+            Some("6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau")
+        );
+        assert_eq!(info.get_pool_quote_token_address().as_deref(), Some(WSOL));
+        assert_eq!(
+            info.get_pool_address().as_deref(),
+            Some("5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1")
+        );
+        assert_eq!(
+            info.get_received_token_sol(),
+            Some(("6nD8eK4qYH6LMmUVwAgLmKdVSTGqhw97wvyVcsVVLEau".to_string(), 1_234_567_890_000))
+        );
+        assert_eq!(info.get_spent_token_sol(), Some((WSOL.to_string(), 1_500_000_000)));
+        assert_eq!(info.get_signer_base_token_change_lamports(), 1_234_567_890_000);
+        assert!(info.get_token_quote_ratio().is_some());
+    }
+
+    #[test]
+    fn test_pump_bond_curve_buy_fixture() {
+        let info = load_transaction_info("pump_bond_curve_buy");
+        assert!(info.is_swap());
+        assert_eq!(info.get_direction(), Direction::Buy);
+        assert_eq!(
+            info.get_pool_base_token_address().as_deref(),
+            Some("tdDKPtF1TJTe1JxVwCYgXrX17EhBvXhLrp2HJ5FQJsU")
+        );
+        assert_eq!(info.get_pool_quote_token_address().as_deref(), Some(SOL));
+        assert_eq!(
+            info.get_pool_address().as_deref(),
+            Some("AzrfcWRu6dCVMTo9tvChPShXKvqPetUZAmkF6ouMHysp")
+        );
+        assert_eq!(info.get_spent_token_sol(), None);
+        // 0.5 SOL into the curve plus the 1% fee and the new token account rent
+        let ratio = info.get_token_quote_ratio().unwrap();
+        let expected = (500_000_000.0 + 5_000_000.0 + 2_039_280.0) / 1e9 / 17_000_000.0;
+        assert!((ratio - expected).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_jupiter_route_sell_fixture() {
+        let info = load_transaction_info("jupiter_route_sell");
+        assert!(info.is_swap());
+        assert_eq!(info.get_direction(), Direction::Sell);
+        assert_eq!(
+            info.get_pool_base_token_address().as_deref(),
+            Some("FxUiMKkyNwrvAm3ZBxEf9chdERWkX6DwYYzHqz51CXPz")
+        );
+        assert_eq!(info.get_pool_quote_token_address().as_deref(), Some(USDC));
+        assert_eq!(info.get_received_token_address().as_deref(), Some(USDC));
+        // 10000 tokens sold for 120 USDC
+        let ratio = info.get_token_quote_ratio().unwrap();
+        assert!((ratio - 0.012).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_counterparties() {
+        assert_eq!(
+            load_transaction_info("raydium_v4_swap_buy").counterparties(),
+            vec!["5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1"]
+        );
+        // both hops of the route: the pool taking the token and the pool paying out USDC
+        assert_eq!(
+            load_transaction_info("jupiter_route_sell").counterparties(),
+            vec![
+                "Am6eXhBRmBkEq6GuTR71tqYgzuPj76FkfDyycwq8W9pT",
+                "AncZgQyinCorKqMT1QSN7BFfpfgahKkrQAXwXPq6LMrT"
+            ]
+        );
+        assert_eq!(
+            load_transaction_info("orca_whirlpool_swap").counterparties(),
+            vec!["D5WhC6g8KSKXyhE2gUAJXw8eR5YWbSBrGag3zstBbXdh"]
+        );
+        assert!(load_transaction_info("sol_transfer").counterparties().is_empty());
+    }
+
+    #[test]
+    fn test_sol_transfer_fixture() {
+        let info = load_transaction_info("sol_transfer");
+        assert!(!info.is_swap());
+        assert_eq!(info.get_direction(), Direction::Unknown);
+        assert_eq!(info.get_pool_base_token_address(), None);
+        assert_eq!(info.get_pool_address(), None);
+        assert_eq!(info.get_token_quote_ratio(), None);
+        assert_eq!(info.get_signer_net_sol_income_lamports(), -1_500_000_000);
+    }
+
+    #[test]
+    fn test_token_to_token_direction() {
+        let info = load_transaction_info("token_to_token_swap");
+        let (sold, bought) = (
+            "H2mxRwCPTiYf3GR6ayNYykfu5u8fstcyfmVHHY4CNeBt",
+            "8XVrSZkyeJnHfMmpzUNv5JQgA199qznRiunt2DhRUr4x",
+        );
+        // neither leg is a quote token and no prices are known
+        assert_eq!(info.get_direction(), Direction::Unknown);
+        // 1,000 sold tokens worth $10 for 50 bought tokens worth $50: the bought token is the quote
+        let prices = |mint: &str| match mint {
+            m if m == sold => Some(0.01),
+            m if m == bought => Some(1.0),
+            _ => None,
+        };
+        assert_eq!(info.get_direction_with_prices(prices), Direction::Sell);
+        let prices = |mint: &str| match mint {
+            m if m == sold => Some(1.0),
+            m if m == bought => Some(0.01),
+            _ => None,
+        };
+        assert_eq!(info.get_direction_with_prices(prices), Direction::Buy);
+    }
+
+    #[test]
+    fn test_wsol_quote_ignores_ata_rent() {
+        // buy paid from a held WSOL account, the signer also pays rent for the new token account
+        let info = load_transaction_info("raydium_v4_swap_buy_new_ata");
+        assert_eq!(info.get_pool_quote_token_address().as_deref(), Some(WSOL));
+        assert!(info.is_quote_token(WSOL));
+        assert_eq!(info.get_token_decimals(WSOL), Some(9));
+        assert_eq!(info.get_signer_quote_token_change_lamports(), -2_000_000_000);
+        assert_eq!(info.get_signer_quote_token_change_decimal(), Some(-2.0));
+        assert_eq!(
+            info.get_pool_base_token_address().as_deref(),
+            Some("B2FqYRbPsFmrNNbW5bXgmnfmmQD57vD2MzyA1Yv6v6Ek")
+        );
+        assert_eq!(info.get_direction(), Direction::Buy);
+        let ratio = info.get_token_quote_ratio().unwrap();
+        assert!((ratio - 2.0 / 1_000_000.0).abs() < 1e-12);
+        // the rent is still visible in the native SOL change
+        assert_eq!(info.get_signer_net_sol_income_lamports(), -2_002_039_280);
+    }
+
+    #[test]
+    fn test_get_price_names_its_mints() {
+        let info = load_transaction_info("raydium_v4_swap_buy_new_ata");
+        let price = info.get_price().unwrap();
+        assert_eq!(price.base_mint, "B2FqYRbPsFmrNNbW5bXgmnfmmQD57vD2MzyA1Yv6v6Ek");
+        assert_eq!(price.quote_mint, WSOL);
+        assert_eq!(price.quote_decimals, 9);
+        assert!((price.price - 2.0 / 1_000_000.0).abs() < 1e-12);
+        assert_eq!(info.get_token_quote_ratio(), Some(price.price));
+        // bond curve trades are quoted in native SOL
+        let info = load_transaction_info("pump_bond_curve_sell");
+        let price = info.get_price().unwrap();
+        assert_eq!(price.quote_mint, SOL);
+        assert_eq!(price.base_mint, info.get_pump_trade_event().unwrap().mint);
+        assert!(load_transaction_info("sol_transfer").get_price().is_none());
+    }
+
+    #[test]
+    fn test_extra_quotes() {
+        let (sold, bought) = (
+            "H2mxRwCPTiYf3GR6ayNYykfu5u8fstcyfmVHHY4CNeBt",
+            "8XVrSZkyeJnHfMmpzUNv5JQgA199qznRiunt2DhRUr4x",
+        );
+        let info = load_transaction_info("token_to_token_swap");
+        assert_eq!(info.get_pool_base_token_address().as_deref(), Some(sold));
+        assert_eq!(info.get_direction(), Direction::Unknown);
+        // treating the bought token as a stablecoin quote makes this a sell of the other token
+        let info = info.with_extra_quotes(&[bought, bought, WSOL]);
+        assert_eq!(info.extra_quotes, vec![bought.to_string()]);
+        assert_eq!(info.get_pool_quote_token_address().as_deref(), Some(bought));
+        assert_eq!(info.get_pool_base_token_address().as_deref(), Some(sold));
+        assert_eq!(info.get_direction(), Direction::Sell);
+        let ratio = info.get_token_quote_ratio().unwrap();
+        assert!((ratio - 50.0 / 1000.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_failed_swap_has_no_trade_amounts() {
+        let info = load_transaction_info("raydium_v4_swap_failed");
+        assert!(info.is_failed());
+        assert!(!info.is_successful());
+        assert!(info.is_swap);
+        assert_eq!(info.input_amount, None);
+        assert_eq!(info.output_amount, None);
+        // only the fee left the signer, it is not a quote amount
+        assert_eq!(info.fee, 105_000);
+        assert_eq!(info.get_signer_quote_token_change_lamports(), 0);
+        assert_eq!(info.get_signer_base_token_change_lamports(), 0);
+        assert_eq!(info.get_direction(), Direction::Unknown);
+        assert_eq!(info.get_token_quote_ratio(), None);
+        assert_eq!(info.get_received_token_sol(), None);
+        assert_eq!(info.get_spent_token_sol(), None);
+        assert!(info.get_aggregator_path_info().is_empty());
+    }
+}
@@ -0,0 +1,188 @@
+use solana_sdk::pubkey::Pubkey;
+
+use crate::global::{PUMP_AAM_PROGRAM_ID, WSOL};
+use crate::trade::info::TransactionInfo;
+use crate::types::Direction;
+
+/// anchor discriminator of the PumpSwap `BuyEvent`
+const BUY_EVENT_DISCRIMINATOR: [u8; 8] = [103, 244, 82, 31, 44, 245, 119, 119];
+/// anchor discriminator of the PumpSwap `SellEvent`
+const SELL_EVENT_DISCRIMINATOR: [u8; 8] = [62, 47, 55, 10, 165, 3, 220, 42];
+/// discriminator, timestamp, 13 u64 amounts, pool and user
+const EVENT_MIN_LEN: usize = 8 + 8 + 13 * 8 + 32 + 32;
+
+/// swap amounts of a PumpSwap (pump.fun AMM) trade, decoded from its `BuyEvent` / `SellEvent`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PumpAmmTransactionInfo {
+    pub direction: Direction, // Buy or Sell of the base token
+    pub timestamp: i64,
+    pub pool: String,
+    pub user: String,
+    pub base_mint: String,
+    pub quote_mint: String,
+    pub base_amount: u64,  // base tokens bought or sold (raw units)
+    pub quote_amount: u64, // quote tokens paid or received by the user, fees included (raw units)
+    pub lp_fee: u64,
+    pub protocol_fee: u64,
+    pub pool_base_token_reserves: u64, // pool reserves before the trade
+    pub pool_quote_token_reserves: u64,
+}
+
+impl PumpAmmTransactionInfo {
+    /// (token address, raw amount) the user spent
+    pub fn spent_token(&self) -> (String, u64) {
+        match self.direction {
+            Direction::Sell => (self.base_mint.clone(), self.base_amount),
+            _ => (self.quote_mint.clone(), self.quote_amount),
+        }
+    }
+
+    /// (token address, raw amount) the user received
+    pub fn received_token(&self) -> (String, u64) {
+        match self.direction {
+            Direction::Sell => (self.quote_mint.clone(), self.quote_amount),
+            _ => (self.base_mint.clone(), self.base_amount),
+        }
+    }
+}
+
+impl TransactionInfo {
+    /// Decode the first PumpSwap buy or sell of the transaction from its `Program data:` logs
+    /// Returns None for failed transactions and transactions without a PumpSwap trade
+    pub fn get_pump_amm_transaction_info(&self) -> Option<PumpAmmTransactionInfo> {
+        if self.is_failed() {
+            return None;
+        }
+        Self::program_data(&self.logs, PUMP_AAM_PROGRAM_ID)
+            .iter()
+            .find_map(|data| self.decode_pump_amm_event(data))
+    }
+
+    fn decode_pump_amm_event(&self, data: &[u8]) -> Option<PumpAmmTransactionInfo> {
+        if data.len() < EVENT_MIN_LEN {
+            return None;
+        }
+        let direction = match data[..8].try_into().ok()? {
+            BUY_EVENT_DISCRIMINATOR => Direction::Buy,
+            SELL_EVENT_DISCRIMINATOR => Direction::Sell,
+            _ => return None,
+        };
+        let u64_at = |index: usize| {
+            let offset = 16 + index * 8;
+            u64::from_le_bytes(data[offset..offset + 8].try_into().unwrap())
+        };
+        let pubkey_at = |offset: usize| {
+            Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap()).to_string()
+        };
+        // both events share the layout: base amount, limit, user reserves (2), pool reserves (2),
+        // quote amount, lp fee bps, lp fee, protocol fee bps, protocol fee, quote amount net of lp fee,
+        // user quote amount
+        let pool = pubkey_at(16 + 13 * 8);
+        let (base_mint, quote_mint) = self.pump_amm_pool_mints(&pool, direction);
+        Some(PumpAmmTransactionInfo {
+            direction,
+            timestamp: i64::from_le_bytes(data[8..16].try_into().unwrap()),
+            user: pubkey_at(16 + 13 * 8 + 32),
+            pool,
+            base_mint: base_mint?,
+            quote_mint: quote_mint.unwrap_or_else(|| WSOL.to_string()),
+            base_amount: u64_at(0),
+            quote_amount: u64_at(12),
+            lp_fee: u64_at(8),
+            protocol_fee: u64_at(10),
+            pool_base_token_reserves: u64_at(4),
+            pool_quote_token_reserves: u64_at(5),
+        })
+    }
+
+    /// (base mint, quote mint) of a PumpSwap pool, read from its vaults' balance changes:
+    /// a buy drains the base vault and fills the quote vault, a sell the opposite
+    fn pump_amm_pool_mints(
+        &self,
+        pool: &str,
+        direction: Direction,
+    ) -> (Option<String>, Option<String>) {
+        let (mut base_mint, mut quote_mint) = (None, None);
+        for account in self.token_account_changes() {
+            if account.owner.as_deref() != Some(pool) || account.delta == 0 {
+                continue;
+            }
+            let drained = match direction {
+                Direction::Sell => account.delta > 0,
+                _ => account.delta < 0,
+            };
+            if drained {
+                base_mint.get_or_insert(account.mint);
+            } else {
+                quote_mint.get_or_insert(account.mint);
+            }
+        }
+        (base_mint, quote_mint)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::load_transaction_info;
+    use crate::types::DexProgramType;
+
+    const MINT: &str = "9eU21qY11yiohyYTuWWfnP2Zgi7RZqMXgc1g1yMmLCgM";
+    const POOL: &str = "DVYn65Yt1Xr8XyutftZGnesnXWhXk6dGYhwRwjZqozRu";
+    const USER: &str = "AWNBeheFeCin3tv2nvMLTD5yZJ38YjiPSzsEvrBmnykk";
+
+    #[test]
+    fn test_pump_amm_buy() {
+        let info = load_transaction_info("pump_amm_buy");
+        assert_eq!(info.dex_program_type, Some(DexProgramType::PumpAAM));
+        let pump_amm = info.get_pump_amm_transaction_info().unwrap();
+        assert_eq!(pump_amm.direction, Direction::Buy);
+        assert_eq!(pump_amm.pool, POOL);
+        assert_eq!(pump_amm.user, USER);
+        assert_eq!(pump_amm.base_mint, MINT);
+        assert_eq!(pump_amm.quote_mint, WSOL);
+        assert_eq!(pump_amm.base_amount, 3_500_000_000_000);
+        assert_eq!(pump_amm.quote_amount, 100_250_000);
+        assert_eq!(pump_amm.lp_fee, 200_000);
+        assert_eq!(pump_amm.protocol_fee, 50_000);
+        // the wsol was wrapped into a temporary account, so no quote token balance moved
+        assert_eq!(
+            info.get_spent_token_sol(),
+            Some((WSOL.to_string(), 100_250_000))
+        );
+        assert_eq!(
+            info.get_received_token_sol(),
+            Some((MINT.to_string(), 3_500_000_000_000))
+        );
+        let ratio = info.get_token_quote_ratio().unwrap();
+        assert!((ratio - 0.10025 / 3_500_000.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_pump_amm_sell() {
+        let info = load_transaction_info("pump_amm_sell");
+        let pump_amm = info.get_pump_amm_transaction_info().unwrap();
+        assert_eq!(pump_amm.direction, Direction::Sell);
+        assert_eq!(pump_amm.base_mint, MINT);
+        assert_eq!(pump_amm.quote_mint, WSOL);
+        assert_eq!(pump_amm.base_amount, 1_000_000_000_000);
+        assert_eq!(pump_amm.quote_amount, 29_925_000);
+        assert_eq!(pump_amm.pool_base_token_reserves, 196_500_000_000_000);
+        assert_eq!(
+            info.get_spent_token_sol(),
+            Some((MINT.to_string(), 1_000_000_000_000))
+        );
+        assert_eq!(
+            info.get_received_token_sol(),
+            Some((WSOL.to_string(), 29_925_000))
+        );
+        let ratio = info.get_token_quote_ratio().unwrap();
+        assert!((ratio - 0.029925 / 1_000_000.0).abs() < 1e-15);
+    }
+
+    #[test]
+    fn test_non_pump_amm_transaction() {
+        let info = load_transaction_info("raydium_v4_swap_buy");
+        assert!(info.get_pump_amm_transaction_info().is_none());
+    }
+}
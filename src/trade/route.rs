@@ -0,0 +1,246 @@
+use crate::global::JUPITER_V6_PROGRAM_ID;
+use crate::trade::info::TransactionInfo;
+use crate::types::Price;
+
+/// one pool of a swap route, amounts in raw units
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hop {
+    /// owner of the pool vaults, the pool or its authority
+    pub pool: String,
+    /// token paid into the pool
+    pub input_mint: String,
+    pub input_amount: u64,
+    /// token paid out by the pool
+    pub output_mint: String,
+    pub output_amount: u64,
+}
+
+impl TransactionInfo {
+    /// Get the pools a swap routed through, in route order
+    /// Each hop is an owner, other than the signer, whose token balances went up in exactly one mint
+    /// and down in exactly one other; hops are ordered so every output feeds the next input
+    /// (when they do not chain they keep the order of the token balances)
+    /// Returns an empty Vec for failed transactions and transactions moving no pool tokens
+    pub fn route_hops(&self) -> Vec<Hop> {
+        if self.is_failed() {
+            return Vec::new();
+        }
+        let mut owners: Vec<&str> = Vec::new();
+        for balance in self
+            .raw_pre_token_balances
+            .iter()
+            .chain(&self.raw_post_token_balances)
+        {
+            if let Some(owner) = balance.owner.as_deref()
+                && owner != self.signer
+                && owner != self.fee_payer
+                && !owners.contains(&owner)
+            {
+                owners.push(owner);
+            }
+        }
+        let hops: Vec<Hop> = owners
+            .into_iter()
+            .filter_map(|owner| self.hop_of(owner))
+            .collect();
+        Self::chain_hops(hops)
+    }
+
+    /// (input mint, input amount, output mint, output amount) of the fee payer across the whole route:
+    /// the mints are the first hop's input and the last hop's output, the amounts what the fee payer
+    /// actually paid and received of them (see `fee_payer_swap_changes`)
+    /// None unless at least two hops chain and the fee payer lost the input and gained the output
+    pub(crate) fn route_net_amounts(&self) -> Option<(String, u64, String, u64)> {
+        let hops = self.route_hops();
+        if hops.len() < 2
+            || !hops
+                .windows(2)
+                .all(|pair| pair[0].output_mint == pair[1].input_mint)
+        {
+            return None;
+        }
+        let (first, last) = (hops.first()?, hops.last()?);
+        let changes = self.fee_payer_swap_changes();
+        let delta = |mint: &str| {
+            changes
+                .iter()
+                .find(|(changed, _, _)| changed == mint)
+                .map(|(_, delta, _)| *delta)
+        };
+        Some((
+            first.input_mint.clone(),
+            u64::try_from(-delta(&first.input_mint)?).ok()?,
+            last.output_mint.clone(),
+            u64::try_from(delta(&last.output_mint)?).ok()?,
+        ))
+    }
+
+    /// price of a jupiter route from its net input and output, ignoring the intermediate hops
+    pub(crate) fn jupiter_route_price(&self) -> Option<Price> {
+        if !self
+            .instructions
+            .iter()
+            .any(|instruction| instruction.program_id == JUPITER_V6_PROGRAM_ID)
+        {
+            return None;
+        }
+        let (input_mint, input_amount, output_mint, output_amount) = self.route_net_amounts()?;
+        // selling into a quote token or buying with one
+        let (base_mint, base_raw, quote_mint, quote_raw) = if self.is_quote_token(&output_mint) {
+            (input_mint, input_amount, output_mint, output_amount)
+        } else if self.is_quote_token(&input_mint) {
+            (output_mint, output_amount, input_mint, input_amount)
+        } else {
+            return None;
+        };
+        let quote_decimals = self.get_token_decimals(&quote_mint)?;
+        let base = crate::tool::raw_to_ui(base_raw, self.get_token_decimals(&base_mint)?);
+        let quote = crate::tool::raw_to_ui(quote_raw, quote_decimals);
+        (base > 0.0 && quote > 0.0).then(|| Price {
+            base_mint,
+            quote_mint,
+            price: quote / base,
+            quote_decimals,
+        })
+    }
+
+    /// hop of the pool whose vaults `owner` owns, None unless exactly one mint went in and one came out
+    fn hop_of(&self, owner: &str) -> Option<Hop> {
+        let changes = self.balance_changes_for_owner(owner);
+        let [(a, a_change, _), (b, b_change, _)] = changes.as_slice() else {
+            return None;
+        };
+        let ((input_mint, input), (output_mint, output)) = match (*a_change > 0, *b_change > 0) {
+            (true, false) => ((a, a_change), (b, b_change)),
+            (false, true) => ((b, b_change), (a, a_change)),
+            _ => return None,
+        };
+        Some(Hop {
+            pool: owner.to_string(),
+            input_mint: input_mint.to_string(),
+            input_amount: u64::try_from(*input).ok()?,
+            output_mint: output_mint.to_string(),
+            output_amount: u64::try_from(-output).ok()?,
+        })
+    }
+
+    /// order hops so each output is the next input, starting at the hop no other hop feeds
+    fn chain_hops(hops: Vec<Hop>) -> Vec<Hop> {
+        let Some(start) = hops
+            .iter()
+            .position(|hop| !hops.iter().any(|other| other.output_mint == hop.input_mint))
+        else {
+            return hops;
+        };
+        let mut remaining = hops.clone();
+        let mut chain = vec![remaining.remove(start)];
+        while let Some(next) = remaining
+            .iter()
+            .position(|hop| Some(&hop.input_mint) == chain.last().map(|last| &last.output_mint))
+        {
+            chain.push(remaining.remove(next));
+        }
+        if remaining.is_empty() { chain } else { hops }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hop;
+    use crate::fixtures::{load_fixture, load_transaction_info};
+    use crate::global::{USDC, USDT, WSOL};
+    use crate::trade::info::TransactionInfo;
+
+    const MINT: &str = "FrinYNv6U7pKFVetWjtgyvaKtkewrK7nozJGreJh6Vkx";
+
+    #[test]
+    fn test_route_hops() {
+        // token -> wsol on orca, wsol -> usdt on raydium clmm, usdt -> usdc on meteora dlmm
+        let info = load_transaction_info("jupiter_route_three_hops");
+        let hop = |pool: &str, input: (&str, u64), output: (&str, u64)| Hop {
+            pool: pool.to_string(),
+            input_mint: input.0.to_string(),
+            input_amount: input.1,
+            output_mint: output.0.to_string(),
+            output_amount: output.1,
+        };
+        assert_eq!(
+            info.route_hops(),
+            vec![
+                hop(
+                    "9LmTrZHBLJ5WknU3k5Bc7VxchnZNCtPMEqdLbfxEuuzv",
+                    (MINT, 10_000_000_000),
+                    (WSOL, 800_000_000)
+                ),
+                hop(
+                    "AZPe82VwhfWLX2ZBxwyDbcjsQenvnLUKg7wi9uBZ2S5R",
+                    (WSOL, 800_000_000),
+                    (USDT, 121_000_000)
+                ),
+                hop(
+                    "9iUkGSdx436BEt2kQKE6hKRhW2te7vrs2XipsY4YBQ3s",
+                    (USDT, 121_000_000),
+                    (USDC, 120_900_000)
+                ),
+            ]
+        );
+        assert_eq!(
+            info.route_net_amounts(),
+            Some((
+                MINT.to_string(),
+                10_000_000_000,
+                USDC.to_string(),
+                120_900_000
+            ))
+        );
+        let price = info.get_price().unwrap();
+        assert_eq!(price.base_mint, MINT);
+        assert_eq!(price.quote_mint, USDC);
+        assert!((price.price - 120.9 / 10_000.0).abs() < 1e-12);
+        // a single pool swap is one hop and is priced from the signer's balance changes
+        let info = load_transaction_info("raydium_v4_swap_buy_new_ata");
+        assert_eq!(info.route_hops().len(), 1);
+        assert_eq!(info.route_net_amounts(), None);
+        assert!(
+            load_transaction_info("sol_transfer")
+                .route_hops()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_route_net_amounts_are_the_fee_payers() {
+        // a 0.9 USDC platform fee is taken from the last hop's output before it reaches the fee payer
+        let mut tx = serde_json::to_value(load_fixture("jupiter_route_three_hops")).unwrap();
+        tx["meta"]["postTokenBalances"][3]["uiTokenAmount"]["amount"] = "125000000".into();
+        let tx = serde_json::from_value(tx).unwrap();
+        let info = TransactionInfo::from_encoded_transaction(&tx, "signature");
+        assert_eq!(info.route_hops()[2].output_amount, 120_900_000);
+        assert_eq!(
+            info.route_net_amounts(),
+            Some((
+                MINT.to_string(),
+                10_000_000_000,
+                USDC.to_string(),
+                120_000_000
+            ))
+        );
+        let price = info.get_price().unwrap();
+        assert!((price.price - 120.0 / 10_000.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_route_price_ignores_intermediate_dust() {
+        // the signer keeps 0.01 SOL of the first hop, the price still comes from the route's net in and out
+        let mut tx = serde_json::to_value(load_fixture("jupiter_route_three_hops")).unwrap();
+        let post = tx["meta"]["postTokenBalances"].as_array_mut().unwrap();
+        post[1]["uiTokenAmount"]["amount"] = "10000000".into();
+        post[6]["uiTokenAmount"]["amount"] = "80790000000".into();
+        let tx = serde_json::from_value(tx).unwrap();
+        let info = TransactionInfo::from_encoded_transaction(&tx, "signature");
+        let price = info.get_price().unwrap();
+        assert_eq!(price.base_mint, MINT);
+        assert_eq!(price.quote_mint, USDC);
+        assert!((price.price - 120.9 / 10_000.0).abs() < 1e-12);
+    }
+}
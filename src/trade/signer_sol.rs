@@ -1,3 +1,4 @@
+use crate::tool::{lamports_to_sol, lamports_to_sol_signed};
 use crate::trade::info::TransactionInfo;
 
 impl TransactionInfo {
@@ -45,9 +46,7 @@ impl TransactionInfo {
 
     /// Get total SOL received by signer (in SOL, decimal)
     pub fn get_signer_total_sol_received_sol(&self) -> f64 {
-        use solana_sdk::native_token::LAMPORTS_PER_SOL;
-        let lamports = self.get_signer_total_sol_received_lamports();
-        lamports as f64 / LAMPORTS_PER_SOL as f64
+        lamports_to_sol(self.get_signer_total_sol_received_lamports())
     }
 
     /// Get total SOL paid by signer (in lamports)
@@ -87,9 +86,7 @@ impl TransactionInfo {
 
     /// Get total SOL paid by signer (in SOL, decimal)
     pub fn get_signer_total_sol_paid_sol(&self) -> f64 {
-        use solana_sdk::native_token::LAMPORTS_PER_SOL;
-        let lamports = self.get_signer_total_sol_paid_lamports();
-        lamports as f64 / LAMPORTS_PER_SOL as f64
+        lamports_to_sol(self.get_signer_total_sol_paid_lamports())
     }
 
     /// Get signer's net SOL income (in lamports)
@@ -103,9 +100,7 @@ impl TransactionInfo {
     /// Get signer's net SOL income (in SOL, decimal)
     /// Total income - total expenses, positive means net income, negative means net expense
     pub fn get_signer_net_sol_income_sol(&self) -> f64 {
-        use solana_sdk::native_token::LAMPORTS_PER_SOL;
-        let lamports = self.get_signer_net_sol_income_lamports();
-        lamports as f64 / LAMPORTS_PER_SOL as f64
+        lamports_to_sol_signed(self.get_signer_net_sol_income_lamports())
     }
 
     /// Get signer's net SOL expense (in lamports)
@@ -119,8 +114,6 @@ impl TransactionInfo {
     /// Get signer's net SOL expense (in SOL, decimal)
     /// Total expenses - total income, positive means net expense, negative means net income
     pub fn get_signer_net_sol_expense_sol(&self) -> f64 {
-        use solana_sdk::native_token::LAMPORTS_PER_SOL;
-        let lamports = self.get_signer_net_sol_expense_lamports();
-        lamports as f64 / LAMPORTS_PER_SOL as f64
+        lamports_to_sol_signed(self.get_signer_net_sol_expense_lamports())
     }
 }
@@ -0,0 +1,545 @@
+use std::str::FromStr;
+
+use raydium_sdk::liquidity::v4::{RaydiumLiquidityPoolData, RaydiumLiquidityPoolV4};
+use solana_sdk::{
+    instruction::{AccountMeta, Instruction},
+    message::Message,
+    pubkey::Pubkey,
+    signature::Signer,
+    transaction::Transaction,
+};
+
+use crate::global::{RAYDIUM_V4_POOL_PROGRAM_ID, SPL_TOKEN_PROGRAM_V1};
+use crate::spl::{self, TokenProgramType};
+use crate::trade::Trade;
+use crate::types::{TradeError, TxOptions};
+use crate::wallet::Wallet;
+
+/// raydium v4 amm authority, shared by every v4 pool
+const RAYDIUM_V4_AUTHORITY: &str = "5Q544fKrFoe6tsEbD7S8EmxGTJYAKtTVhAW5Q5pge4j1";
+/// raydium v4 `swap_base_in` instruction tag
+const RAYDIUM_V4_SWAP_BASE_IN: u8 = 9;
+/// openbook market account size (5 bytes head + 376 bytes state + 7 bytes tail)
+const OPENBOOK_MARKET_DATA_SIZE: usize = 388;
+
+/// openbook market accounts a raydium v4 swap has to pass along
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct MarketAccounts {
+    pub bids: Pubkey,
+    pub asks: Pubkey,
+    pub event_queue: Pubkey,
+    pub coin_vault: Pubkey,
+    pub pc_vault: Pubkey,
+    pub vault_signer: Pubkey,
+}
+
+impl MarketAccounts {
+    /// parse the openbook market account referenced by a raydium v4 pool
+    pub(crate) fn parse(
+        market: &Pubkey,
+        market_program: &Pubkey,
+        data: &[u8],
+    ) -> Result<Self, TradeError> {
+        if data.len() < OPENBOOK_MARKET_DATA_SIZE {
+            return Err(TradeError::ParseError(format!(
+                "market {} data length {} is too short",
+                market,
+                data.len()
+            )));
+        }
+        let key =
+            |offset: usize| Pubkey::new_from_array(data[offset..offset + 32].try_into().unwrap());
+        let nonce = u64::from_le_bytes(data[45..53].try_into().unwrap());
+        let vault_signer = Pubkey::create_program_address(
+            &[market.as_ref(), &nonce.to_le_bytes()],
+            market_program,
+        )
+        .map_err(|e| TradeError::ParseError(format!("market vault signer error: {:?}", e)))?;
+        Ok(Self {
+            coin_vault: key(117),
+            pc_vault: key(165),
+            event_queue: key(253),
+            bids: key(285),
+            asks: key(317),
+            vault_signer,
+        })
+    }
+}
+
+/// expected output of a constant product swap after the pool's swap fee
+///
+/// # Params
+/// amount_in - input amount in raw units
+/// reserve_in - pool reserve of the input token
+/// reserve_out - pool reserve of the output token
+/// fee_numerator / fee_denominator - pool swap fee, 25 / 10000 on most v4 pools
+pub fn raydium_v4_amount_out(
+    amount_in: u64,
+    reserve_in: u64,
+    reserve_out: u64,
+    fee_numerator: u64,
+    fee_denominator: u64,
+) -> u64 {
+    if fee_denominator == 0 || reserve_in == 0 || reserve_out == 0 {
+        return 0;
+    }
+    let amount_in = amount_in as u128;
+    let fee = (amount_in * fee_numerator as u128).div_ceil(fee_denominator as u128);
+    let amount_in_after_fee = amount_in.saturating_sub(fee);
+    let amount_out =
+        reserve_out as u128 * amount_in_after_fee / (reserve_in as u128 + amount_in_after_fee);
+    amount_out as u64
+}
+
+/// associated token account of `owner` for `mint` (spl token v1)
+fn associated_token_address(owner: &Pubkey, mint: &Pubkey) -> Pubkey {
+    spl::associated_token_address(owner, mint, TokenProgramType::StandardSplToken)
+}
+
+/// create the associated token account unless it already exists (spl token v1)
+fn create_associated_token_account_idempotent(
+    payer: &Pubkey,
+    owner: &Pubkey,
+    mint: &Pubkey,
+) -> Instruction {
+    spl::create_associated_token_account_idempotent(
+        payer,
+        owner,
+        mint,
+        TokenProgramType::StandardSplToken,
+    )
+}
+
+/// build a raydium v4 `swap_base_in` instruction selling `input_mint` from the owner's
+/// associated token account into the associated token account of the pool's other mint
+pub(crate) fn raydium_v4_swap_instruction(
+    pool_address: &Pubkey,
+    pool: &RaydiumLiquidityPoolData,
+    market: &MarketAccounts,
+    owner: &Pubkey,
+    input_mint: &Pubkey,
+    amount_in: u64,
+    min_amount_out: u64,
+) -> Instruction {
+    let output_mint = if *input_mint == pool.base_mint {
+        pool.quote_mint
+    } else {
+        pool.base_mint
+    };
+    let mut data = Vec::with_capacity(17);
+    data.push(RAYDIUM_V4_SWAP_BASE_IN);
+    data.extend_from_slice(&amount_in.to_le_bytes());
+    data.extend_from_slice(&min_amount_out.to_le_bytes());
+    Instruction {
+        program_id: Pubkey::from_str(RAYDIUM_V4_POOL_PROGRAM_ID).unwrap(),
+        accounts: vec![
+            AccountMeta::new_readonly(Pubkey::from_str(SPL_TOKEN_PROGRAM_V1).unwrap(), false),
+            AccountMeta::new(*pool_address, false),
+            AccountMeta::new_readonly(Pubkey::from_str(RAYDIUM_V4_AUTHORITY).unwrap(), false),
+            AccountMeta::new(pool.open_orders, false),
+            AccountMeta::new(pool.target_orders, false),
+            AccountMeta::new(pool.base_vault, false),
+            AccountMeta::new(pool.quote_vault, false),
+            AccountMeta::new_readonly(pool.market_program_id, false),
+            AccountMeta::new(pool.market_id, false),
+            AccountMeta::new(market.bids, false),
+            AccountMeta::new(market.asks, false),
+            AccountMeta::new(market.event_queue, false),
+            AccountMeta::new(market.coin_vault, false),
+            AccountMeta::new(market.pc_vault, false),
+            AccountMeta::new_readonly(market.vault_signer, false),
+            AccountMeta::new(associated_token_address(owner, input_mint), false),
+            AccountMeta::new(associated_token_address(owner, &output_mint), false),
+            AccountMeta::new_readonly(*owner, true),
+        ],
+        data,
+    }
+}
+
+impl Trade {
+    /// swap on a raydium v4 pool with a slippage floor
+    /// the expected output is computed from the pool reserves first, and the swap is rejected
+    /// without sending when `min_amount_out` is above it
+    /// the wallet must already hold the input token in its associated token account,
+    /// the output associated token account is created when missing
+    /// if the block hash expires before the swap lands, it is re-signed with a fresh one
+    ///
+    /// # Params
+    /// wallet - signer and fee payer
+    /// pool - raydium v4 pool address
+    /// input_mint - mint being sold, either the pool's base or quote mint
+    /// amount_in - input amount in raw units
+    /// min_amount_out - minimum output in raw units
+    ///
+    /// # Returns
+    /// transaction signature
+    ///
+    /// # Example
+    /// ```ignore
+    /// let solana = Solana::new(Mode::MAIN).unwrap();
+    /// let trade = solana.create_trade();
+    /// let signature = trade
+    ///     .swap_raydium(&wallet, "pool address", WSOL, 100_000_000, 1_000_000)
+    ///     .await?;
+    /// ```
+    pub async fn swap_raydium(
+        &self,
+        wallet: &Wallet,
+        pool: &str,
+        input_mint: &str,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<String, TradeError> {
+        self.swap_raydium_with_options(
+            wallet,
+            pool,
+            input_mint,
+            amount_in,
+            min_amount_out,
+            &TxOptions::default(),
+        )
+        .await
+    }
+
+    /// `swap_raydium` with explicit priority fee, compute unit limit, preflight and retries
+    ///
+    /// # Example
+    /// ```ignore
+    /// let opts = TxOptions {
+    ///     priority_fee_micro_lamports: Some(50_000),
+    ///     compute_unit_limit: Some(120_000),
+    ///     ..Default::default()
+    /// };
+    /// let signature = trade
+    ///     .swap_raydium_with_options(&wallet, "pool address", WSOL, 100_000_000, 1_000_000, &opts)
+    ///     .await?;
+    /// ```
+    pub async fn swap_raydium_with_options(
+        &self,
+        wallet: &Wallet,
+        pool: &str,
+        input_mint: &str,
+        amount_in: u64,
+        min_amount_out: u64,
+        opts: &TxOptions,
+    ) -> Result<String, TradeError> {
+        let instructions = self
+            .raydium_swap_instructions(wallet, pool, input_mint, amount_in, min_amount_out)
+            .await?;
+        let keypair = wallet.keypair.as_ref().ok_or(TradeError::MissingKeypair)?;
+        self.send_with_fresh_blockhash(&instructions, keypair, opts)
+            .await
+    }
+
+    /// simulate the raydium v4 swap `swap_raydium_with_options` would send with `opts` and
+    /// return its compute units, e.g. to request a tight compute unit limit for the real swap
+    /// the same checks apply, and a failing simulation returns `TradeError::SimulationFailed`
+    ///
+    /// # Example
+    /// ```ignore
+    /// let units = trade
+    ///     .estimate_swap_raydium_compute_units(&wallet, "pool address", WSOL, 100_000_000, 1_000_000, &opts)
+    ///     .await?;
+    /// ```
+    pub async fn estimate_swap_raydium_compute_units(
+        &self,
+        wallet: &Wallet,
+        pool: &str,
+        input_mint: &str,
+        amount_in: u64,
+        min_amount_out: u64,
+        opts: &TxOptions,
+    ) -> Result<u64, TradeError> {
+        let mut instructions = self.compute_budget_instructions(opts).await?;
+        instructions.extend(
+            self.raydium_swap_instructions(wallet, pool, input_mint, amount_in, min_amount_out)
+                .await?,
+        );
+        let keypair = wallet.keypair.as_ref().ok_or(TradeError::MissingKeypair)?;
+        let blockhash = self
+            .client
+            .get_latest_blockhash()
+            .await
+            .map_err(|e| TradeError::RpcError(format!("get block hash error: {:?}", e)))?;
+        let transaction = Transaction::new_signed_with_payer(
+            &instructions,
+            Some(&keypair.pubkey()),
+            &[keypair],
+            blockhash,
+        );
+        let simulation = self.simulate(&transaction).await?;
+        if let Some(err) = simulation.err {
+            return Err(TradeError::SimulationFailed(err));
+        }
+        simulation
+            .units_consumed
+            .ok_or_else(|| TradeError::SimulationFailed("no compute units reported".to_string()))
+    }
+
+    /// fee in lamports of the raydium v4 swap `swap_raydium_with_options` would send with
+    /// `opts`, priced by the rpc, priority fee included
+    /// the same checks apply, nothing is sent
+    ///
+    /// # Example
+    /// ```ignore
+    /// let fee = trade
+    ///     .estimate_swap_raydium_fee(&wallet, "pool address", WSOL, 100_000_000, 1_000_000, &opts)
+    ///     .await?;
+    /// ```
+    pub async fn estimate_swap_raydium_fee(
+        &self,
+        wallet: &Wallet,
+        pool: &str,
+        input_mint: &str,
+        amount_in: u64,
+        min_amount_out: u64,
+        opts: &TxOptions,
+    ) -> Result<u64, TradeError> {
+        let mut instructions = self.compute_budget_instructions(opts).await?;
+        instructions.extend(
+            self.raydium_swap_instructions(wallet, pool, input_mint, amount_in, min_amount_out)
+                .await?,
+        );
+        let keypair = wallet.keypair.as_ref().ok_or(TradeError::MissingKeypair)?;
+        let blockhash = self
+            .client
+            .get_latest_blockhash()
+            .await
+            .map_err(|e| TradeError::RpcError(format!("get block hash error: {:?}", e)))?;
+        let message =
+            Message::new_with_blockhash(&instructions, Some(&keypair.pubkey()), &blockhash);
+        self.estimate_fee_for(&message).await
+    }
+
+    /// build the instructions of a raydium v4 swap, see `swap_raydium`
+    async fn raydium_swap_instructions(
+        &self,
+        wallet: &Wallet,
+        pool: &str,
+        input_mint: &str,
+        amount_in: u64,
+        min_amount_out: u64,
+    ) -> Result<Vec<Instruction>, TradeError> {
+        let pool_address =
+            Pubkey::from_str(pool).map_err(|_| TradeError::InvalidAddress(pool.to_string()))?;
+        let input_mint_pubkey = Pubkey::from_str(input_mint)
+            .map_err(|_| TradeError::InvalidAddress(input_mint.to_string()))?;
+        let pool_data = self
+            .client
+            .get_account_data(&pool_address)
+            .await
+            .map_err(|e| TradeError::RpcError(format!("get pool account error: {:?}", e)))?;
+        let pool_info = RaydiumLiquidityPoolV4::get_liquidity_pool_info(&pool_data)
+            .map_err(TradeError::ParseError)?;
+        let (input_vault, output_vault, output_mint, input_pnl, output_pnl) =
+            if input_mint_pubkey == pool_info.base_mint {
+                (
+                    pool_info.base_vault,
+                    pool_info.quote_vault,
+                    pool_info.quote_mint,
+                    pool_info.base_need_take_pnl,
+                    pool_info.quote_need_take_pnl,
+                )
+            } else if input_mint_pubkey == pool_info.quote_mint {
+                (
+                    pool_info.quote_vault,
+                    pool_info.base_vault,
+                    pool_info.base_mint,
+                    pool_info.quote_need_take_pnl,
+                    pool_info.base_need_take_pnl,
+                )
+            } else {
+                return Err(TradeError::InvalidAddress(input_mint.to_string()));
+            };
+        let (input_amount, output_amount) = self.vault_amounts(&input_vault, &output_vault).await?;
+        let reserve_in = input_amount.saturating_sub(input_pnl);
+        let reserve_out = output_amount.saturating_sub(output_pnl);
+        let expected = raydium_v4_amount_out(
+            amount_in,
+            reserve_in,
+            reserve_out,
+            pool_info.swap_fee_numerator,
+            pool_info.swap_fee_denominator,
+        );
+        if expected < min_amount_out {
+            return Err(TradeError::SlippageExceeded {
+                expected,
+                minimum: min_amount_out,
+            });
+        }
+        let keypair = wallet.keypair.as_ref().ok_or(TradeError::MissingKeypair)?;
+        let owner = keypair.pubkey();
+        let market_data = self
+            .client
+            .get_account_data(&pool_info.market_id)
+            .await
+            .map_err(|e| TradeError::RpcError(format!("get market account error: {:?}", e)))?;
+        let market = MarketAccounts::parse(
+            &pool_info.market_id,
+            &pool_info.market_program_id,
+            &market_data,
+        )?;
+        Ok(vec![
+            create_associated_token_account_idempotent(&owner, &owner, &output_mint),
+            raydium_v4_swap_instruction(
+                &pool_address,
+                &pool_info,
+                &market,
+                &owner,
+                &input_mint_pubkey,
+                amount_in,
+                min_amount_out,
+            ),
+        ])
+    }
+
+    /// raw token amounts held by the two pool vaults, read in a single request
+    async fn vault_amounts(&self, a: &Pubkey, b: &Pubkey) -> Result<(u64, u64), TradeError> {
+        let accounts = self
+            .client
+            .get_multiple_accounts(&[*a, *b])
+            .await
+            .map_err(|e| TradeError::RpcError(format!("get vault accounts error: {:?}", e)))?;
+        let amount = |vault: &Pubkey, account: Option<&solana_sdk::account::Account>| {
+            // spl token account layout: mint (32) | owner (32) | amount (8) | ...
+            account
+                .and_then(|account| account.data.get(64..72))
+                .map(|bytes| u64::from_le_bytes(bytes.try_into().unwrap()))
+                .ok_or_else(|| TradeError::ParseError(format!("{} is not a token account", vault)))
+        };
+        Ok((
+            amount(a, accounts.first().and_then(|a| a.as_ref()))?,
+            amount(b, accounts.get(1).and_then(|b| b.as_ref()))?,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::mock_solana;
+    use crate::mock::MockRpc;
+    use base64::Engine;
+    use solana_client::rpc_request::RpcRequest;
+
+    #[test]
+    fn test_raydium_v4_amount_out() {
+        // 1 SOL into a 100 SOL / 15,000 USDC pool with the 0.25% fee
+        let out = raydium_v4_amount_out(1_000_000_000, 100_000_000_000, 15_000_000_000, 25, 10_000);
+        assert_eq!(out, 148_147_231);
+        assert_eq!(raydium_v4_amount_out(1_000, 0, 1_000, 25, 10_000), 0);
+        assert_eq!(raydium_v4_amount_out(0, 1_000, 1_000, 25, 10_000), 0);
+    }
+
+    #[test]
+    fn test_market_accounts_parse() {
+        let market = Pubkey::new_unique();
+        let program = Pubkey::new_unique();
+        let mut data = vec![0u8; OPENBOOK_MARKET_DATA_SIZE];
+        let (coin_vault, pc_vault, event_queue, bids, asks) = (
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        );
+        data[117..149].copy_from_slice(coin_vault.as_ref());
+        data[165..197].copy_from_slice(pc_vault.as_ref());
+        data[253..285].copy_from_slice(event_queue.as_ref());
+        data[285..317].copy_from_slice(bids.as_ref());
+        data[317..349].copy_from_slice(asks.as_ref());
+        // find a nonce whose program address is off the curve, as the market creator does
+        let nonce = (0u64..)
+            .find(|n| {
+                Pubkey::create_program_address(&[market.as_ref(), &n.to_le_bytes()], &program)
+                    .is_ok()
+            })
+            .unwrap();
+        data[45..53].copy_from_slice(&nonce.to_le_bytes());
+        let accounts = MarketAccounts::parse(&market, &program, &data).unwrap();
+        assert_eq!(accounts.coin_vault, coin_vault);
+        assert_eq!(accounts.pc_vault, pc_vault);
+        assert_eq!(accounts.event_queue, event_queue);
+        assert_eq!(accounts.bids, bids);
+        assert_eq!(accounts.asks, asks);
+        assert!(MarketAccounts::parse(&market, &program, &data[..100]).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_swap_raydium_rejects_unreachable_min_out() {
+        let base_mint = Pubkey::new_unique();
+        let quote_mint = Pubkey::new_unique();
+        let mut pool = vec![0u8; 752];
+        pool[176..184].copy_from_slice(&25u64.to_le_bytes());
+        pool[184..192].copy_from_slice(&10_000u64.to_le_bytes());
+        pool[336..368].copy_from_slice(Pubkey::new_unique().as_ref());
+        pool[368..400].copy_from_slice(Pubkey::new_unique().as_ref());
+        pool[400..432].copy_from_slice(base_mint.as_ref());
+        pool[432..464].copy_from_slice(quote_mint.as_ref());
+        let rpc = MockRpc::new();
+        rpc.set_response(
+            RpcRequest::GetAccountInfo,
+            serde_json::json!({
+                "context": { "slot": 1 },
+                "value": {
+                    "lamports": 6_124_800,
+                    "data": [base64::engine::general_purpose::STANDARD.encode(&pool), "base64"],
+                    "owner": RAYDIUM_V4_POOL_PROGRAM_ID,
+                    "executable": false,
+                    "rentEpoch": 0,
+                    "space": 752
+                }
+            }),
+        );
+        // both vaults hold 1,000 tokens
+        let mut vault = vec![0u8; 165];
+        vault[64..72].copy_from_slice(&1_000_000_000u64.to_le_bytes());
+        let vault_account = serde_json::json!({
+            "lamports": 2_039_280,
+            "data": [base64::engine::general_purpose::STANDARD.encode(&vault), "base64"],
+            "owner": SPL_TOKEN_PROGRAM_V1,
+            "executable": false,
+            "rentEpoch": 0,
+            "space": 165
+        });
+        rpc.set_response(
+            RpcRequest::GetMultipleAccounts,
+            serde_json::json!({
+                "context": { "slot": 1 },
+                "value": [vault_account.clone(), vault_account]
+            }),
+        );
+        let trade = mock_solana(&rpc).create_trade();
+        let wallet = Wallet::create_new_wallet();
+        let expected = raydium_v4_amount_out(1_000_000, 1_000_000_000, 1_000_000_000, 25, 10_000);
+        assert_eq!(
+            trade
+                .swap_raydium(
+                    &wallet,
+                    &Pubkey::new_unique().to_string(),
+                    &base_mint.to_string(),
+                    1_000_000,
+                    expected + 1,
+                )
+                .await,
+            Err(TradeError::SlippageExceeded {
+                expected,
+                minimum: expected + 1
+            })
+        );
+        // a mint that is neither side of the pool
+        let trade = mock_solana(&rpc).create_trade();
+        assert!(matches!(
+            trade
+                .swap_raydium(
+                    &wallet,
+                    &Pubkey::new_unique().to_string(),
+                    &Pubkey::new_unique().to_string(),
+                    1_000_000,
+                    0,
+                )
+                .await,
+            Err(TradeError::InvalidAddress(_))
+        ));
+    }
+}
@@ -0,0 +1,188 @@
+use serde_json::Value;
+
+use crate::global::SPL_TOKEN_PROGRAM_2022;
+use crate::trade::info::{InstructionInfo, TransactionInfo};
+
+/// Token-2022 `TransferFeeExtension` instruction tag
+const TRANSFER_FEE_EXTENSION: u8 = 26;
+/// `TransferCheckedWithFee` tag inside the transfer fee extension
+const TRANSFER_CHECKED_WITH_FEE: u8 = 1;
+/// Token-2022 instruction tags that create or destroy tokens: MintTo, Burn, MintToChecked, BurnChecked
+const SUPPLY_CHANGING_TAGS: [u8; 4] = [7, 8, 14, 15];
+
+/// amounts of the Token-2022 transfers of one mint in a transaction (raw units)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransferFeeAmounts {
+    pub gross: u64, // sent by the senders
+    pub fee: u64,   // withheld in the recipients' token accounts
+    pub net: u64,   // credited to the recipients
+}
+
+impl TransactionInfo {
+    /// Get the transfer fee withheld from the Token-2022 transfers of `mint` (raw units)
+    /// Only applies to Token-2022 mints with the transfer fee extension: recipients get the
+    /// sent amount minus the fee, so balance changes report the net amount
+    /// Returns None for classic spl tokens and Token-2022 transfers nothing was withheld from
+    pub fn transfer_fee(&self, mint: &str) -> Option<u64> {
+        self.transfer_fee_amounts(mint).map(|amounts| amounts.fee)
+    }
+
+    /// Get the gross, withheld and net amounts of the Token-2022 transfers of `mint`
+    /// The fee is read from `TransferCheckedWithFee` instructions; plain transfers of a fee
+    /// bearing mint fall back to the gap between the debited and credited token balances
+    pub fn transfer_fee_amounts(&self, mint: &str) -> Option<TransferFeeAmounts> {
+        if self.is_failed() {
+            return None;
+        }
+        let instructions = self.instructions_for_program(SPL_TOKEN_PROGRAM_2022);
+        let with_fee: Vec<(u64, u64)> = instructions
+            .iter()
+            .filter_map(|instruction| Self::transfer_checked_with_fee(instruction, mint))
+            .collect();
+        if !with_fee.is_empty() {
+            let gross = with_fee.iter().map(|(amount, _)| amount).sum::<u64>();
+            let fee = with_fee.iter().map(|(_, fee)| fee).sum::<u64>();
+            return Some(TransferFeeAmounts {
+                gross,
+                fee,
+                net: gross.saturating_sub(fee),
+            });
+        }
+        // mints and burns would show up as fees in the balance gap
+        if instructions
+            .iter()
+            .any(|instruction| Self::changes_supply(instruction))
+        {
+            return None;
+        }
+        self.transfer_fee_from_balances(mint)
+    }
+
+    /// (amount, fee) of a `TransferCheckedWithFee` instruction of `mint`
+    fn transfer_checked_with_fee(instruction: &InstructionInfo, mint: &str) -> Option<(u64, u64)> {
+        match instruction.program.as_str() {
+            "compiled" | "partially_decoded" => {
+                let data = bs58::decode(&instruction.data).into_vec().ok()?;
+                // tag, extension tag, amount, decimals, fee; accounts: source, mint, destination, authority
+                if data.len() < 19
+                    || data[..2] != [TRANSFER_FEE_EXTENSION, TRANSFER_CHECKED_WITH_FEE]
+                    || instruction.accounts.get(1).map(String::as_str) != Some(mint)
+                {
+                    return None;
+                }
+                Some((
+                    u64::from_le_bytes(data[2..10].try_into().ok()?),
+                    u64::from_le_bytes(data[11..19].try_into().ok()?),
+                ))
+            }
+            _ => {
+                let parsed: Value = serde_json::from_str(&instruction.data).ok()?;
+                if parsed["type"] != "transferCheckedWithFee" || parsed["info"]["mint"] != mint {
+                    return None;
+                }
+                let amount_of = |field: &str| {
+                    parsed["info"][field]["amount"]
+                        .as_str()
+                        .and_then(|amount| amount.parse::<u64>().ok())
+                };
+                Some((amount_of("tokenAmount")?, amount_of("feeAmount")?))
+            }
+        }
+    }
+
+    fn changes_supply(instruction: &InstructionInfo) -> bool {
+        match instruction.program.as_str() {
+            "compiled" | "partially_decoded" => bs58::decode(&instruction.data)
+                .into_vec()
+                .ok()
+                .and_then(|data| data.first().copied())
+                .is_some_and(|tag| SUPPLY_CHANGING_TAGS.contains(&tag)),
+            _ => serde_json::from_str::<Value>(&instruction.data)
+                .ok()
+                .and_then(|parsed| parsed["type"].as_str().map(str::to_string))
+                .is_some_and(|kind| kind.starts_with("mintTo") || kind.starts_with("burn")),
+        }
+    }
+
+    /// debits vs credits of the Token-2022 accounts of `mint`, the gap is the withheld fee
+    fn transfer_fee_from_balances(&self, mint: &str) -> Option<TransferFeeAmounts> {
+        let is_token_2022 = self
+            .raw_pre_token_balances
+            .iter()
+            .chain(&self.raw_post_token_balances)
+            .any(|b| b.mint == mint && b.program_id.as_deref() == Some(SPL_TOKEN_PROGRAM_2022));
+        if !is_token_2022 {
+            return None;
+        }
+        let (mut debited, mut credited) = (0i128, 0i128);
+        for account in self.token_account_changes() {
+            if account.mint != mint {
+                continue;
+            }
+            if account.delta < 0 {
+                debited -= account.delta;
+            } else {
+                credited += account.delta;
+            }
+        }
+        let fee = debited - credited;
+        if fee <= 0 {
+            return None;
+        }
+        Some(TransferFeeAmounts {
+            gross: debited as u64,
+            fee: fee as u64,
+            net: credited as u64,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::{load_fixture, load_transaction_info};
+
+    #[test]
+    fn test_transfer_checked_with_fee() {
+        let info = load_transaction_info("token_2022_transfer_fee");
+        let mint = &info.raw_post_token_balances[0].mint;
+        assert_eq!(info.transfer_fee(mint), Some(10_000));
+        assert_eq!(
+            info.transfer_fee_amounts(mint),
+            Some(TransferFeeAmounts {
+                gross: 1_000_000,
+                fee: 10_000,
+                net: 990_000
+            })
+        );
+        assert_eq!(
+            info.transfer_fee("So11111111111111111111111111111111111111112"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_transfer_fee_from_balances() {
+        // a plain TransferChecked of the same mint: the fee only shows in the balance gap
+        let mut tx = serde_json::to_value(load_fixture("token_2022_transfer_fee")).unwrap();
+        tx["transaction"]["message"]["instructions"][0]["data"] = "gvPShZQhKrzGM".into();
+        let tx = serde_json::from_value(tx).unwrap();
+        let info = TransactionInfo::from_encoded_transaction(&tx, "signature");
+        let mint = &info.raw_post_token_balances[0].mint;
+        assert_eq!(
+            info.transfer_fee_amounts(mint),
+            Some(TransferFeeAmounts {
+                gross: 1_000_000,
+                fee: 10_000,
+                net: 990_000
+            })
+        );
+    }
+
+    #[test]
+    fn test_classic_token_has_no_transfer_fee() {
+        let info = load_transaction_info("token_payment");
+        let mint = info.raw_post_token_balances[0].mint.clone();
+        assert_eq!(info.transfer_fee(&mint), None);
+    }
+}
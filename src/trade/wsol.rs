@@ -0,0 +1,326 @@
+use std::collections::{HashMap, HashSet};
+
+use serde_json::Value;
+
+use crate::global::{SPL_TOKEN_PROGRAM_V1, SYSTEM_PROGRAM_ID, WSOL};
+use crate::trade::info::{InstructionInfo, TransactionInfo};
+
+/// lamports a 165 byte token account keeps for rent, they are not wrapped
+const TOKEN_ACCOUNT_RENT: u64 = 2_039_280;
+/// system program `CreateAccount` and `CreateAccountWithSeed` instruction tags
+const SYSTEM_CREATE_ACCOUNT: u32 = 0;
+const SYSTEM_CREATE_ACCOUNT_WITH_SEED: u32 = 3;
+/// token program `InitializeAccount`, `InitializeAccount2` and `InitializeAccount3` tags
+const INITIALIZE_ACCOUNT_TAGS: [u8; 3] = [1, 16, 18];
+/// token program `Transfer` and `TransferChecked` tags
+const TRANSFER: u8 = 3;
+const TRANSFER_CHECKED: u8 = 12;
+/// token program `CloseAccount` and `SyncNative` tags
+const CLOSE_ACCOUNT: u8 = 9;
+const SYNC_NATIVE: u8 = 17;
+
+/// token program instructions that move native SOL in and out of WSOL accounts
+enum TokenInstruction {
+    InitializeAccount {
+        account: String,
+        mint: String,
+    },
+    Transfer {
+        source: String,
+        destination: String,
+        amount: u64,
+    },
+    CloseAccount {
+        account: String,
+    },
+    SyncNative {
+        account: String,
+    },
+}
+
+impl TransactionInfo {
+    /// Get the lamports the transaction wrapped into WSOL
+    /// Counts the SOL sent to WSOL accounts synced with `SyncNative` or initialized in the
+    /// transaction, the rent of accounts created in the transaction is left out
+    /// Returns None for failed transactions and transactions wrapping into no WSOL account
+    pub fn wsol_wrapped(&self) -> Option<u64> {
+        let wrapped = self.wrapped_native_accounts()?;
+        Some(
+            wrapped
+                .iter()
+                .map(|account| self.lamports_wrapped_into(account))
+                .sum(),
+        )
+    }
+
+    /// Get the WSOL the transaction unwrapped back into SOL by closing WSOL accounts
+    /// The amount of a closed account is its balance before the transaction plus the WSOL
+    /// wrapped or transferred into it, minus the WSOL transferred out, rent excluded
+    /// Returns None for failed transactions and transactions closing no WSOL account
+    pub fn wsol_unwrapped(&self) -> Option<u64> {
+        if self.is_failed() {
+            return None;
+        }
+        let instructions = self.token_instructions();
+        let native = self.native_accounts(&instructions);
+        let closed: Vec<&String> = instructions
+            .iter()
+            .filter_map(|instruction| match instruction {
+                TokenInstruction::CloseAccount { account } if native.contains(account) => {
+                    Some(account)
+                }
+                _ => None,
+            })
+            .collect();
+        if closed.is_empty() {
+            return None;
+        }
+        let mut flows: HashMap<&str, i128> = HashMap::new();
+        for instruction in &instructions {
+            if let TokenInstruction::Transfer {
+                source,
+                destination,
+                amount,
+            } = instruction
+            {
+                *flows.entry(source).or_default() -= *amount as i128;
+                *flows.entry(destination).or_default() += *amount as i128;
+            }
+        }
+        let wrapped_accounts = self.wrapped_native_accounts().unwrap_or_default();
+        let unwrapped = closed
+            .into_iter()
+            .map(|account| {
+                let wrapped = if wrapped_accounts.contains(account) {
+                    self.lamports_wrapped_into(account)
+                } else {
+                    0
+                };
+                let amount = self.pre_wsol_amount(account) as i128
+                    + wrapped as i128
+                    + flows.get(account.as_str()).copied().unwrap_or(0);
+                amount.max(0) as u64
+            })
+            .sum();
+        Some(unwrapped)
+    }
+
+    /// WSOL accounts synced with `SyncNative` or initialized in the transaction,
+    /// None for failed transactions and when there are none
+    fn wrapped_native_accounts(&self) -> Option<HashSet<String>> {
+        if self.is_failed() {
+            return None;
+        }
+        let wrapped: HashSet<String> = self
+            .token_instructions()
+            .into_iter()
+            .filter_map(|instruction| match instruction {
+                TokenInstruction::SyncNative { account } => Some(account),
+                TokenInstruction::InitializeAccount { account, mint } if mint == WSOL => {
+                    Some(account)
+                }
+                _ => None,
+            })
+            .collect();
+        (!wrapped.is_empty()).then_some(wrapped)
+    }
+
+    /// lamports sent to `account` with system transfers and account creations, rent excluded
+    fn lamports_wrapped_into(&self, account: &str) -> u64 {
+        let mut transferred = 0u64;
+        let mut created_with = 0u64;
+        for instruction in self.instructions_for_program(SYSTEM_PROGRAM_ID) {
+            if let Some((destination, lamports)) = Self::system_transfer(instruction) {
+                if destination == account {
+                    transferred += lamports;
+                }
+            } else if let Some((new_account, lamports)) = Self::system_create_account(instruction)
+                && new_account == account
+            {
+                created_with += lamports;
+            }
+        }
+        transferred + created_with.saturating_sub(TOKEN_ACCOUNT_RENT)
+    }
+
+    /// WSOL balance of `account` before the transaction (raw units), 0 when it did not exist
+    fn pre_wsol_amount(&self, account: &str) -> u64 {
+        let Some(index) = self.raw_account_keys.iter().position(|key| key == account) else {
+            return 0;
+        };
+        self.raw_pre_token_balances
+            .iter()
+            .find(|balance| balance.account_index as usize == index && balance.mint == WSOL)
+            .and_then(|balance| balance.ui_token_amount.amount.parse().ok())
+            .unwrap_or(0)
+    }
+
+    /// WSOL accounts of the transaction: listed with the WSOL mint in the token balances,
+    /// initialized with the WSOL mint or synced with `SyncNative`
+    fn native_accounts(&self, instructions: &[TokenInstruction]) -> HashSet<String> {
+        let mut native: HashSet<String> = self
+            .raw_pre_token_balances
+            .iter()
+            .chain(&self.raw_post_token_balances)
+            .filter(|balance| balance.mint == WSOL)
+            .filter_map(|balance| {
+                self.raw_account_keys
+                    .get(balance.account_index as usize)
+                    .cloned()
+            })
+            .collect();
+        for instruction in instructions {
+            match instruction {
+                TokenInstruction::InitializeAccount { account, mint } if mint == WSOL => {
+                    native.insert(account.clone());
+                }
+                TokenInstruction::SyncNative { account } => {
+                    native.insert(account.clone());
+                }
+                _ => {}
+            }
+        }
+        native
+    }
+
+    fn token_instructions(&self) -> Vec<TokenInstruction> {
+        self.instructions_for_program(SPL_TOKEN_PROGRAM_V1)
+            .into_iter()
+            .filter_map(Self::token_instruction)
+            .collect()
+    }
+
+    fn token_instruction(instruction: &InstructionInfo) -> Option<TokenInstruction> {
+        let account = |index: usize| instruction.accounts.get(index).cloned();
+        match instruction.program.as_str() {
+            "compiled" | "partially_decoded" => {
+                let data = bs58::decode(&instruction.data).into_vec().ok()?;
+                let amount = || Some(u64::from_le_bytes(data.get(1..9)?.try_into().ok()?));
+                match *data.first()? {
+                    tag if INITIALIZE_ACCOUNT_TAGS.contains(&tag) => {
+                        Some(TokenInstruction::InitializeAccount {
+                            account: account(0)?,
+                            mint: account(1)?,
+                        })
+                    }
+                    // accounts: source, destination, authority
+                    TRANSFER => Some(TokenInstruction::Transfer {
+                        source: account(0)?,
+                        destination: account(1)?,
+                        amount: amount()?,
+                    }),
+                    // accounts: source, mint, destination, authority
+                    TRANSFER_CHECKED => Some(TokenInstruction::Transfer {
+                        source: account(0)?,
+                        destination: account(2)?,
+                        amount: amount()?,
+                    }),
+                    CLOSE_ACCOUNT => Some(TokenInstruction::CloseAccount {
+                        account: account(0)?,
+                    }),
+                    SYNC_NATIVE => Some(TokenInstruction::SyncNative {
+                        account: account(0)?,
+                    }),
+                    _ => None,
+                }
+            }
+            _ => {
+                let parsed: Value = serde_json::from_str(&instruction.data).ok()?;
+                let info = &parsed["info"];
+                let field = |name: &str| info[name].as_str().map(str::to_string);
+                match parsed["type"].as_str()? {
+                    "initializeAccount" | "initializeAccount2" | "initializeAccount3" => {
+                        Some(TokenInstruction::InitializeAccount {
+                            account: field("account")?,
+                            mint: field("mint")?,
+                        })
+                    }
+                    "transfer" | "transferChecked" => Some(TokenInstruction::Transfer {
+                        source: field("source")?,
+                        destination: field("destination")?,
+                        amount: info["amount"]
+                            .as_str()
+                            .or_else(|| info["tokenAmount"]["amount"].as_str())?
+                            .parse()
+                            .ok()?,
+                    }),
+                    "closeAccount" => Some(TokenInstruction::CloseAccount {
+                        account: field("account")?,
+                    }),
+                    "syncNative" => Some(TokenInstruction::SyncNative {
+                        account: field("account")?,
+                    }),
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// (new account, lamports) of a system program `CreateAccount` or `CreateAccountWithSeed`
+    fn system_create_account(instruction: &InstructionInfo) -> Option<(String, u64)> {
+        match instruction.program.as_str() {
+            "compiled" | "partially_decoded" => {
+                let data = bs58::decode(&instruction.data).into_vec().ok()?;
+                let lamports_offset = match u32::from_le_bytes(data.get(..4)?.try_into().ok()?) {
+                    // u32 tag, u64 lamports, u64 space, owner; accounts: funder, new account
+                    SYSTEM_CREATE_ACCOUNT => 4,
+                    // u32 tag, base, u64 seed length, seed, u64 lamports, ...
+                    SYSTEM_CREATE_ACCOUNT_WITH_SEED => {
+                        let seed_len = u64::from_le_bytes(data.get(36..44)?.try_into().ok()?);
+                        44 + seed_len as usize
+                    }
+                    _ => return None,
+                };
+                Some((
+                    instruction.accounts.get(1)?.clone(),
+                    u64::from_le_bytes(
+                        data.get(lamports_offset..lamports_offset + 8)?
+                            .try_into()
+                            .ok()?,
+                    ),
+                ))
+            }
+            _ => {
+                let parsed: Value = serde_json::from_str(&instruction.data).ok()?;
+                if !matches!(
+                    parsed["type"].as_str()?,
+                    "createAccount" | "createAccountWithSeed"
+                ) {
+                    return None;
+                }
+                Some((
+                    parsed["info"]["newAccount"].as_str()?.to_string(),
+                    parsed["info"]["lamports"].as_u64()?,
+                ))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::fixtures::load_transaction_info;
+    use crate::global::WSOL;
+
+    #[test]
+    fn test_wsol_wrapped_and_unwrapped() {
+        // temporary WSOL account: created with rent, funded, synced, swapped from and closed
+        let info = load_transaction_info("raydium_v4_swap_buy_wrapped_wsol");
+        assert_eq!(info.wsol_wrapped(), Some(2_000_000_000));
+        assert_eq!(info.wsol_unwrapped(), Some(0));
+        assert_eq!(info.get_pool_quote_token_address().as_deref(), Some(WSOL));
+        assert_eq!(
+            info.get_signer_quote_token_change_lamports(),
+            -2_000_000_000
+        );
+        assert_eq!(info.get_signer_quote_token_change_decimal(), Some(-2.0));
+        // a held WSOL account is neither wrapped into nor closed
+        let info = load_transaction_info("raydium_v4_swap_buy_new_ata");
+        assert_eq!(info.wsol_wrapped(), None);
+        assert_eq!(info.wsol_unwrapped(), None);
+        assert_eq!(
+            info.get_signer_quote_token_change_lamports(),
+            -2_000_000_000
+        );
+    }
+}
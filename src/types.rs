@@ -1,8 +1,104 @@
-#[derive(Debug, Clone, Copy)]
+use std::{collections::HashMap, fmt, net::SocketAddr, str::FromStr, time::Duration};
+
+use crate::global::{
+    SOLANA_DEV_NET_URL, SOLANA_LOCAL_NET_URL, SOLANA_OFFICIAL_MAIN_NET_URL, SOLANA_TEST_NET_URL,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Mode {
     MAIN,
     TEST,
     DEV,
+    /// a local solana-test-validator
+    LOCALNET,
+}
+
+impl Mode {
+    /// default rpc url of the mode
+    pub fn url(&self) -> &'static str {
+        match self {
+            Mode::MAIN => SOLANA_OFFICIAL_MAIN_NET_URL,
+            Mode::TEST => SOLANA_TEST_NET_URL,
+            Mode::DEV => SOLANA_DEV_NET_URL,
+            Mode::LOCALNET => SOLANA_LOCAL_NET_URL,
+        }
+    }
+
+    /// default websocket url of the mode, used for subscriptions
+    pub fn websocket_url(&self) -> String {
+        crate::tool::rpc::websocket_url(self.url())
+    }
+
+    /// whether the mode points at a local validator
+    pub fn is_local(&self) -> bool {
+        matches!(self, Mode::LOCALNET)
+    }
+
+    /// whether the mode points at mainnet
+    pub fn is_mainnet(&self) -> bool {
+        matches!(self, Mode::MAIN)
+    }
+
+    /// whether the mode points at a test cluster (testnet or devnet)
+    pub fn is_test(&self) -> bool {
+        matches!(self, Mode::TEST | Mode::DEV)
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Mode::MAIN => "mainnet",
+            Mode::TEST => "testnet",
+            Mode::DEV => "devnet",
+            Mode::LOCALNET => "localnet",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for Mode {
+    type Err = String;
+
+    /// parse a cluster name, case-insensitive: `main`/`mainnet`/`mainnet-beta`/`m`,
+    /// `test`/`testnet`/`t`, `dev`/`devnet`/`d` or `localnet`
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "main" | "mainnet" | "mainnet-beta" | "m" => Ok(Mode::MAIN),
+            "test" | "testnet" | "t" => Ok(Mode::TEST),
+            "dev" | "devnet" | "d" => Ok(Mode::DEV),
+            "localnet" => Ok(Mode::LOCALNET),
+            _ => Err(format!(
+                "unknown mode {:?}, expected mainnet, testnet, devnet or localnet",
+                s
+            )),
+        }
+    }
+}
+
+/// retry policy for failed rpc calls
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// number of retries after the first failed attempt
+    pub max_retries: u32,
+    /// delay between attempts
+    pub delay: Duration,
+}
+
+impl RetryPolicy {
+    pub fn new(max_retries: u32, delay: Duration) -> Self {
+        Self { max_retries, delay }
+    }
+}
+
+impl Default for RetryPolicy {
+    /// no retries
+    fn default() -> Self {
+        Self {
+            max_retries: 0,
+            delay: Duration::from_millis(500),
+        }
+    }
 }
 
 /// unified result
@@ -14,6 +110,265 @@ pub enum UnifiedError<T> {
     Error(T),
 }
 
+/// trade api error
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum TradeError {
+    #[error("invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("invalid signature: {0}")]
+    InvalidSignature(String),
+    #[error("rpc error: {0}")]
+    RpcError(String),
+    #[error("empty result")]
+    Empty,
+    #[error("parse error: {0}")]
+    ParseError(String),
+    #[error("expected output {expected} is below the minimum {minimum}")]
+    SlippageExceeded { expected: u64, minimum: u64 },
+    #[error("wallet has no keypair")]
+    MissingKeypair,
+    #[error("simulation failed: {0}")]
+    SimulationFailed(String),
+}
+
+impl From<TradeError> for String {
+    fn from(e: TradeError) -> Self {
+        e.to_string()
+    }
+}
+
+/// prioritization fee paid in a recent slot
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PrioritizationFee {
+    pub slot: u64,
+    /// compute unit price in micro-lamports
+    pub fee: u64,
+}
+
+/// a node of the cluster as advertised over gossip
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClusterNode {
+    pub pubkey: String,
+    pub gossip: Option<SocketAddr>,
+    pub tpu: Option<SocketAddr>,
+    /// None when the node does not serve json rpc
+    pub rpc: Option<SocketAddr>,
+    pub version: Option<String>,
+}
+
+/// identity, version and health of the rpc node, see `Solana::node_info`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NodeInfo {
+    /// identity pubkey of the node
+    pub identity: String,
+    pub solana_core: String,
+    /// feature set identifier, None when the node does not report it
+    pub feature_set: Option<u32>,
+    /// false when the node is behind the cluster
+    pub healthy: bool,
+}
+
+/// outcome of simulating a transaction
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SimulationResult {
+    /// transaction error, None when the simulation succeeded
+    pub err: Option<String>,
+    pub logs: Vec<String>,
+    pub units_consumed: Option<u64>,
+    /// (program id, data) set by the last program that returned data
+    pub return_data: Option<(String, Vec<u8>)>,
+}
+
+/// landing options of the transactions the sdk sends
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TxOptions {
+    /// compute unit price, None uses the median of the recent prioritization fees, 0 pays none
+    pub priority_fee_micro_lamports: Option<u64>,
+    /// compute unit limit, None keeps the runtime default
+    pub compute_unit_limit: Option<u32>,
+    /// send without the rpc's preflight simulation
+    pub skip_preflight: bool,
+    /// times the transaction is re-signed with a fresh block hash after the previous one expired
+    pub max_retries: usize,
+}
+
+impl Default for TxOptions {
+    fn default() -> Self {
+        Self {
+            priority_fee_micro_lamports: None,
+            compute_unit_limit: None,
+            skip_preflight: false,
+            max_retries: 2,
+        }
+    }
+}
+
+/// which accounts `Solana::get_largest_accounts` ranks
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AccountFilter {
+    Circulating,
+    NonCirculating,
+}
+
+/// full on-chain state of an account
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AccountInfo {
+    pub lamports: u64,
+    /// program that owns the account, e.g. the token program for mints and token accounts
+    pub owner: String,
+    pub executable: bool,
+    pub rent_epoch: u64,
+    pub data: Vec<u8>,
+}
+
+/// execution price of a trade, see `TransactionInfo::get_price`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Price {
+    pub base_mint: String,
+    /// SOL for native SOL quoted trades
+    pub quote_mint: String,
+    /// quote tokens paid or received per 1 whole base token, both in ui units (decimals applied)
+    pub price: f64,
+    pub quote_decimals: u8,
+}
+
+/// payments between two addresses X and Y, see `Trade::get_flow_between`
+#[derive(Debug, Clone, Default)]
+pub struct FlowSummary {
+    /// total paid by X to Y
+    pub x_to_y: u64,
+    /// total paid by Y to X
+    pub y_to_x: u64,
+    /// `x_to_y - y_to_x`, positive when X sent more than it received
+    pub net_flow: i128,
+    pub x_to_y_transactions:
+        Vec<solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature>,
+    pub y_to_x_transactions:
+        Vec<solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature>,
+}
+
+/// signatures read from an address history, newest first
+/// scans stop at the `max_signatures` cap of `Trade`, see `Trade::with_max_signatures`
+#[derive(Debug, Clone, Default)]
+pub struct SignatureHistory {
+    pub signatures: Vec<solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature>,
+    /// the scan stopped at the cap, older signatures may exist that were not read
+    pub truncated: bool,
+}
+
+impl std::ops::Deref for SignatureHistory {
+    type Target = Vec<solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.signatures
+    }
+}
+
+impl IntoIterator for SignatureHistory {
+    type Item = solana_client::rpc_response::RpcConfirmedTransactionStatusWithSignature;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.signatures.into_iter()
+    }
+}
+
+/// transactions and slots processed by the cluster over a sample period
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PerfSample {
+    pub slot: u64,
+    pub num_transactions: u64,
+    pub num_slots: u64,
+    pub sample_period_secs: u16,
+}
+
+impl PerfSample {
+    /// transactions per second over the sample period, 0.0 for an empty period
+    pub fn tps(&self) -> f64 {
+        if self.sample_period_secs == 0 {
+            return 0.0;
+        }
+        self.num_transactions as f64 / self.sample_period_secs as f64
+    }
+}
+
+/// inflation of an epoch, as fractions per year (0.05 = 5%)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InflationRate {
+    pub total: f64,
+    pub validator: f64,
+    pub foundation: f64,
+    pub epoch: u64,
+}
+
+/// SOL supply in lamports
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolSupply {
+    pub total: u64,
+    pub circulating: u64,
+    pub non_circulating: u64,
+}
+
+/// activation state of a stake account's delegation
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StakeActivationState {
+    Activating,
+    Active,
+    Deactivating,
+    Inactive,
+}
+
+/// activation of a stake account at the current epoch
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StakeActivation {
+    pub state: StakeActivationState,
+    pub active: u64,   // effective stake in lamports
+    pub inactive: u64, // lamports not yet (or no longer) staking, rent-exempt reserve excluded
+}
+
+/// a validator's vote account, see `Solana::get_vote_accounts`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VoteAccount {
+    pub vote_pubkey: String,
+    /// identity of the validator node
+    pub node_pubkey: String,
+    /// stake delegated to the vote account and active in the current epoch, in lamports
+    pub activated_stake: u64,
+    /// percentage (0-100) of rewards kept by the validator
+    pub commission: u8,
+    pub last_vote: u64,
+    pub root_slot: u64,
+    /// whether the account is staked for the current epoch
+    pub epoch_vote_account: bool,
+}
+
+/// current and delinquent vote accounts of the cluster
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VoteAccounts {
+    /// sorted by activated stake, largest first
+    pub current: Vec<VoteAccount>,
+    pub delinquent: Vec<VoteAccount>,
+}
+
+/// leader slots and produced blocks per validator identity, see `Solana::get_block_production`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockProduction {
+    /// identity -> (leader slots, blocks produced)
+    pub by_identity: HashMap<String, (usize, usize)>,
+    /// first and last slot covered (inclusive)
+    pub first_slot: u64,
+    pub last_slot: u64,
+}
+
+impl BlockProduction {
+    /// share (0.0-1.0) of its leader slots the validator skipped, None when it had no leader slot
+    pub fn skip_rate(&self, identity: &str) -> Option<f64> {
+        let (leader_slots, produced) = self.by_identity.get(identity)?;
+        (*leader_slots > 0)
+            .then(|| leader_slots.saturating_sub(*produced) as f64 / *leader_slots as f64)
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Direction {
     Buy,
@@ -26,10 +381,26 @@ pub enum DexProgramType {
     PumpBondCurve,
     PumpAAM,
     Raydium,
-    Meteora,
+    MeteoraDlmm,   // dynamic liquidity market maker (bins)
+    MeteoraDammV1, // dynamic amm, constant product
+    MeteoraDammV2, // cp-amm, concentrated around a price range
+    MeteoraDbc,    // dynamic bonding curve
     Orca,
 }
 
+impl DexProgramType {
+    /// any of the meteora programs
+    pub fn is_meteora(&self) -> bool {
+        matches!(
+            self,
+            DexProgramType::MeteoraDlmm
+                | DexProgramType::MeteoraDammV1
+                | DexProgramType::MeteoraDammV2
+                | DexProgramType::MeteoraDbc
+        )
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum DexPoolType {
     PumpBondCurve,
@@ -44,6 +415,10 @@ pub enum TransactionType {
     Swap,
     AddLiquidity,
     RemoveLiquidity,
+    OpenPosition,
+    ClosePosition,
+    PoolCreate,
+    Migration,
     Binary,
     Other,
     Raw,
@@ -51,3 +426,65 @@ pub enum TransactionType {
     NFTTransfer,
     Transfer,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_trade_error_into_string() {
+        let e = TradeError::InvalidAddress("abc".to_string());
+        assert_eq!(String::from(e.clone()), "invalid address: abc");
+        let boxed: Box<dyn std::error::Error> = Box::new(e);
+        assert_eq!(boxed.to_string(), "invalid address: abc");
+        assert_eq!(String::from(TradeError::Empty), "empty result");
+    }
+
+    #[test]
+    fn test_mode_predicates() {
+        assert!(Mode::MAIN.is_mainnet());
+        assert!(!Mode::MAIN.is_test());
+        assert!(!Mode::TEST.is_mainnet());
+        assert!(Mode::TEST.is_test());
+        assert!(!Mode::DEV.is_mainnet());
+        assert!(Mode::DEV.is_test());
+        assert!(!Mode::LOCALNET.is_mainnet());
+        assert!(!Mode::LOCALNET.is_test());
+        assert!(Mode::LOCALNET.is_local());
+    }
+
+    #[test]
+    fn test_mode_from_str() {
+        for (input, mode) in [
+            ("main", Mode::MAIN),
+            ("Mainnet", Mode::MAIN),
+            ("mainnet-beta", Mode::MAIN),
+            ("m", Mode::MAIN),
+            ("dev", Mode::DEV),
+            ("devnet", Mode::DEV),
+            ("d", Mode::DEV),
+            ("test", Mode::TEST),
+            (" testnet ", Mode::TEST),
+            ("t", Mode::TEST),
+            ("localnet", Mode::LOCALNET),
+        ] {
+            assert_eq!(input.parse::<Mode>(), Ok(mode));
+        }
+        for mode in [Mode::MAIN, Mode::TEST, Mode::DEV, Mode::LOCALNET] {
+            assert_eq!(mode.to_string().parse::<Mode>(), Ok(mode));
+        }
+        assert!("moonnet".parse::<Mode>().unwrap_err().contains("moonnet"));
+    }
+
+    #[test]
+    fn test_mode_urls() {
+        use crate::global::SOLANA_LOCAL_NET_WS_URL;
+        assert_eq!(Mode::LOCALNET.url(), SOLANA_LOCAL_NET_URL);
+        assert_eq!(Mode::LOCALNET.websocket_url(), SOLANA_LOCAL_NET_WS_URL);
+        assert_eq!(Mode::DEV.url(), SOLANA_DEV_NET_URL);
+        assert_eq!(
+            Mode::MAIN.websocket_url(),
+            "wss://api.mainnet-beta.solana.com"
+        );
+    }
+}
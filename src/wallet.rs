@@ -16,7 +16,7 @@ impl Wallet {
     /// # Returns
     /// wallet object
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let w: Wallet = Wallet::create_new_wallet();
     /// ```
     pub fn create_new_wallet() -> Wallet {
@@ -37,7 +37,7 @@ impl Wallet {
     /// # private_key
     /// * 64 bytes private key
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let w = Wallet::from_private_key_64("64 bytes private");
     /// ```
     pub fn from_private_key_64(private_key: &str) -> Wallet {
@@ -54,7 +54,7 @@ impl Wallet {
     /// # private_key
     /// * 64 bytes private key
     /// # Example
-    /// ```rust
+    /// ```ignore
     /// let w = Wallet::from_private_key_64("32 bytes private");
     /// ```
     pub fn from_private_key_32(private_key: &str) -> Result<Wallet, String> {